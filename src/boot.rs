@@ -0,0 +1,353 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: Boot
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Decodes and encodes load options (Boot####, Driver####, SysPrep####)
+// and their order lists, as stored in the UEFI global variables
+
+// Note: this module operates on plain bytes and strings only and has no
+// dependencies on the UEFI environment, so that it can also be used on a host
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+// Load option attributes
+pub const LOAD_OPTION_ACTIVE: u32 = 0x00000001;  // Considered by the boot manager
+
+// Device path node types marking the end of the path
+pub const DEVICE_PATH_END_TYPE:    u8 = 0x7F;  // End of hardware device path
+pub const DEVICE_PATH_END_ENTIRE:  u8 = 0xFF;  // End of entire device path
+pub const DEVICE_PATH_HEADER_SIZE: usize = 4;  // Type, subtype and length
+
+// Load option type
+// Determines the variable names, and the order variable
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LoadOptionType {
+
+    #[default]
+    Boot,     // Boot####, BootOrder
+    Driver,   // Driver####, DriverOrder
+    SysPrep,  // SysPrep####, SysPrepOrder
+
+}
+
+// Implementation
+impl LoadOptionType {
+
+    // All the types, in the order they are listed
+    pub const ALL: [Self; 3] = [Self::Boot, Self::Driver, Self::SysPrep];
+
+    // Variable name prefix
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Boot => "Boot",
+            Self::Driver => "Driver",
+            Self::SysPrep => "SysPrep",
+        }
+    }
+
+    // Name of the variable holding the order
+    pub fn order_name(self) -> String {
+        format!("{}Order", self.prefix())
+    }
+
+}
+
+// Load option identifier
+// Type and number, which together make up the variable name
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadOptionId {
+    pub kind: LoadOptionType,  // Boot, driver or system preparation
+    pub number: u16,           // Four hexadecimal digits in the name
+}
+
+// Implementation
+impl LoadOptionId {
+
+    // Parses user input such as "0003", "3" or "Driver0001",
+    // the type defaults to boot if the prefix is absent
+    pub fn parse(string: &str) -> Option<Self> {
+
+        // Determine the type from the prefix, if any
+        let (kind, digits) = LoadOptionType::ALL.iter()
+            .find_map(|&kind| string.strip_prefix(kind.prefix()).map(|d| (kind, d)))
+            .unwrap_or((LoadOptionType::Boot, string));
+
+        // Up to four hexadecimal digits follow
+        Some(Self { kind, number: parse_number(digits)? })
+
+    }
+
+    // Parses a variable name, which must follow the
+    // exact format with four uppercase hexadecimal digits
+    pub fn from_variable_name(name: &str) -> Option<Self> {
+
+        // Retrieve the type from the prefix
+        let (kind, digits) = LoadOptionType::ALL.iter()
+            .find_map(|&kind| name.strip_prefix(kind.prefix()).map(|d| (kind, d)))?;
+
+        // Check the digits strictly
+        if digits.len() != 4
+            || !digits.chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()) {
+            return None;
+        }
+
+        // Return the identifier
+        Some(Self { kind, number: parse_number(digits)? })
+
+    }
+
+}
+
+// Implementation: formatting as the variable name
+impl Display for LoadOptionId {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}{:04X}", self.kind.prefix(), self.number)
+    }
+
+}
+
+// Load option
+// Contents of a Boot####, Driver#### or SysPrep#### variable (EFI_LOAD_OPTION)
+
+#[derive(Clone, Debug, Default)]
+pub struct LoadOption {
+    pub attributes: u32,         // Active, hidden, category and so on
+    pub description: String,     // Human-readable name
+    pub file_path: Vec<u8>,      // Device path list, as stored
+    pub optional_data: Vec<u8>,  // Passed to the loaded image
+}
+
+// Implementation
+impl LoadOption {
+
+    // Decodes a load option from the variable contents
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+
+        // Fixed-size header
+        let attributes = u32::from_le_bytes(bytes.get(0 .. 4)?.try_into().ok()?);
+        let file_path_length = u16::from_le_bytes(bytes.get(4 .. 6)?.try_into().ok()?) as usize;
+
+        // Description, a null-terminated UCS-2 string,
+        // which has to have the terminating null
+        let description = bytes.get(6 ..)?.chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&c| c != 0)
+            .collect::<Vec<_>>();
+        let file_path_start = 6 + (description.len() + 1) * 2;
+        if bytes.get(file_path_start - 2 .. file_path_start)? != [0, 0] {
+            return None;
+        }
+
+        // Device path list, followed by the optional data
+        let file_path_end = file_path_start + file_path_length;
+        let file_path = bytes.get(file_path_start .. file_path_end)?.to_vec();
+        let optional_data = bytes.get(file_path_end ..)?.to_vec();
+
+        // Return the result
+        Some(Self { attributes,
+            description: char::decode_utf16(description)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect(),
+            file_path, optional_data })
+
+    }
+
+    // Encodes the load option as the variable contents
+    pub fn to_bytes(&self) -> Vec<u8> {
+
+        // Fixed-size header
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.attributes.to_le_bytes());
+        bytes.extend_from_slice(&(self.file_path.len() as u16).to_le_bytes());
+
+        // Description with the terminating null
+        bytes.extend(self.description.encode_utf16().chain([0])
+            .flat_map(u16::to_le_bytes));
+
+        // Device path list and the optional data
+        bytes.extend_from_slice(&self.file_path);
+        bytes.extend_from_slice(&self.optional_data);
+
+        // Return the result
+        bytes
+
+    }
+
+    // Whether the boot manager considers the load option
+    pub fn is_active(&self) -> bool {
+        self.attributes & LOAD_OPTION_ACTIVE != 0
+    }
+
+}
+
+// Public Functions
+
+// Determines the length of the first device path in a list,
+// returns nothing if the path is malformed or runs out of bounds
+pub fn device_path_length(bytes: &[u8]) -> Option<usize> {
+
+    // Walk the nodes until the end of the entire path
+    let mut offset = 0;
+    loop {
+
+        // Each node begins with a header holding its length
+        let header = bytes.get(offset .. offset + DEVICE_PATH_HEADER_SIZE)?;
+        let length = u16::from_le_bytes([header[2], header[3]]) as usize;
+        if length < DEVICE_PATH_HEADER_SIZE || offset + length > bytes.len() {
+            return None;
+        }
+        offset += length;
+
+        // Stop at the end node
+        if header[0] == DEVICE_PATH_END_TYPE && header[1] == DEVICE_PATH_END_ENTIRE {
+            return Some(offset);
+        }
+
+    }
+
+}
+
+// Decodes an order variable (BootOrder and the like),
+// returns nothing if it does not hold whole numbers
+pub fn parse_order(bytes: &[u8]) -> Option<Vec<u16>> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    Some(bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect())
+}
+
+// Formats an order variable (BootOrder and the like) for display
+pub fn format_order(order: &[u16]) -> String {
+    order.iter().map(|n| format!("{n:04X}")).collect::<Vec<_>>().join(",")
+}
+
+// Encodes an order variable (BootOrder and the like)
+pub fn order_to_bytes(order: &[u16]) -> Vec<u8> {
+    order.iter().flat_map(|n| n.to_le_bytes()).collect()
+}
+
+// Private Functions
+
+// Parses up to four hexadecimal digits
+fn parse_number(digits: &str) -> Option<u16> {
+    if digits.is_empty() || digits.len() > 4
+        || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u16::from_str_radix(digits, 16).ok()
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Constructed load option, see fixtures/README.md
+    const BOOT: &[u8] = include_bytes!("../fixtures/boot/boot0000.bin");
+
+    // Where the device path list and the optional data start in it
+    const FILE_PATH: usize = 0x30;
+    const OPTIONAL_DATA: usize = 0xA4;
+
+    // Decodes a load option with optional data, and encodes it back the same
+    #[test]
+    fn load_option() {
+
+        let option = LoadOption::from_bytes(BOOT).unwrap();
+        assert!(option.is_active());
+        assert_eq!(option.description, "Windows Boot Manager");
+        assert_eq!(option.file_path, &BOOT[FILE_PATH .. OPTIONAL_DATA]);
+        assert_eq!(device_path_length(&option.file_path), Some(option.file_path.len()));
+        assert_eq!(option.optional_data, &BOOT[OPTIONAL_DATA ..]);
+        assert!(option.optional_data.starts_with(b"WINDOWS\0"));
+
+        // Encoded back to the same bytes, also without the optional data
+        assert_eq!(option.to_bytes(), BOOT);
+        let option = LoadOption { optional_data: Vec::new(), ..option };
+        assert_eq!(LoadOption::from_bytes(&option.to_bytes()).unwrap().file_path, option.file_path);
+
+    }
+
+    // Rejects a load option cut short, or with the description not terminated
+    #[test]
+    fn load_option_malformed() {
+
+        // Device path list running past the end
+        assert!(LoadOption::from_bytes(&BOOT[.. OPTIONAL_DATA - 1]).is_none());
+        assert!(LoadOption::from_bytes(&BOOT[.. 5]).is_none());
+
+        // Description without the terminating null, whether the bytes
+        // end with it or right after the last character, or past that
+        let mut bytes = Vec::from([0x01, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        bytes.extend("Boot".encode_utf16().flat_map(u16::to_le_bytes));
+        assert!(LoadOption::from_bytes(&bytes).is_none());
+        bytes.push(0);
+        assert!(LoadOption::from_bytes(&bytes).is_none());
+        bytes.push(0);
+        assert_eq!(LoadOption::from_bytes(&bytes).unwrap().description, "Boot");
+
+    }
+
+    // Measures the first device path in a list, rejecting the nodes
+    // too short to hold their own header, or running past the end
+    #[test]
+    fn device_paths() {
+
+        let path = &BOOT[FILE_PATH .. OPTIONAL_DATA];
+        let end = [DEVICE_PATH_END_TYPE, DEVICE_PATH_END_ENTIRE, 0x04, 0x00];
+        assert_eq!(device_path_length(&end), Some(4));
+        assert_eq!(device_path_length(&[path, path].concat()), Some(path.len()));
+
+        // Malformed or missing nodes
+        for bytes in [&[0x04, 0x01, 0x03, 0x00, 0x7F, 0xFF, 0x04, 0x00][..], &[0x04, 0x01, 0x00, 0x00],
+            &[0x04, 0x04, 0x08, 0x00, 0x7F, 0xFF, 0x04, 0x00], &end[.. 3], &path[.. path.len() - 4], &[]] {
+            assert_eq!(device_path_length(bytes), None, "{bytes:02X?}");
+        }
+
+    }
+
+    // Decodes and encodes the order, which has to hold whole numbers
+    #[test]
+    fn order() {
+
+        let bytes = [0x03, 0x00, 0x00, 0x00, 0x0A, 0x10];
+        let order = parse_order(&bytes).unwrap();
+        assert_eq!(order, [0x0003, 0x0000, 0x100A]);
+        assert_eq!(format_order(&order), "0003,0000,100A");
+        assert_eq!(order_to_bytes(&order), bytes);
+        assert_eq!(parse_order(&[]), Some(Vec::new()));
+
+        // Odd length
+        assert_eq!(parse_order(&bytes[.. 5]), None);
+        assert_eq!(parse_order(&[0x01]), None);
+
+    }
+
+    // Parses the identifiers given by the user, and the variable names strictly
+    #[test]
+    fn identifiers() {
+
+        let id = |kind, number| Some(LoadOptionId { kind, number });
+        assert_eq!(LoadOptionId::parse("0003"), id(LoadOptionType::Boot, 3));
+        assert_eq!(LoadOptionId::parse("a"), id(LoadOptionType::Boot, 0x0A));
+        assert_eq!(LoadOptionId::parse("Driver0001"), id(LoadOptionType::Driver, 1));
+        for string in ["", "12345", "Boot", "00G0"] {
+            assert_eq!(LoadOptionId::parse(string), None, "{string}");
+        }
+
+        assert_eq!(LoadOptionId::from_variable_name("SysPrep000A"), id(LoadOptionType::SysPrep, 0x0A));
+        assert_eq!(LoadOptionId::from_variable_name("Boot000a"), None);
+        assert_eq!(LoadOptionId::from_variable_name("Boot01"), None);
+        assert_eq!(LoadOptionId::from_variable_name("BootOrder"), None);
+        assert_eq!(id(LoadOptionType::Driver, 0x1F).unwrap().to_string(), "Driver001F");
+
+    }
+
+}
@@ -20,24 +20,125 @@ pub const BUILD_TYPE:  Option<&str> = option_env!("BUILD_TYPE");
 pub const CHAR_ARG_ASS:       char = '=';         // Argument assignment operator
 pub const CHAR_ARG_BKT_L:     char = '(';         // Opening bracket for optional variable identifier or size
 pub const CHAR_ARG_BKT_R:     char = ')';         // Closing bracket for optional variable identifier or size
+pub const CHAR_ARG_GUID_L:    char = '{';         // Opening brace for optional variable vendor GUID
+pub const CHAR_ARG_GUID_R:    char = '}';         // Closing brace for optional variable vendor GUID
+pub const CHAR_ARG_HII:       char = '~';         // Operation prefix to route through HII configuration
 pub const CHAR_ARG_OPT:       char = '-';         // Argument option prefix
 pub const CHAR_ARG_POS:       char = ':';         // Argument offset indicator for variables
 pub const CHAR_ARG_SEP:       char = ' ';         // Argument separator
+pub const CHAR_BOOT_SEP:      char = ',';         // Boot entry command value separator
 pub const CHAR_BLANK_SPACE:   char = ' ';         // Space (SP) whitespace character
 pub const CHAR_BLANK_TAB:     char = '\t';        // Horizontal tabulation (HT) whitespace character
 pub const CHAR_FILE_EXT:      char = '.';         // File extension separator
+pub const CHAR_FILE_FS:       char = ':';         // File system name terminator, such as in fs0:
 pub const CHAR_FILE_PATH:     char = '\\';        // File path separator (single backlash, escaped)
 pub const CHAR_INPUT_COMMENT: char = '#';         // Comment prefix, rest of the line is ignored
 pub const CHAR_INPUT_DEF:     char = ',';         // Input definition separator
+pub const CHAR_INPUT_ENUM:    char = ',';         // Separates the names of the values of a definition
+pub const CHAR_INPUT_ENUM_L:  char = '{';         // Opening brace for the names of the values of a definition
+pub const CHAR_INPUT_ENUM_R:  char = '}';         // Closing brace for the names of the values of a definition
+pub const CHAR_INPUT_FIELD:   char = '.';         // Selects a field of a structure in a reference
+pub const CHAR_INPUT_INDEX_L: char = '[';         // Opening bracket for the number of elements, or the index of one
+pub const CHAR_INPUT_INDEX_R: char = ']';         // Closing bracket for the number of elements, or the index of one
 pub const CHAR_INPUT_OPT:     char = '!';         // Input option prefix
 pub const CHAR_INPUT_REF:     char = '@';         // Input reference prefix
 pub const CHAR_CTL_BOM:       char = '\u{FEFF}';  // Byte Order Mark (BOM) control character
+pub const CHAR_CTL_BS:        char = '\u{8}';     // Backspace (BS) control character
 pub const CHAR_CTL_CR:        char = '\r';        // Carriage Return (CR) control character
 pub const CHAR_CTL_LF:        char = '\n';        // Line Feed (LF) control character
+pub const CHAR_EXPR_NAME:     char = '_';         // Allowed in constant names besides letters and digits
+pub const CHAR_COND_GUID_L:   char = '{';         // Opening brace for the vendor GUID of a variable in conditions
+pub const CHAR_COND_GUID_R:   char = '}';         // Closing brace for the vendor GUID of a variable in conditions
+pub const CHAR_COND_QUOTE:    char = '"';         // Encloses text in conditions, such as a vendor name
+pub const CHAR_COND_VERSION:  char = '.';         // Version number separator in conditions
+
+// Condition operators, in conditional blocks and requirements
+pub const COND_AND:     &str = "and";      // Both hold, binds tighter than or
+pub const COND_DEFINED: &str = "defined";  // Followed by a definition name, holds if made before
+pub const COND_EQ:      &str = "=";        // Equal
+pub const COND_EQ_ALT:  &str = "==";       // Equal, same as COND_EQ
+pub const COND_EXISTS:  &str = "exists";   // Followed by a variable name, holds if it is there
+pub const COND_GE:      &str = ">=";       // Greater or equal
+pub const COND_GT:      &str = ">";        // Greater
+pub const COND_LE:      &str = "<=";       // Less or equal
+pub const COND_LT:      &str = "<";        // Less
+pub const COND_NE:      &str = "!=";       // Not equal
+pub const COND_NOT:     &str = "not";      // Does not hold
+pub const COND_OR:      &str = "or";       // Either holds
+
+// Condition properties, describing the system
+pub const COND_KEY_BIOS:         &str = "bios";          // BIOS version (SMBIOS), such as 1402
+pub const COND_KEY_BIOS_DATE:    &str = "bios_date";     // BIOS release date (SMBIOS), such as 09/12/2023
+pub const COND_KEY_BOARD:        &str = "board";         // Baseboard product name (SMBIOS)
+pub const COND_KEY_MANUFACTURER: &str = "manufacturer";  // System manufacturer (SMBIOS)
+pub const COND_KEY_PRODUCT:      &str = "product";       // System product name (SMBIOS)
+pub const COND_KEY_REVISION:     &str = "revision";      // Firmware revision, such as 5.24
+pub const COND_KEY_UEFI:         &str = "uefi";          // UEFI specification revision, such as 2.8
+pub const COND_KEY_VENDOR:       &str = "vendor";        // Firmware vendor, such as "American Megatrends"
+
+// Expression operators, in offsets, sizes and values
+pub const EXPR_ADD: &str = "+";   // Addition
+pub const EXPR_AND: &str = "&";   // Bitwise and
+pub const EXPR_MUL: &str = "*";   // Multiplication
+pub const EXPR_OR:  &str = "|";   // Bitwise or
+pub const EXPR_SHL: &str = "<<";  // Shift left
+pub const EXPR_SHR: &str = ">>";  // Shift right
+pub const EXPR_SUB: &str = "-";   // Subtraction
+
+// Boot manager variables (EFI_GLOBAL_VARIABLE vendor GUID)
+pub const VAR_BOOT_CURRENT: &str = "BootCurrent";  // Entry used for the current boot
+pub const VAR_BOOT_NEXT:    &str = "BootNext";     // Entry to use for the next boot only
+
+// Secure Boot state variables (EFI_GLOBAL_VARIABLE vendor GUID), each a single byte
+pub const VAR_SECURE_BOOT_STATE: [&str; 4] = ["SecureBoot", "SetupMode", "AuditMode", "DeployedMode"];
+
+// Secure Boot signature databases
+pub const VAR_SIGLIST_PK:       &str = "PK";       // Platform key (EFI_GLOBAL_VARIABLE)
+pub const VAR_SIGLIST_KEK:      &str = "KEK";      // Key exchange keys (EFI_GLOBAL_VARIABLE)
+pub const VAR_SIGLIST_DB:       &str = "db";       // Allowed (EFI_IMAGE_SECURITY_DATABASE)
+pub const VAR_SIGLIST_DBX:      &str = "dbx";      // Forbidden (EFI_IMAGE_SECURITY_DATABASE)
+pub const VAR_SIGLIST_DBT:      &str = "dbt";      // Timestamps (EFI_IMAGE_SECURITY_DATABASE)
+pub const VAR_SIGLIST_MOK_LIST: &str = "MokList";  // Machine owner keys (shim)
+pub const VAR_SIGLISTS: [&str; 6] = [VAR_SIGLIST_PK, VAR_SIGLIST_KEK,
+    VAR_SIGLIST_DB, VAR_SIGLIST_DBX, VAR_SIGLIST_DBT, VAR_SIGLIST_MOK_LIST];
+
+// Secure Boot signature databases accepting authenticated updates
+pub const VAR_SIGLISTS_AUTH: [&str; 5] = [VAR_SIGLIST_PK, VAR_SIGLIST_KEK,
+    VAR_SIGLIST_DB, VAR_SIGLIST_DBX, VAR_SIGLIST_DBT];
+
+// Platform language (EFI_GLOBAL_VARIABLE vendor GUID), such as en-US
+pub const VAR_PLATFORM_LANG: &str = "PlatformLang";
+pub const HII_LANG_DEFAULT:  &str = "en-US";  // Used if not set
+
+// Operating system indications (OsIndications and OsIndicationsSupported)
+pub const VAR_OS_INDICATIONS:           &str = "OsIndications";
+pub const VAR_OS_INDICATIONS_SUPPORTED: &str = "OsIndicationsSupported";
+pub const OS_INDICATIONS_BOOT_TO_FW_UI: u64 = 0x0000000000000001;  // Stop in firmware setup
+
+// Variable attributes, abbreviated and joined by this when described
+pub const VAR_ATTR_SEP: &str = "+";
+
+// Value size limit
+pub const VALUE_SIZE_MAX: usize = 8;  // Bytes that fit in a single operation (64 bits)
 
 // Command-line options
+pub const OPT_ARG_BOOT:          &str = "-b";
+pub const OPT_ARG_CONFIG:        &str = "-c";
+pub const OPT_ARG_CONFIG_LONG:   &str = "--config";
+pub const OPT_ARG_ENCODING:      &str = "-e";  // Followed by an encoding name
+pub const OPT_ARG_ENCODING_LONG: &str = "--encoding";
 pub const OPT_ARG_FORCE:         &str = "-f";
 pub const OPT_ARG_FORCE_LONG:    &str = "--force";
+pub const OPT_ARG_FORMAT_LONG:   &str = "--format";  // Followed by a format name
+pub const OPT_ARG_HII:           &str = "-v";
+pub const OPT_ARG_HII_LONG:      &str = "--via-hii";
+pub const OPT_ARG_INPUT:         &str = "-i";  // Followed by a file path
+pub const OPT_ARG_INPUT_LONG:    &str = "--input";
+pub const OPT_ARG_LONG:          &str = "--";  // Prefix for boot entry commands
+pub const OPT_ARG_OUTPUT:        &str = "-o";  // Followed by a file path
+pub const OPT_ARG_OUTPUT_LONG:   &str = "--output";
+pub const OPT_ARG_QUIET:         &str = "-q";
+pub const OPT_ARG_QUIET_LONG:    &str = "--quiet";
 pub const OPT_ARG_RESTART:       &str = "-r";
 pub const OPT_ARG_RESTART_LONG:  &str = "--restart";
 pub const OPT_ARG_SIMULATE:      &str = "-s";
@@ -45,7 +146,124 @@ pub const OPT_ARG_SIMULATE_LONG: &str = "--simulate";
 pub const OPT_ARG_USAGE:         &str = "-h";
 pub const OPT_ARG_USAGE_LONG:    &str = "--help";
 
+// Boot entry commands (prefixed with OPT_ARG_LONG or CHAR_INPUT_OPT)
+pub const OPT_BOOT_CREATE:  &str = "boot-create";   // Add a load option
+pub const OPT_BOOT_DELETE:  &str = "boot-delete";   // Remove a load option
+pub const OPT_BOOT_DISABLE: &str = "boot-disable";  // Clear the active attribute
+pub const OPT_BOOT_EDIT:    &str = "edit";          // Open a variable in the editor
+pub const OPT_BOOT_ENABLE:  &str = "boot-enable";   // Set the active attribute
+pub const OPT_BOOT_LIST:    &str = "boot";          // List load options
+pub const OPT_BOOT_NEXT:    &str = "boot-next";     // Boot once from a given entry
+pub const OPT_BOOT_ORDER:   &str = "boot-order";    // Move entries to the front
+pub const OPT_BOOT_SECURE:  &str = "secure-boot";   // Show Secure Boot state and keys
+pub const OPT_BOOT_SECURE_APPEND: &str = "secure-boot-append";  // Append a signed update
+pub const OPT_BOOT_SECURE_WRITE:  &str = "secure-boot-write";   // Write a signed update
+
+// Output file encodings (following CHAR_ARG_ASS after the encoding option)
+pub const OPT_ENCODING_UTF8:    &str = "utf8";     // UTF-8, the default
+pub const OPT_ENCODING_UTF16:   &str = "utf16";    // UTF-16 LE with BOM, as the shell
+pub const OPT_ENCODING_UTF16BE: &str = "utf16be";  // UTF-16 BE with BOM
+
+// Output formats (following CHAR_ARG_ASS after the format option)
+pub const OPT_FORMAT_JSON: &str = "json";  // A single JSON document
+pub const OPT_FORMAT_TEXT: &str = "text";  // Lines in the input format, the default
+
+// Restart types (following CHAR_ARG_ASS after the restart option)
+pub const OPT_RESTART_COLD:     &str = "cold";      // Cold reset, power cycle
+pub const OPT_RESTART_PLATFORM: &str = "platform";  // Platform-specific reset
+pub const OPT_RESTART_SETUP:    &str = "setup";     // Reboot into firmware setup (UI)
+pub const OPT_RESTART_SHUTDOWN: &str = "shutdown";  // Power off
+pub const OPT_RESTART_WARM:     &str = "warm";      // Warm reset, the default
+
 // Input options (prefixed with CHAR_INPUT_OPT)
+pub const OPT_INPUT_CONFIG:   &str = "config";
+pub const OPT_INPUT_ELSE:     &str = "else";     // Starts the lines used if the condition does not hold
+pub const OPT_INPUT_ENDIF:    &str = "endif";    // Ends a conditional block
 pub const OPT_INPUT_FORCE:    &str = "force";
+pub const OPT_INPUT_HII:      &str = "via-hii";
+pub const OPT_INPUT_IF:       &str = "if";       // Followed by a condition, starts a conditional block
+pub const OPT_INPUT_IFDEF:    &str = "ifdef";    // Followed by a definition name, same as OPT_INPUT_IF with COND_DEFINED
+pub const OPT_INPUT_INCLUDE:  &str = "include";  // Followed by a file path
+pub const OPT_INPUT_PARAM:    &str = "param";    // Same as OPT_INPUT_SET, can be overridden
+pub const OPT_INPUT_REQUIRE:  &str = "require";  // Followed by a condition, stops if it does not hold
 pub const OPT_INPUT_RESTART:  &str = "restart";
+pub const OPT_INPUT_SET:      &str = "set";      // Followed by a constant name and value
 pub const OPT_INPUT_SIMULATE: &str = "simulate";
+pub const OPT_INPUT_STRUCT:   &str = "struct";   // Followed by a structure name and its fields in braces
+
+// Input keywords
+pub const INPUT_DEF_AS: &str = "as";  // Binds a definition to a structure, or makes it an array
+
+// JSON output
+pub const JSON_NULL:           &str = "null";     // Absent value
+pub const JSON_STATUS_FAILED:  &str = "failed";   // Operation could not be performed
+pub const JSON_STATUS_READ:    &str = "read";     // Value retrieved
+pub const JSON_STATUS_SKIPPED: &str = "skipped";  // Value already set, not written
+pub const JSON_STATUS_WRITTEN: &str = "written";  // Value written (or simulated)
+
+// Input files
+pub const INPUT_ERROR_INDENT:      &str = "  ";       // Before the line shown with an error
+pub const INPUT_ERROR_MARKER:      char = '^';        // Under the column where the error is
+pub const INPUT_INCLUDE_DEPTH_MAX: usize = 16;        // Files included within one another
+pub const INPUT_SOURCE_STDIN:      &str = "<stdin>";  // Name of the standard input in errors
+
+// Interactive mode commands (entered at the prompt instead of input)
+pub const CMD_INTERACTIVE_DUMP: &str = "dump";  // Show the contents of a variable
+pub const CMD_INTERACTIVE_EDIT: &str = "edit";  // Open a variable in the editor
+pub const CMD_INTERACTIVE_EXIT: &str = "exit";  // End the session
+pub const CMD_INTERACTIVE_HELP: &str = "help";  // Show the available commands
+pub const CMD_INTERACTIVE_LIST: &str = "list";  // Show the definitions
+pub const CMD_INTERACTIVE_QUIT: &str = "quit";  // End the session (same as exit)
+
+// Interactive mode settings
+pub const INTERACTIVE_HISTORY_MAX:   usize = 64;      // Lines kept in history
+pub const INTERACTIVE_PROMPT:        &str = "uvt> ";  // Shown before each line
+pub const INTERACTIVE_WATCHDOG_CODE: u64 = 0x10000;   // First code not reserved for firmware
+
+// Keyboard input
+pub const KEY_POLL_INTERVAL: usize = 10_000;  // Microseconds between keyboard checks
+
+// Variable contents dump and editor
+pub const DUMP_LINE_SIZE:   usize = 16;  // Bytes shown on each line of a dump
+pub const EDITOR_LINE_SIZE: usize = 16;  // Bytes shown on each line in the editor
+
+// Host-side tool (uvt-host) commands and options
+#[cfg(feature = "host")]
+pub mod host {
+
+    // Commands
+    pub const CMD_IFR:     &str = "ifr";
+    pub const CMD_SIGLIST: &str = "siglist";
+    pub const CMD_SIGN:    &str = "sign";
+    pub const CMD_SMBIOS:  &str = "smbios";
+
+    // Size of the attributes preceding the contents in variable files (efivarfs)
+    pub const EFIVARFS_ATTR_SIZE: usize = 4;
+
+    // Options
+    pub const OPT_APPEND:      &str = "-a";
+    pub const OPT_APPEND_LONG: &str = "--append";
+    pub const OPT_CERT:        &str = "-c";
+    pub const OPT_CERT_LONG:   &str = "--cert";
+    pub const OPT_HASH:        &str = "-x";
+    pub const OPT_HASH_LONG:   &str = "--hash";
+    pub const OPT_KEY:         &str = "-k";
+    pub const OPT_KEY_LONG:    &str = "--key";
+    pub const OPT_LANG:        &str = "-l";
+    pub const OPT_LANG_LONG:   &str = "--lang";
+    pub const OPT_OWNER:       &str = "-g";
+    pub const OPT_OWNER_LONG:  &str = "--owner";
+    pub const OPT_SIGNER:      &str = "-s";
+    pub const OPT_SIGNER_LONG: &str = "--signer";
+    pub const OPT_TIME:        &str = "-t";
+    pub const OPT_TIME_LONG:   &str = "--time";
+    pub const OPT_USAGE:       &str = "-h";
+    pub const OPT_USAGE_LONG:  &str = "--help";
+
+    // Defaults
+    pub const DEFAULT_LANG: &str = "en-US";  // Language of the extracted strings
+
+    // Line break written to output files
+    pub const LINE_BREAK: &str = "\r\n";
+
+}
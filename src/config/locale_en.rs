@@ -17,36 +17,95 @@ pub const BUILD_TYPE: &str = "Manual";
 
 // Error message prefixes and suffixes
 pub const ERR_PREFIX_ARG: &str = "Argument error";
+pub const ERR_PREFIX_BOOT: &str = "Boot entry error";
+pub const ERR_PREFIX_COND: &str = "Condition error";
 pub const ERR_PREFIX_INPUT: &str = "Input error";
 pub const ERR_PREFIX_OP_GET: &str = "Get variable error";
 pub const ERR_PREFIX_OP_SET: &str = "Set variable error";
+pub const ERR_PREFIX_OUTPUT: &str = "Output error";
+pub const ERR_PREFIX_RESTART: &str = "Restart error";
 
 // Error messages
 pub const ERR_ARG: &str = "Failed to parse";
 pub const ERR_ARG_ASS: [&'static str; 2] = ["Must have at most a single assignment operator", "followed by a value"];
+pub const ERR_ARG_BOOT: &str = "Malformed boot entry command";
+pub const ERR_ARG_CONST: &str = "Constant not set";
+pub const ERR_ARG_ENCODING: [&'static str; 2] = ["Unknown encoding", "(use utf8, utf16 or utf16be)"];
+pub const ERR_ARG_EXPR: &str = "Malformed expression";
+pub const ERR_ARG_EXPR_RANGE: [&'static str; 2] = ["Expression", "goes out of range (below zero, or too large)"];
+pub const ERR_ARG_FORMAT: [&'static str; 2] = ["Unknown output format", "(use text or json)"];
+pub const ERR_ARG_INPUT: &str = "Missing file path after input file option";
 pub const ERR_ARG_MORE: &str = "Premature end of string";
 pub const ERR_ARG_NUM_DEC: &str = "Only digits 0-9 should appear in decimal value";
 pub const ERR_ARG_NUM_HEX: &str = "Only digits 0-9, a-f or A-F should appear in hexadecimal value";
 pub const ERR_ARG_NUM_HEX_PREFIX: &str = "Use prefix \"0x\" or \"0X\" for hexadecimal value";
 pub const ERR_ARG_OPT: &str = "Unrecognized option";
+pub const ERR_ARG_OUTPUT: &str = "Missing file path after output file option";
+pub const ERR_ARG_PARAM: [&'static str; 2] = ["Parameter", "not declared with !param in the input files"];
 pub const ERR_ARG_POS: [&'static str; 2] = ["Must have exactly one offset indicator", "followed by a value"];
 pub const ERR_ARG_POS_BKT_L: &str = "Surplus opening bracket in offset identifier";
 pub const ERR_ARG_POS_BKT_R: &str = "Missing closing bracket in offset identifier";
+pub const ERR_ARG_RESTART: [&'static str; 2] = ["Unknown restart type", "(use cold, platform, setup, shutdown or warm)"];
+pub const ERR_ARG_SIZE: [&'static str; 2] = ["Value size", "must be from 1 to 8 bytes"];
 pub const ERR_ARG_SIZE_LIMIT: [&'static str; 2] = ["Number", "is too large (64 bits or 8 bytes maximum)"];
 pub const ERR_ARG_SIZE_MISMATCH: [&'static str; 3] = ["Value", "too large to fit into", "bytes"];
 pub const ERR_ARG_VAR_BKT_L: &str = "Surplus opening bracket in variable identifier";
 pub const ERR_ARG_VAR_BKT_R: &str = "Missing closing bracket in variable identifier";
+pub const ERR_ARG_VAR_GUID: &str = "Malformed variable vendor GUID, or given together with identifier";
+pub const ERR_BOOT_AUTH: &str = "Not an authenticated variable update (EFI_VARIABLE_AUTHENTICATION_2) signed with PKCS #7";
+pub const ERR_BOOT_EXISTS: &str = "Load option already exists";
+pub const ERR_BOOT_OPTION: &str = "Malformed load option";
+pub const ERR_BOOT_ORDER: &str = "Malformed load option order";
+pub const ERR_BOOT_PATH: &str = "Failed to convert device path";
+pub const ERR_BOOT_PATH_FIND: &str = "Failed to locate UEFI device path from text protocol";
+pub const ERR_EDITOR_SCREEN: &str = "Screen too small for the editor";
+pub const ERR_FILE_OPEN: &str = "Failed to open file";
+pub const ERR_FILE_READ: &str = "Failed to read file";
+pub const ERR_FILE_TEXT: &str = "File is not UTF-8, UTF-16 or ASCII text, or has characters outside UCS-2";
+pub const ERR_FILE_WRITE: &str = "Failed to write file";
 pub const ERR_INPUT: &str = "Parse error in input";
+pub const ERR_INPUT_COMMAND: &str = "Malformed command, expected a variable name or a reference";
+pub const ERR_INPUT_CONFIG: &str = "Malformed HII configuration string";
+pub const ERR_INPUT_CONFIG_NONE: &str = "No HII configuration strings in input";
+pub const ERR_INPUT_COND: &str = "Malformed condition, expected e.g. vendor=\"Name\" and revision>=5.24";
+pub const ERR_INPUT_COND_KEY: [&'static str; 2] = ["Unknown property",
+    "(use vendor, revision, uefi, manufacturer, product, board, bios or bios_date)"];
+pub const ERR_INPUT_COND_TEXT: [&'static str; 2] = ["Text can only be compared for equality (= or !=), not with", "operator"];
+pub const ERR_INPUT_COND_VERSION: [&'static str; 2] = ["Malformed version", "(use numbers separated by dots, e.g. 5.24)"];
+pub const ERR_INPUT_CONST: &str = "Malformed constant, expected a name, and a value after \"=\"";
 pub const ERR_INPUT_DEF: &str = "Malformed definition";
 pub const ERR_INPUT_DEF_SET: [&'static str; 2] = ["Definition for", "must not specify new value to set"];
+pub const ERR_INPUT_DEF_VALUES: &str = "Malformed names of the values, expected e.g. {Disabled=0,Enabled=1}";
+pub const ERR_INPUT_ELSE: &str = "Else directive without a matching if, or following another else";
+pub const ERR_INPUT_ENDIF: &str = "Endif directive without a matching if";
+pub const ERR_INPUT_IF: &str = "If directive without a matching endif in the same file";
+pub const ERR_INPUT_IF_ENTRY: &str = "Only operations can be in a block checking the variables, as it is decided when they are run";
+pub const ERR_INPUT_INCLUDE: &str = "Malformed include directive, expected a file path";
+pub const ERR_INPUT_INCLUDE_CYCLE: [&'static str; 2] = ["File", "includes itself, directly or through other files"];
+pub const ERR_INPUT_INCLUDE_DEPTH: &str = "Too many files included within one another, at most";
+pub const ERR_INPUT_MANY: &str = "errors in the input:";
 pub const ERR_INPUT_NONE: &str = "No command-line arguments or standard input: use -h or --help for usage information";
 pub const ERR_INPUT_OPT: &str = "Unrecognized input option";
 pub const ERR_INPUT_READ: &str = "Failed to read standard input";
 pub const ERR_INPUT_REF: &str = "Malformed reference";
 pub const ERR_INPUT_REF_NONE: &str = "Failed to resolve reference";
+pub const ERR_INPUT_REF_PATH: &str = "Reference does not fit the definition, expected an index below the number of elements of an array, and a field of a structure";
+pub const ERR_INPUT_REQUIRE: [&'static str; 2] = ["Requirement", "not met by this system:"];
+pub const ERR_INPUT_REQUIRE_VALUE: &str = "Requirements are checked before running, so cannot check the variables, use an if block instead";
+pub const ERR_INPUT_STRUCT: &str = "Malformed structure, expected e.g. !struct Timing { tCL:0(1) tRAS:3(2) }";
+pub const ERR_INPUT_STRUCT_NONE: &str = "Structure not declared above";
 pub const ERR_INT_DEF: &str = "Internal parser error: definition retrieval attempted on wrong entry type";
-pub const ERR_INT_OP: &str = "Internal parser error: operation retrieval attempted on wrong entry type";
 pub const ERR_INT_SPLIT: &str = "Internal error: failed to split string into parts";
+pub const ERR_UEFI_HII_DB_EXPORT: &str = "Failed to export HII packages";
+pub const ERR_UEFI_HII_DB_FIND: &str = "Failed to locate HII database protocol";
+pub const ERR_UEFI_HII_DB_OPEN: &str = "Failed to initialize HII database protocol";
+pub const ERR_UEFI_HII_EXPORT: &str = "Failed to export HII configuration";
+pub const ERR_UEFI_HII_EXTRACT: &str = "Failed to extract HII configuration for variable";
+pub const ERR_UEFI_HII_FIND: &str = "Failed to locate HII configuration routing protocol";
+pub const ERR_UEFI_HII_NONE: &str = "Variable not configurable through HII";
+pub const ERR_UEFI_HII_OPEN: &str = "Failed to initialize HII configuration routing protocol";
+pub const ERR_UEFI_HII_ROUTE: &str = "Failed to route HII configuration for variable";
+pub const ERR_UEFI_HII_VALUE: &str = "HII configuration lacks requested value for variable";
 pub const ERR_UEFI_INIT: &str = "Failed to initialize UEFI services";
 pub const ERR_UEFI_LOAD: &str = "Failed to initialize UEFI loaded image protocol";
 pub const ERR_UEFI_LOAD_OPT: &str = "Failed to obtain UEFI image load options";
@@ -54,47 +113,161 @@ pub const ERR_UEFI_PATH_CONV: &str = "Failed to convert device image path";
 pub const ERR_UEFI_PATH_FIND: &str = "Failed to locate UEFI device path protocol";
 pub const ERR_UEFI_PATH_NONE: &str = "Device image path is empty";
 pub const ERR_UEFI_PATH_OPEN: &str = "Failed to initialize UEFI device path protocol";
+pub const ERR_UEFI_RESTART_SETUP: &str = "Firmware does not support rebooting into setup";
+pub const ERR_UEFI_VAR_AUTH: &str = "Variable only accepts authenticated writes, use a signed update instead";
 pub const ERR_UEFI_VAR_CONV: &str = "Internal error: failed to convert UEFI variable name";
+pub const ERR_UEFI_VAR_DELETE: &str = "Failed to delete variable";
 pub const ERR_UEFI_VAR_GET: &str = "Failed to get variable";
 pub const ERR_UEFI_VAR_GET_MANY: &str = "Use one of the above identifiers";
 pub const ERR_UEFI_VAR_GET_MANY_HEAD: &str = "Which one do you mean?";
-pub const ERR_UEFI_VAR_GET_MANY_ITEM: &str = " # Size: ";
+pub const ERR_UEFI_VAR_GET_MANY_ITEM: &str = " # ";
 pub const ERR_UEFI_VAR_GET_NONE: &str = "No such variable";
 pub const ERR_UEFI_VAR_LIST: &str = "Error while enumerating UEFI variables";
 pub const ERR_UEFI_VAR_SET: &str = "Failed to set variable";
+pub const ERR_UEFI_VAR_SET_AUTH: &str = "Failed to write authenticated update to variable";
+pub const ERR_UEFI_VAR_SET_AUTH_PARAM: &str = "update malformed, or attributes not matching the existing variable";
+pub const ERR_UEFI_VAR_SET_AUTH_PROTECTED: &str = "variable is write-protected";
+pub const ERR_UEFI_VAR_SET_AUTH_SECURITY: &str = "signature rejected, the update must be signed for this variable by a key in KEK (for db, dbx, dbt) or PK (for KEK, PK), with a timestamp later than that of the previous update unless appending";
 pub const ERR_UEFI_VAR_SIZE: [&'static str; 3] = ["Variable size", "less than offset", "and value size"];
 pub const ERR_UEFI_VAR_SIZE_GET: &str = "Failed to get variable size";
 
+// Variables
+pub const VAR_SIZE: &str = "Size: ";
+
+// Boot entries
+pub const BOOT_CREATED: &str = "Created";
+pub const BOOT_DATA: &str = "bytes of optional data";
+pub const BOOT_DELETED: &str = "Deleted";
+pub const BOOT_DISABLED: &str = "Disabled";
+pub const BOOT_ENABLED: &str = "Enabled";
+pub const BOOT_NONE: &str = "(none)";
+pub const BOOT_PATH_UNKNOWN: &str = "(unknown device path)";
+
+// Secure Boot
+pub const SECURE_BOOT_APPENDED: &str = "Appended";
+pub const SECURE_BOOT_NO: &str = "No";
+pub const SECURE_BOOT_SIGNED: [&'static str; 2] = ["Update signed", "bytes of signature"];
+pub const SECURE_BOOT_WRITTEN: &str = "Written";
+pub const SECURE_BOOT_YES: &str = "Yes";
+
+// Signature lists
+pub const SIGLIST_BYTES: &str = "bytes";
+pub const SIGLIST_CERT_MALFORMED: &str = "(malformed certificate)";
+pub const SIGLIST_ISSUER: &str = "Issuer";
+pub const SIGLIST_MALFORMED: &str = "(malformed signature list)";
+pub const SIGLIST_SERIAL: &str = "Serial";
+pub const SIGLIST_SIGNATURES: &str = "signature(s)";
+pub const SIGLIST_SUBJECT: &str = "Subject";
+
+// Editor
+pub const EDITOR_BYTES: &str = "bytes";
+pub const EDITOR_CHANGED: &str = "changed";
+pub const EDITOR_DISCARD: &str = "Changes not written, press Esc again to discard them";
+pub const EDITOR_EMPTY: &str = "Variable is empty, nothing to edit";
+pub const EDITOR_KEYS: &str = " Arrows PgUp PgDn Home End Move  0-F Edit  Del F3 Undo  F2 Write  Esc Quit";
+pub const EDITOR_ORIGINAL: &str = "was";
+pub const EDITOR_SIMULATE: &str = " (simulated)";
+pub const EDITOR_UNDONE: &str = "All changes undone";
+pub const EDITOR_WRITTEN: [&'static str; 2] = ["Written", "value(s)"];
+
+// Interactive mode
+pub const INTERACTIVE_BYTES: &str = "bytes";
+pub const INTERACTIVE_CHOICE: &str = "Identifier, or blank to cancel: ";
+pub const INTERACTIVE_CHOSEN: &str = "used from now on for";
+pub const INTERACTIVE_DEFS_NONE: &str = "No definitions yet";
+pub const INTERACTIVE_HEADER: &str = "Interactive mode: type help for the list of commands, exit to quit";
+pub const INTERACTIVE_HELP: &str = "# Enter any line of the input file format to run it right away:
+#   <VarName>[(<VarId>)]:<Offset>[(<Size>)][=<Value>]  Get or set a value
+#   <Def>,<VarName>:<Offset>[(<Size>)] [{<Name>=<Value>[,...]}]
+#                                       Define a variable to reference later
+#   <Def>,<VarName>:<Offset> as <Struct>[[<Count>]]
+#                                       Same, bound to a structure, or an array
+#   [~]@<Def>[[<Index>]][.<Field>][=<Value>|=<Name>]
+#                                       Get or set a referenced variable
+#   !<config|force|restart[=<Type>]|simulate|via-hii>  Set options
+#   !boot[-<Command>=<Value>]           Boot entry commands
+#   !set <Name>=<Value>                 Set a constant for the lines to come
+#   !param <Name>=<Value>               Same, nothing overrides it here
+#   !struct <Struct> { <Field>:<Offset>[(<Size>)] ... }
+#                                       Declare a structure for definitions
+# Commands:
+#   dump <VarName>[(<VarId>)] | @<Def>  Show all contents of a variable
+#   edit <VarName>[(<VarId>)] | @<Def>  Open a variable in the editor
+#   list                                Show the constants, structures and definitions
+#   help                                Show this information
+#   exit | quit                         End the session
+# Keys: Up and Down browse the history, Esc clears the line
+# If several variables share a name, asks which one, and remembers the choice";
+
 // Operations
 pub const OP_SKIPPED: &str = " # Already";
 
+// Board identification (SMBIOS) in application header
+pub const SMBIOS_BIOS: &str = "BIOS";
+
 // Version prompt in application header
 pub const VERSION: &str = "Version";
 pub const VERSION_UNKNOWN: &str = "Unknown";
 
 // Usage information
-pub const USAGE: [&'static str; 4] = ["Usage: ", "[.efi] [<Options>] <Op1> [<Op2> [... [<OpN>]]
+pub const USAGE: [&'static str; 5] = ["Usage: ", "[.efi] [<Options>] <Op1> [<Op2> [... [<OpN>]]
+- or - ", "[.efi] [<Options>] -i <InputFile1> [-i <InputFile2> [...]] [<Name>=<Value> [...]]
 - or - ", "[.efi] < <InputFile>
 Where:
 <Options>: Optional global-scope application settings
+  -b --boot      List boot entries (load options) and their order
+  -c --config    Output values as HII configuration strings (ConfigResp)
+  -e --encoding=<utf8|utf16|utf16be>  Output file encoding (utf8)
   -f --force     Force-write values even if already set as requested
+     --format=<text|json>  Results as text lines, or one JSON document (text)
   -h --help      Show usage information (precludes other operations)
+  -i --input     Run the script from the file that follows, can be repeated
+  -o --output    Also write the results to the file that follows
+  -q --quiet     Do not show the results on the console, only errors
   -r --restart   Upon successful completion, perform a system restart
+     --restart=<cold|platform|setup|shutdown|warm> Choose how (warm)
   -s --simulate  Do not write, only simulate actions (will still read)
+  -v --via-hii   Route all operations through HII configuration drivers
 <Op#>: Operation(s) to perform, can be multiple, each in the format:
-  <VarName>[(<VarId>)]:<Offset>[(<Size>)][=<Value>]
+  [~]<VarName>[(<VarId>)|{<GUID>}]:<Offset>[(<Size>)][=<Value>]
 Arg Overview:
   <VarName>      UEFI variable name to read or write to, case-sensitive
   <VarId>        If two variables share a name, will prompt to use this
+  <GUID>         Or the vendor GUID instead, to tell such variables apart
   <Offset>       Data starting position within the given UEFI variable
   <Size>         Optional, a byte (1) by default if omitted; little-endian
   <Value>        Value to write, 8 bytes (64 bits) maximum; read if absent
+  ~              Route this operation through HII configuration drivers
+Boot Entry Commands: Can be used same as options, <Entry> is e.g. 0001
+  --boot-create=<Entry>,<Description>,<DevicePath>  Add, append to order
+  --boot-delete=<Entry>   --boot-disable=<Entry>   --boot-enable=<Entry>
+  --boot-next=<Entry>     --boot-order=<Entry1>[,<Entry2>[...]]
+  --edit=<VarName>[(<VarId>)]  Open a variable in a full-screen hex editor
+  --secure-boot           Show Secure Boot state and signature databases
+  --secure-boot-append=<Var>,<File>  --secure-boot-write=<Var>,<File>
+                          Write a signed update (.auth) to PK, KEK, db, dbx, dbt
   <InputFile>    Script to run, same base format as arguments + see below
 File Overview:
   #                                   Comment, ignored until end of line
-  !<force|restart|simulate>           Set options, same as above arguments
-  <Def>,<VarName>:<Offset>[(<Size>)]  Define a variable to reference later
-  @<Def>[=<Value>]                    Assign to a referenced variable
+  !<config|force|restart[=<Type>]|simulate|via-hii>
+                                      Set options, same as above arguments
+  !boot[-<Command>=<Value>]           Boot entry commands, same as above
+  !include <File>                     Include another file, relative to this one
+  !set <Name>=<Value>                 Constant usable in offsets, sizes, values
+  !param <Name>=<Value>               Same, can be overridden by <Name>=<Value>
+  !if <Cond> ... [!else ...] !endif   Use the lines only on some firmware,
+                                      or run them only if variables match
+  !ifdef <Def>                        Same as !if, if <Def> is defined above
+  !require <Cond>                     Do nothing at all unless it holds
+  !struct <Struct> { <Field>:<Offset>[(<Size>)] ... }
+                                      Declare a structure, offsets relative
+  <Def>,<VarName>:<Offset>[(<Size>)] [{<Name>=<Value>[,...]}]
+                                      Define a variable to reference later,
+                                      optionally naming its values
+  <Def>,<VarName>:<Offset> as <Struct>[[<Count>]]
+                                      Same, bound to a structure, or an array
+  [~]@<Def>[[<Index>]][.<Field>][=<Value>|=<Name>]
+                                      Assign to a referenced variable
 Example Command Line:
   ", " -s Lang:0x00 Lang:0x00(4)=0x01020304 Lang:0x00(4)
   Read byte at offset 0, simulate-set the dword (4 bytes), then read again
@@ -104,5 +277,75 @@ Example Input File:
   @Language=0x01020304   # Write to the target referred to by \"Language\"
 
 <Offset>, <Size> and <Value> can be decimal or hexadecimal: use prefix \"0x\"
+They can also be expressions, e.g. BASE+0x10 with + - * << >> | & and ( )
+<Cond> is e.g. vendor=\"American Megatrends\" and (revision>=5.24 or uefi<2.8)
+  or Setup:0x10==0x01, @<Def>!=0, exists <VarName>[{<GUID>}], defined <Def>
 File should be a UTF-16 LE text, UEFI firmware and shell version-dependent
-Output saved to a file can be re-used as input again: format is the same"];
+Output saved to a file can be re-used as input again: format is the same
+Input can also consist of HII configuration strings (ConfigResp) instead
+With neither arguments nor input, shows a prompt to enter lines one by one"];
+
+// Host-side tool (uvt-host)
+#[cfg(feature = "host")]
+pub mod host {
+
+    // Error message prefix
+    pub const ERR_PREFIX: &str = "Error";
+
+    // Error messages
+    pub const ERR_ARG_MISSING: &str = "Missing argument for";
+    pub const ERR_ARG_OPT: &str = "Unrecognized option";
+    pub const ERR_CMD: &str = "Unrecognized command";
+    pub const ERR_FILE_READ: &str = "Failed to read file";
+    pub const ERR_FILE_WRITE: &str = "Failed to write file";
+    pub const ERR_IFR_FORMS_NONE: &str = "No IFR form packages found in";
+    pub const ERR_IFR_STRINGS_NONE: [&'static str; 2] = ["No HII string packages for language", "found in"];
+    pub const ERR_SIGN_CERT: &str = "Malformed X.509 certificate";
+    pub const ERR_SIGN_HASH: &str = "Malformed SHA-256 hash, 64 hexadecimal digits expected";
+    pub const ERR_SIGN_KEY: &str = "Unsupported private key, unencrypted RSA in PEM or DER expected";
+    pub const ERR_SIGN_OWNER: &str = "Malformed owner GUID";
+    pub const ERR_SIGN_SIGNER: &str = "Unsupported signer certificate, X.509 with an RSA key expected";
+    pub const ERR_SIGN_TIME: &str = "Malformed timestamp, YYYY-MM-DD[THH:MM:SS] expected";
+    pub const ERR_SIGN_VAR: &str = "Not a signature database accepting authenticated updates (PK, KEK, db, dbx, dbt)";
+    pub const ERR_SIGN_VERIFY: &str = "Signature does not verify against the signer certificate, make sure the key matches it";
+    pub const ERR_SMBIOS_NONE: &str = "No board identification (SMBIOS system, baseboard or BIOS information) found in";
+
+    // Signed updates
+    pub const SIGN_VERIFIED: &str = "Signature verified against";
+
+    // Extracted definitions
+    pub const IFR_HEADER: [&'static str; 3] = ["UEFI Variable Tool (UVT) Definitions", "Extracted from", "Language"];
+    pub const IFR_VAR_STORE: [&'static str; 2] = ["Variable", "Size"];
+
+    // Usage information
+    pub const USAGE: &str = "Usage: uvt-host <Command> [<Args>]
+Where <Command> is one of:
+  ifr [-l <Lang>] <Image> [<OutputFile>]
+                 Extract setup questions from a firmware image or a Setup
+                 module as definitions in the input file format; strings
+                 in <Lang> (en-US by default), written as UTF-16 LE to the
+                 <OutputFile> if given, otherwise to the standard output
+  siglist <File1> [<File2> [...]]
+                 Decode Secure Boot signature databases (PK, KEK, db, dbx,
+                 MokList and so on) captured as files, with or without the
+                 attributes preceding the contents as in efivarfs, or the
+                 signed updates to them (.auth)
+  sign <Var> -k <KeyFile> -s <CertFile> [-c <CertFile>] [-x <SHA256>]
+       [-g <OwnerGuid>] [-t <Time>] [-a] <OutputFile>
+                 Build signature lists from X.509 certificates (-c) and
+                 SHA-256 hashes (-x), each of them can be given any number
+                 of times, owned by <OwnerGuid> (zeros by default), and sign
+                 them as an update (.auth) to <Var> (PK, KEK, db, dbx, dbt)
+                 with the RSA key and its certificate (-k and -s), as of
+                 <Time> (YYYY-MM-DD[THH:MM:SS] in UTC, now by default), to
+                 replace the contents or to append to them (-a); the keys
+                 and certificates can be either PEM or DER
+  smbios <File1> [<File2> [...]]
+                 Show the board identification from SMBIOS tables captured
+                 as files, either the structure table alone, as in
+                 /sys/firmware/dmi/tables/DMI, or from dmidecode --dump-bin,
+                 as the conditions to use in the scripts (!require and !if)
+Options:
+  -h --help      Show usage information";
+
+}
@@ -0,0 +1,648 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: Crypto
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Computes SHA-256 hashes, and signs and verifies data with RSA keys
+// as PKCS #7 signed data, for authenticated variable updates
+
+// Note: implemented here, so that the host-side tool has no external
+// dependencies and builds offline; meant for occasional signing only,
+// with no attempt at resisting side-channel attacks, so only use it
+// on a trusted host
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{vec, vec::Vec};
+
+// Symbols from other modules
+use crate::siglist::read_der;
+
+// Size of a SHA-256 hash
+pub const SHA256_SIZE: usize = 32;
+
+// SHA-256 round constants
+const SHA256_K: [u32; 64] = [
+    0x428A2F98, 0x71374491, 0xB5C0FBCF, 0xE9B5DBA5, 0x3956C25B, 0x59F111F1, 0x923F82A4, 0xAB1C5ED5,
+    0xD807AA98, 0x12835B01, 0x243185BE, 0x550C7DC3, 0x72BE5D74, 0x80DEB1FE, 0x9BDC06A7, 0xC19BF174,
+    0xE49B69C1, 0xEFBE4786, 0x0FC19DC6, 0x240CA1CC, 0x2DE92C6F, 0x4A7484AA, 0x5CB0A9DC, 0x76F988DA,
+    0x983E5152, 0xA831C66D, 0xB00327C8, 0xBF597FC7, 0xC6E00BF3, 0xD5A79147, 0x06CA6351, 0x14292967,
+    0x27B70A85, 0x2E1B2138, 0x4D2C6DFC, 0x53380D13, 0x650A7354, 0x766A0ABB, 0x81C2C92E, 0x92722C85,
+    0xA2BFE8A1, 0xA81A664B, 0xC24B8B70, 0xC76C51A3, 0xD192E819, 0xD6990624, 0xF40E3585, 0x106AA070,
+    0x19A4C116, 0x1E376C08, 0x2748774C, 0x34B0BCB5, 0x391C0CB3, 0x4ED8AA4A, 0x5B9CCA4F, 0x682E6FF3,
+    0x748F82EE, 0x78A5636F, 0x84C87814, 0x8CC70208, 0x90BEFFFA, 0xA4506CEB, 0xBEF9A3F7, 0xC67178F2,
+];
+
+// SHA-256 initial hash value
+const SHA256_H: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+// DER tags
+const DER_TAG_INTEGER:      u8 = 0x02;
+const DER_TAG_BIT_STRING:   u8 = 0x03;
+const DER_TAG_OCTET_STRING: u8 = 0x04;
+const DER_TAG_NULL:         u8 = 0x05;
+const DER_TAG_OID:          u8 = 0x06;
+const DER_TAG_SEQUENCE:     u8 = 0x30;
+const DER_TAG_SET:          u8 = 0x31;
+const DER_TAG_CONTEXT_0:    u8 = 0xA0;  // Explicit or constructed context-specific tag [0]
+
+// Encoded OIDs
+const OID_PKCS7_DATA:   &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x01];
+const OID_PKCS7_SIGNED: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+const OID_RSA:          &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01];
+const OID_SHA256:       &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+
+// Encoded digest algorithm and hash prefix for PKCS #1 v1.5 signatures (DigestInfo)
+const DIGEST_INFO_SHA256: &[u8] = &[0x30, 0x31, 0x30, 0x0D, 0x06, 0x09, 0x60, 0x86, 0x48,
+    0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05, 0x00, 0x04, 0x20];
+
+// PEM armor
+const PEM_BEGIN: &str = "-----BEGIN ";
+const PEM_END:   &str = "-----END ";
+
+// Public key
+// RSA modulus and public exponent, both big-endian
+
+#[derive(Clone, Debug, Default)]
+pub struct PublicKey {
+    modulus: Vec<u8>,
+    exponent: Vec<u8>,
+}
+
+// Private key
+// RSA modulus and private exponent, both big-endian
+
+#[derive(Clone, Debug, Default)]
+pub struct PrivateKey {
+    modulus: Vec<u8>,
+    exponent: Vec<u8>,
+}
+
+// Implementation
+impl PrivateKey {
+
+    // Decodes an unencrypted RSA private key, either on its own (PKCS #1)
+    // or wrapped together with the algorithm identifier (PKCS #8)
+    pub fn from_der(bytes: &[u8]) -> Option<Self> {
+
+        // Version, then either the algorithm identifier, or the modulus
+        let (DER_TAG_SEQUENCE, key, _) = read_der(bytes)? else { return None };
+        let (DER_TAG_INTEGER, _, rest) = read_der(key)? else { return None };
+        match read_der(rest)? {
+
+            // PKCS #8: the key follows within an octet string
+            (DER_TAG_SEQUENCE, _, rest) => {
+                let (DER_TAG_OCTET_STRING, key, _) = read_der(rest)? else { return None };
+                Self::from_der(key)
+            }
+
+            // PKCS #1: modulus, public exponent, private exponent
+            (DER_TAG_INTEGER, modulus, rest) => {
+                let (DER_TAG_INTEGER, _, rest) = read_der(rest)? else { return None };
+                let (DER_TAG_INTEGER, exponent, _) = read_der(rest)? else { return None };
+                Some(Self { modulus: strip_zeros(modulus).to_vec(),
+                    exponent: strip_zeros(exponent).to_vec() })
+            }
+
+            // Unknown
+            _ => None,
+
+        }
+
+    }
+
+}
+
+// Signer
+// Certificate to be included with the signature, together with the
+// parts of it that identify the signer and verify the signature
+
+#[derive(Clone, Debug, Default)]
+pub struct Signer {
+    certificate: Vec<u8>,  // Certificate as a whole
+    issuer: Vec<u8>,       // Distinguished name of the issuer, encoded
+    serial: Vec<u8>,       // Serial number, big-endian
+    pub key: PublicKey,    // Public key of the subject
+}
+
+// Implementation
+impl Signer {
+
+    // Decodes the identification and the RSA public key from a certificate
+    pub fn from_der(bytes: &[u8]) -> Option<Self> {
+
+        // Certificate, then the part to be signed within
+        let (DER_TAG_SEQUENCE, certificate, _) = read_der(bytes)? else { return None };
+        let (DER_TAG_SEQUENCE, mut tbs, _) = read_der(certificate)? else { return None };
+
+        // Optional version
+        if let (DER_TAG_CONTEXT_0, _, rest) = read_der(tbs)? {
+            tbs = rest;
+        }
+
+        // Serial number, signature algorithm, issuer, validity and subject
+        let (DER_TAG_INTEGER, serial, tbs) = read_der(tbs)? else { return None };
+        let (_, _, tbs) = read_der(tbs)?;
+        let (DER_TAG_SEQUENCE, issuer, tbs) = read_der(tbs)? else { return None };
+        let (_, _, tbs) = read_der(tbs)?;
+        let (_, _, tbs) = read_der(tbs)?;
+
+        // Public key information: algorithm, then the key in a bit string
+        let (DER_TAG_SEQUENCE, info, _) = read_der(tbs)? else { return None };
+        let (DER_TAG_SEQUENCE, algorithm, info) = read_der(info)? else { return None };
+        let (DER_TAG_OID, OID_RSA, _) = read_der(algorithm)? else { return None };
+        let (DER_TAG_BIT_STRING, [0, key @ ..], _) = read_der(info)? else { return None };
+
+        // Modulus and public exponent
+        let (DER_TAG_SEQUENCE, key, _) = read_der(key)? else { return None };
+        let (DER_TAG_INTEGER, modulus, key) = read_der(key)? else { return None };
+        let (DER_TAG_INTEGER, exponent, _) = read_der(key)? else { return None };
+
+        // Return the result
+        Some(Self {
+            certificate: bytes[0 .. bytes.len() - read_der(bytes)?.2.len()].to_vec(),
+            issuer: der(DER_TAG_SEQUENCE, &[issuer]),
+            serial: serial.to_vec(),
+            key: PublicKey { modulus: strip_zeros(modulus).to_vec(),
+                exponent: strip_zeros(exponent).to_vec() } })
+
+    }
+
+}
+
+// Public Functions
+
+// Decodes the first PEM-armored block in a text, such as a certificate
+// or a key, returns nothing if there is none or it is malformed
+pub fn decode_pem(text: &str) -> Option<Vec<u8>> {
+
+    // Skip the line with the opening armor
+    let start = text.find(PEM_BEGIN)?;
+    let text = &text[start ..];
+    let text = &text[text.find('\n')? + 1 ..];
+
+    // Base64 until the closing armor
+    decode_base64(&text[0 .. text.find(PEM_END)?])
+
+}
+
+// Computes the SHA-256 hash of the data
+pub fn sha256(data: &[u8]) -> [u8; SHA256_SIZE] {
+
+    // Pad the message to a multiple of the block size,
+    // ending with the message length in bits
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    // Process each block in turn
+    let mut hash = SHA256_H;
+    for block in message.chunks_exact(64) {
+
+        // Message schedule
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16 .. 64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        // Compression
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hash;
+        for i in 0 .. 64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(SHA256_K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+        }
+
+        // Add to the hash value
+        for (value, add) in hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *value = value.wrapping_add(add);
+        }
+
+    }
+
+    // Return the result
+    let mut result = [0; SHA256_SIZE];
+    for (bytes, value) in result.chunks_exact_mut(4).zip(hash) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    result
+
+}
+
+// Signs the data with a private key, producing PKCS #7 signed data
+// (SignedData, without the ContentInfo wrapper) with the data detached,
+// no authenticated attributes, and the signer certificate included,
+// as expected for authenticated variable updates
+pub fn sign_pkcs7(data: &[u8], key: &PrivateKey, signer: &Signer) -> Option<Vec<u8>> {
+
+    // Sign the hash of the data
+    let message = encode_pkcs1(&sha256(data), key.modulus.len())?;
+    let signature = mod_pow(&message, &key.exponent, &key.modulus)?;
+
+    // Algorithm identifiers
+    let digest_algorithm = der(DER_TAG_SEQUENCE,
+        &[&der(DER_TAG_OID, &[OID_SHA256]), &der(DER_TAG_NULL, &[])]);
+    let signature_algorithm = der(DER_TAG_SEQUENCE,
+        &[&der(DER_TAG_OID, &[OID_RSA]), &der(DER_TAG_NULL, &[])]);
+
+    // Signer information: version, issuer and serial number, algorithms, signature
+    let signer_info = der(DER_TAG_SEQUENCE, &[
+        &der(DER_TAG_INTEGER, &[&[1]]),
+        &der(DER_TAG_SEQUENCE, &[&signer.issuer, &der(DER_TAG_INTEGER, &[&signer.serial])]),
+        &digest_algorithm,
+        &signature_algorithm,
+        &der(DER_TAG_OCTET_STRING, &[&signature])]);
+
+    // Signed data: version, algorithms, content type only, certificates, signer
+    Some(der(DER_TAG_SEQUENCE, &[
+        &der(DER_TAG_INTEGER, &[&[1]]),
+        &der(DER_TAG_SET, &[&digest_algorithm]),
+        &der(DER_TAG_SEQUENCE, &[&der(DER_TAG_OID, &[OID_PKCS7_DATA])]),
+        &der(DER_TAG_CONTEXT_0, &[&signer.certificate]),
+        &der(DER_TAG_SET, &[&signer_info])]))
+
+}
+
+// Verifies PKCS #7 signed data over the data with a public key,
+// accepting it with or without the ContentInfo wrapper
+// Note: only signatures without authenticated attributes are supported
+pub fn verify_pkcs7(data: &[u8], signed: &[u8], key: &PublicKey) -> bool {
+
+    // Retrieve the signature, then check it against the hash of the data
+    signature_from_pkcs7(signed)
+        .and_then(|signature| mod_pow(signature, &key.exponent, &key.modulus))
+        .zip(encode_pkcs1(&sha256(data), key.modulus.len()))
+        .is_some_and(|(decoded, expected)| decoded == expected)
+
+}
+
+// Private Functions
+
+// Decodes Base64, skipping any whitespace
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+
+    // Collect six bits per character, up to the padding
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()).take_while(|&c| c != b'=') {
+        let value = match c {
+            b'A' ..= b'Z' => c - b'A',
+            b'a' ..= b'z' => c - b'a' + 26,
+            b'0' ..= b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    // Return the result
+    Some(bytes)
+
+}
+
+// Encodes a DER element from its tag and the parts of its contents
+fn der(tag: u8, parts: &[&[u8]]) -> Vec<u8> {
+
+    // Tag, then the length in either the short or the long form
+    let length = parts.iter().map(|p| p.len()).sum::<usize>();
+    let mut bytes = vec![tag];
+    if length < 0x80 {
+        bytes.push(length as u8);
+    } else {
+        let length = strip_zeros(&length.to_be_bytes()).to_vec();
+        bytes.push(0x80 | length.len() as u8);
+        bytes.extend_from_slice(&length);
+    }
+
+    // Contents
+    for part in parts {
+        bytes.extend_from_slice(part);
+    }
+    bytes
+
+}
+
+// Encodes a hash for an RSA signature (EMSA-PKCS1-v1_5) of a given size:
+// a zero, block type one, padding, a zero, then the hash with its algorithm
+fn encode_pkcs1(hash: &[u8], size: usize) -> Option<Vec<u8>> {
+    let padding = size.checked_sub(3 + DIGEST_INFO_SHA256.len() + hash.len())?;
+    let mut message = vec![0x00, 0x01];
+    message.resize(2 + padding, 0xFF);
+    message.push(0x00);
+    message.extend_from_slice(DIGEST_INFO_SHA256);
+    message.extend_from_slice(hash);
+    Some(message)
+}
+
+// Retrieves the signature of the only signer from PKCS #7 signed data
+fn signature_from_pkcs7(signed: &[u8]) -> Option<&[u8]> {
+
+    // Remove the ContentInfo wrapper, if present
+    let (DER_TAG_SEQUENCE, mut signed_data, _) = read_der(signed)? else { return None };
+    if let (DER_TAG_OID, OID_PKCS7_SIGNED, rest) = read_der(signed_data)? {
+        let (DER_TAG_CONTEXT_0, wrapped, _) = read_der(rest)? else { return None };
+        let (DER_TAG_SEQUENCE, wrapped, _) = read_der(wrapped)? else { return None };
+        signed_data = wrapped;
+    }
+
+    // Version, algorithms and content, then the optional
+    // certificates and revocation lists, the signers being last
+    let (_, _, mut rest) = read_der(signed_data)?;
+    for _ in 0 .. 2 {
+        (_, _, rest) = read_der(rest)?;
+    }
+    let mut signers = None;
+    while !rest.is_empty() {
+        let (tag, contents, next) = read_der(rest)?;
+        if tag == DER_TAG_SET {
+            signers = Some(contents);
+        }
+        rest = next;
+    }
+
+    // The signature is the last part of the signer information,
+    // which must not have any authenticated attributes
+    let (DER_TAG_SEQUENCE, mut signer, _) = read_der(signers?)? else { return None };
+    let mut signature = None;
+    while !signer.is_empty() {
+        let (tag, contents, next) = read_der(signer)?;
+        match tag {
+            DER_TAG_CONTEXT_0 => return None,
+            DER_TAG_OCTET_STRING => signature = Some(contents),
+            _ => (),
+        }
+        signer = next;
+    }
+    signature
+
+}
+
+// Removes the leading zeros from a big-endian integer
+fn strip_zeros(bytes: &[u8]) -> &[u8] {
+    &bytes[bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len()) ..]
+}
+
+// Big Integer Arithmetic
+// Numbers are kept as little-endian vectors of 32-bit limbs
+
+// Computes a big-endian base raised to an exponent modulo an odd modulus,
+// the result having the size of the modulus, using Montgomery multiplication
+fn mod_pow(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Option<Vec<u8>> {
+
+    // Convert to limbs, the modulus must be odd and larger than the base
+    let size = modulus.len();
+    let n = limbs_from_bytes(modulus, size.div_ceil(4));
+    let b = limbs_from_bytes(strip_zeros(base), n.len());
+    if n[0] & 1 == 0 || b.len() > n.len() || !limbs_less(&b, &n) {
+        return None;
+    }
+
+    // Montgomery constant, the negated inverse of the lowest limb
+    let mut inverse = 1u32;
+    for _ in 0 .. 5 {
+        inverse = inverse.wrapping_mul(2u32.wrapping_sub(n[0].wrapping_mul(inverse)));
+    }
+    let n_inverse = inverse.wrapping_neg();
+
+    // R squared modulo the modulus, R being 2 to the power of the limb bits,
+    // for the conversion to the Montgomery form
+    let mut r2 = limbs_from_bytes(&[1], n.len());
+    for _ in 0 .. 64 * n.len() {
+        let carry = r2.iter_mut().fold(0, |carry, limb| {
+            let next = *limb >> 31;
+            *limb = *limb << 1 | carry;
+            next });
+        if carry != 0 || !limbs_less(&r2, &n) {
+            limbs_sub(&mut r2, &n);
+        }
+    }
+
+    // Square and multiply, from the highest bit of the exponent
+    let one = limbs_from_bytes(&[1], n.len());
+    let b = mont_mul(&b, &r2, &n, n_inverse);
+    let mut x = mont_mul(&one, &r2, &n, n_inverse);
+    for byte in exponent {
+        for bit in (0 .. 8).rev() {
+            x = mont_mul(&x, &x, &n, n_inverse);
+            if byte >> bit & 1 != 0 {
+                x = mont_mul(&x, &b, &n, n_inverse);
+            }
+        }
+    }
+
+    // Convert back from the Montgomery form
+    let x = mont_mul(&x, &one, &n, n_inverse);
+    let bytes = x.iter().rev().flat_map(|limb| limb.to_be_bytes()).collect::<Vec<_>>();
+    Some(bytes[bytes.len() - size ..].to_vec())
+
+}
+
+// Converts a big-endian integer to limbs, padded to a given count
+fn limbs_from_bytes(bytes: &[u8], count: usize) -> Vec<u32> {
+    let mut limbs = bytes.rchunks(4)
+        .map(|c| c.iter().fold(0, |limb, &b| limb << 8 | b as u32))
+        .collect::<Vec<_>>();
+    limbs.resize(count.max(limbs.len()), 0);
+    limbs
+}
+
+// Whether one number is less than another of the same limb count
+fn limbs_less(a: &[u32], b: &[u32]) -> bool {
+    a.iter().rev().cmp(b.iter().rev()).is_lt()
+}
+
+// Subtracts a number from another of the same limb count, in place
+fn limbs_sub(a: &mut [u32], b: &[u32]) {
+    let mut borrow = 0u64;
+    for (a, &b) in a.iter_mut().zip(b) {
+        let difference = (*a as u64).wrapping_sub(b as u64).wrapping_sub(borrow);
+        *a = difference as u32;
+        borrow = difference >> 63;
+    }
+}
+
+// Montgomery multiplication: computes a * b / R modulo n
+fn mont_mul(a: &[u32], b: &[u32], n: &[u32], n_inverse: u32) -> Vec<u32> {
+
+    // Interleave the multiplication and the reduction, one limb at a time
+    let count = n.len();
+    let mut t = vec![0u32; count + 2];
+    for &b in b {
+
+        // Add a times the limb of b
+        let mut carry = 0u64;
+        for (t, &a) in t.iter_mut().zip(a) {
+            let sum = *t as u64 + a as u64 * b as u64 + carry;
+            *t = sum as u32;
+            carry = sum >> 32;
+        }
+        let sum = t[count] as u64 + carry;
+        t[count] = sum as u32;
+        t[count + 1] = (sum >> 32) as u32;
+
+        // Add a multiple of n making the lowest limb zero, and drop it
+        let m = t[0].wrapping_mul(n_inverse) as u64;
+        let mut carry = (t[0] as u64 + m * n[0] as u64) >> 32;
+        for j in 1 .. count {
+            let sum = t[j] as u64 + m * n[j] as u64 + carry;
+            t[j - 1] = sum as u32;
+            carry = sum >> 32;
+        }
+        let sum = t[count] as u64 + carry;
+        t[count - 1] = sum as u32;
+        t[count] = t[count + 1] + (sum >> 32) as u32;
+        t[count + 1] = 0;
+
+    }
+
+    // The result is less than twice n, subtract n once if needed
+    let overflow = t[count] != 0;
+    t.truncate(count);
+    if overflow || !limbs_less(&t, n) {
+        limbs_sub(&mut t, n);
+    }
+    t
+
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::guid::parse_guid;
+    use crate::siglist::{self, AuthenticatedUpdate, Timestamp};
+
+    // Key and certificate made for the tests only, see fixtures/README.md
+    const KEY: &str = include_str!("../fixtures/crypto/kek.key");
+    const CERTIFICATE: &str = include_str!("../fixtures/crypto/kek.crt");
+
+    // Data of an update, and its signature made with OpenSSL, see fixtures/README.md
+    const UPDATE: &[u8] = include_bytes!("../fixtures/crypto/update.bin");
+    const UPDATE_SIGNED: &[u8] = include_bytes!("../fixtures/crypto/update.p7");
+
+    // Splits a DER element into the elements it holds, each with its tag
+    fn children(bytes: &[u8]) -> Vec<(u8, &[u8])> {
+        let (_, mut contents, _) = read_der(bytes).unwrap();
+        let mut children = Vec::new();
+        while !contents.is_empty() {
+            let (tag, _, rest) = read_der(contents).unwrap();
+            children.push((tag, &contents[.. contents.len() - rest.len()]));
+            contents = rest;
+        }
+        children
+    }
+
+    // Hashes a message spanning more than a single block
+    #[test]
+    fn hash() {
+        assert_eq!(sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"), [
+            0x24, 0x8D, 0x6A, 0x61, 0xD2, 0x06, 0x38, 0xB8, 0xE5, 0xC0, 0x26, 0x93, 0x0C, 0x3E, 0x60, 0x39,
+            0xA3, 0x3C, 0xE4, 0x59, 0x64, 0xFF, 0x21, 0x67, 0xF6, 0xEC, 0xED, 0xD4, 0x19, 0xDB, 0x06, 0xC1]);
+    }
+
+    // Signs an update, then decodes it: the timestamp, the certificate header
+    // (WIN_CERTIFICATE_UEFI_GUID), and the PKCS #7 signed data filling the rest
+    // of the certificate, which verifies against the data that was signed
+    #[test]
+    fn sign_then_parse() {
+
+        // Sign the signature lists of the database fixture
+        let key = PrivateKey::from_der(&decode_pem(KEY).unwrap()).unwrap();
+        let signer = Signer::from_der(&decode_pem(CERTIFICATE).unwrap()).unwrap();
+        let content = include_bytes!("../fixtures/siglist/db.bin");
+        let timestamp = Timestamp::parse("2024-03-05T10:20:30").unwrap();
+        let data = siglist::signed_data("db", siglist::AUTH_ATTRIBUTES, &timestamp, content);
+        let signature = sign_pkcs7(&data, &key, &signer).unwrap();
+        let bytes = AuthenticatedUpdate { timestamp, signature: &signature, content }.to_bytes();
+
+        // Timestamp, with the zone and the rest left as zeros
+        assert_eq!(bytes[0 .. 16], [0xE8, 0x07, 3, 5, 10, 20, 30, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        // Certificate header: length, revision, type, then the type GUID
+        let length = u32::from_le_bytes(bytes[16 .. 20].try_into().unwrap()) as usize;
+        assert_eq!(length, 8 + 16 + signature.len());
+        assert_eq!(bytes[20 .. 24], [0x00, 0x02, 0xF1, 0x0E]);
+        assert_eq!(bytes[24 .. 40], parse_guid("4AAFD29D-68DF-49EE-8AA9-347D375665A7").unwrap());
+
+        // Signed data as a single DER element, the contents after it
+        assert!(matches!(read_der(&bytes[40 .. 16 + length]), Some((DER_TAG_SEQUENCE, _, []))));
+        assert_eq!(&bytes[16 + length ..], content);
+
+        // Decoded back the same, verifying only with the data that was signed
+        let update = AuthenticatedUpdate::parse(&bytes).unwrap();
+        assert_eq!(update.timestamp, timestamp);
+        assert_eq!(update.signature, signature);
+        assert_eq!(update.content, content);
+        assert!(verify_pkcs7(&data, update.signature, &signer.key));
+        let appended = siglist::signed_data("db",
+            siglist::AUTH_ATTRIBUTES | siglist::AUTH_ATTRIBUTE_APPEND, &timestamp, content);
+        assert!(!verify_pkcs7(&appended, update.signature, &signer.key));
+
+    }
+
+    // Signs the same data as OpenSSL does with the same key, matching its signed
+    // data element by element, the signature included, except for the digest
+    // algorithm, given by OpenSSL without the parameters rather than as NULL
+    #[test]
+    fn sign_known_answer() {
+
+        // Data signed, assembled the same way as for the fixture
+        let key = PrivateKey::from_der(&decode_pem(KEY).unwrap()).unwrap();
+        let signer = Signer::from_der(&decode_pem(CERTIFICATE).unwrap()).unwrap();
+        let timestamp = Timestamp::parse("2024-03-05T10:20:30").unwrap();
+        let content = include_bytes!("../fixtures/siglist/db.bin");
+        assert_eq!(siglist::signed_data("db", siglist::AUTH_ATTRIBUTES, &timestamp, content), UPDATE);
+
+        // Signed data from OpenSSL, unwrapped from the ContentInfo
+        let wrapper = children(UPDATE_SIGNED);
+        assert_eq!(wrapper[0], (DER_TAG_OID, &der(DER_TAG_OID, &[OID_PKCS7_SIGNED])[..]));
+        let expected = read_der(wrapper[1].1).unwrap().1;
+
+        // Version, content type, certificates, and signer information, of which
+        // the version, issuer and serial number, signature algorithm and signature
+        let signed = sign_pkcs7(UPDATE, &key, &signer).unwrap();
+        let (actual, expected) = (children(&signed), children(expected));
+        assert_eq!(actual.len(), expected.len());
+        for index in [0, 2, 3] {
+            assert_eq!(actual[index], expected[index], "{index}");
+        }
+        fn info(infos: &[u8]) -> Vec<(u8, &[u8])> { children(children(infos)[0].1) }
+        let (actual_info, expected_info) = (info(actual[4].1), info(expected[4].1));
+        assert_eq!(actual_info.len(), expected_info.len());
+        for index in [0, 1, 3, 4] {
+            assert_eq!(actual_info[index], expected_info[index], "{index}");
+        }
+
+        // Digest algorithm, same apart from the parameters
+        fn algorithm(algorithms: &[u8]) -> (u8, &[u8]) { children(children(algorithms)[0].1)[0] }
+        assert_eq!(algorithm(actual[1].1), algorithm(expected[1].1));
+        assert_eq!(children(actual_info[2].1)[0], children(expected_info[2].1)[0]);
+
+        // Signature made by OpenSSL accepted, with the wrapper
+        assert!(verify_pkcs7(UPDATE, UPDATE_SIGNED, &signer.key));
+        assert!(!verify_pkcs7(&UPDATE[1 ..], UPDATE_SIGNED, &signer.key));
+
+    }
+
+}
@@ -8,14 +8,20 @@
 
 // Declare fully-qualified symbols
 // to be used in the local scope
-use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+use alloc::{borrow::ToOwned, format, string::{String, ToString}, vec::Vec};
 use core::fmt::{Display, Formatter, Result as FmtResult};
-use uefi::{CString16, table::runtime::{VariableAttributes, VariableVendor}};
+use uefi::{CStr16, CString16, table::runtime::{VariableAttributes, VariableVendor}};
+use uefi_services::println;
 
 // Symbols from other modules
+use crate::boot::LoadOptionId;
 use crate::config;
 use crate::config::locale as msg;
 use crate::error::AppError;
+use crate::guid::format_guid;
+use crate::hii::{self, ConfigBlock, ConfigString};
+use crate::script::{self, SystemInfo, TargetLayout, TargetPath};
+use crate::string::quote_json;
 
 // Operation target
 // Structure identifying a value in a UEFI variable
@@ -28,6 +34,75 @@ pub struct OperationTarget {
     pub offset: usize,      // Offset within the variable
     pub size: usize,        // Value data length from offset
 
+    // Vendor GUID, if given to tell namesakes,
+    // resolved to the identifier when run
+    pub vendor: Option<[u8; 16]>,
+
+    // Names of the values, if given in the definition
+    pub values: Vec<(String, usize)>,
+
+    // Structure bound to the definition, if any,
+    // and the number of elements, if an array
+    pub layout: Option<TargetLayout>,
+    pub count: Option<usize>,
+
+}
+
+// Implementation
+impl OperationTarget {
+
+    // Selects the element and the field given by a reference to the definition,
+    // the target being that of the single value selected
+    pub fn select(&self, name: &CStr16, path: &TargetPath) -> Result<Self, AppError> {
+
+        // Select the element and the field, reporting
+        // the reference as written if they do not fit
+        let (offset, size) = path.select(self.offset, self.size, self.count, self.layout.as_ref())
+            .ok_or_else(|| AppError::InputRefPath(format!("{}{name}{path}", config::CHAR_INPUT_REF)))?;
+
+        // Return the target of the single value selected
+        Ok(Self { offset, size, layout: None, count: None, ..self.clone() })
+
+    }
+
+}
+
+// Implementation: formatting for display,
+// in the same format as the input
+impl Display for OperationTarget {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+
+        // Retrieve the vendor GUID or the identifier, if present,
+        // the former taking precedence once resolved to the latter
+        let id_string = match (self.vendor, self.id) {
+            (Some(vendor), _) => format!("{}{}{}",
+                config::CHAR_ARG_GUID_L, format_guid(&vendor), config::CHAR_ARG_GUID_R),
+            (None, Some(id)) => format!("{}{id}{}", config::CHAR_ARG_BKT_L, config::CHAR_ARG_BKT_R),
+            (None, None) => "".to_owned(),
+        };
+
+        // Retrieve the optional size (if not a byte)
+        let size_string = if self.size == 1 {
+            "".to_owned()
+        } else {
+            format!("{}{}{}", config::CHAR_ARG_BKT_L, self.size, config::CHAR_ARG_BKT_R)
+        };
+
+        // Retrieve the structure and the number of elements, if any
+        let layout_string = match (&self.layout, self.count) {
+            (None, None) => "".to_owned(),
+            (layout, count) => format!(" {} {}{}", config::INPUT_DEF_AS,
+                layout.as_ref().map_or("", |layout| layout.name.as_str()),
+                count.map_or("".to_owned(), |count| format!("{}{count}{}",
+                    config::CHAR_INPUT_INDEX_L, config::CHAR_INPUT_INDEX_R))),
+        };
+
+        write!(f, "{}{id_string}{}{:#06x}{size_string}{layout_string}",
+            self.name, config::CHAR_ARG_POS, self.offset)
+
+    }
+
 }
 
 // Operation type
@@ -49,7 +124,12 @@ pub enum OperationType {
 #[derive(Clone, Debug, Default)]
 pub struct ArgOperation {
     pub action: OperationType,    // Get or set
+    pub hii: bool,                // Route through HII
     pub target: OperationTarget,  // Variable, offset, length
+
+    // Name of the definition referred to, if any
+    pub reference: Option<CString16>,
+
 }
 
 // Implementation
@@ -58,12 +138,18 @@ impl ArgOperation {
     // Operation argument validation, with error handling
     pub fn validate(&self) -> Result<(), AppError> {
 
+        // Make sure that the size of the value
+        // fits within a single operation
+        if self.target.size == 0 || self.target.size > config::VALUE_SIZE_MAX {
+            return Err(AppError::ArgSize(self.target.size));
+        }
+
         // For an assignment operation
         if let OperationType::Set(value) = self.action {
 
             // Make sure that the value to be assigned
             // fits within the specified size of the value
-            if value > (1 << (self.target.size * 8)) {
+            if !script::fits_size(value, self.target.size) {
 
                 // Return an error if that's not the case
                 Err(AppError::ArgSizeMismatch(value, self.target.size))
@@ -86,60 +172,193 @@ impl ArgOperation {
 
     }
 
-    // Retrieval as a string, together with the current value
-    pub fn to_string_with_val(&self, value: &UefiValue) -> String {
-
-        // Retrieve the variable name
-        let name = &self.target.name;
+    // Conversion from an HII configuration block, split into
+    // as many operations as needed for each to fit the size limit
+    pub fn from_config_block(name: &CStr16, id: Option<usize>,
+        block: &ConfigBlock) -> Vec<Self> {
 
-        // Retrieve the identifier, if present
-        let id_string = match self.target.id {
-            None => "".to_owned(),
-            Some(id) => format!("{}{id}{}", config::CHAR_ARG_BKT_L, config::CHAR_ARG_BKT_R),
-        };
+        // Split the block into chunks that fit the size limit,
+        // assigning if the block has a value, otherwise querying
+        block.split(config::VALUE_SIZE_MAX).into_iter().map(|(offset, size, value)| Self {
+            action: value.map_or(OperationType::Get, OperationType::Set), hii: false, reference: None,
+            target: OperationTarget { id, name: name.to_owned(), offset, size,
+                values: Vec::new(), layout: None, count: None, vendor: None }}).collect()
 
-        // Retrieve the offset
-        let offset = self.target.offset;
+    }
 
-        // If value size is more than a single byte
-        let size_string = if self.target.size == 1 {
-            "".to_owned()
-        } else {
+    // Conversion to an HII configuration block, together with the value
+    pub fn to_config_block(&self, value: &UefiValue) -> ConfigBlock {
+        ConfigBlock {
+            offset: self.target.offset,
+            value: Some(value.0.clone()),
+            width: self.target.size }
+    }
 
-            // Retrieve the optional size (if not a byte)
-            format!("{}{}{}", config::CHAR_ARG_BKT_L, self.target.size, config::CHAR_ARG_BKT_R)
+    // Retrieval as a string, together with the current value
+    pub fn to_string_with_val(&self, value: &UefiValue) -> String {
 
+        // Retrieve the HII routing prefix, if present
+        let hii_string = if self.hii {
+            format!("{}", config::CHAR_ARG_HII)
+        } else {
+            "".to_owned()
         };
 
         // Retrieve the current value, using UefiValue's implementation
         let value_string = value.to_string_with_size(self.target.size);
 
-        // Format the resulting information and return
-        format!("{name}{id_string}{}{offset:#06x}{size_string}{}{value_string}",
-            config::CHAR_ARG_POS, config::CHAR_ARG_ASS)
+        // Show a reference to a definition with named values the same way,
+        // with the name of the value, if it has one, and the number after it
+        if let Some(reference) = self.reference.as_ref().filter(|_| !self.target.values.is_empty()) {
+            return match script::value_name(&self.target.values, value.to_usize()) {
+                Some(name) => format!("{hii_string}{}{reference}{}{name} {} {value_string}",
+                    config::CHAR_INPUT_REF, config::CHAR_ARG_ASS, config::CHAR_INPUT_COMMENT),
+                None => format!("{hii_string}{}{reference}{}{value_string}",
+                    config::CHAR_INPUT_REF, config::CHAR_ARG_ASS),
+            };
+        }
+
+        // Format the resulting information, using the target's
+        // implementation for the variable, offset and size, and return
+        format!("{hii_string}{}{}{value_string}", self.target, config::CHAR_ARG_ASS)
 
     }
 
 }
 
+// Boot entry command
+// Manages the load options, alongside the operations
+
+#[derive(Clone, Debug)]
+pub enum BootCommand {
+
+    // Add a load option, and append it to the order
+    Create { id: LoadOptionId, description: String, path: CString16 },
+
+    Delete(LoadOptionId),   // Remove a load option, also from the order
+    Disable(LoadOptionId),  // Clear the active attribute
+    Edit { name: CString16, id: Option<usize> },  // Open a variable in the editor
+    Enable(LoadOptionId),   // Set the active attribute
+    List,                   // List load options and the order
+    Next(u16),              // Boot once from a given entry
+    Order(Vec<u16>),        // Move the given entries to the front
+    SecureBoot,             // Show Secure Boot state and keys
+
+    // Write a signed update from a file to a signature database,
+    // either replacing its contents, or appending to them
+    SecureBootWrite { name: String, path: CString16, append: bool },
+
+}
+
 // Option argument list
 #[derive(Debug)]
 pub enum ArgOption {
 
+    Config,    // Output as HII configuration
+    Encoding(TextEncoding),  // Output file encoding
     Force,     // Force-write identical values
-    Restart,   // Restart system when done
+    Format(OutputFormat),  // Results output format
+    Hii,       // Route through HII configuration
+    Quiet,     // Do not show results on the console
+    Restart(RestartType),  // Restart system when done
     Simulate,  // Simulate, do not write
     Usage,     // Show usage information
 
 }
 
+// Restart type
+// How to restart the system when done
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RestartType {
+
+    Cold,      // Cold reset, power cycle
+    Platform,  // Platform-specific reset
+    Setup,     // Reboot into firmware setup (UI)
+    Shutdown,  // Power off
+
+    #[default]
+    Warm,      // Warm reset
+
+}
+
+// Text encoding
+// How the text of a script or output file is stored
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TextEncoding {
+
+    #[default]
+    Utf8,     // UTF-8, including plain ASCII
+    Utf16Be,  // UTF-16 big-endian
+    Utf16Le,  // UTF-16 little-endian, as used by the shell
+
+}
+
+// Output format
+// How the results are presented
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+
+    Json,  // A single JSON document, for automation
+
+    #[default]
+    Text,  // Lines in the input format, with comments
+
+}
+
+// Script parameter
+// Value given on the command line for a parameter declared in the input files
+
+#[derive(Clone, Debug)]
+pub struct ScriptParam {
+
+    pub name: String,  // Name of the constant declared with !param
+    pub value: usize,  // Value to use instead of the one declared
+    pub used: bool,    // Whether any of the input files declared it
+
+}
+
 // Every argument is either
 // an operation, or an option
 #[derive(Debug)]
 pub enum Arg {
 
+    Boot(BootCommand),        // Manage the boot entries
     Operation(ArgOperation),  // Get or set a given UEFI value
     Option(ArgOption),        // Set state in application scope
+    Param(ScriptParam),       // Override a parameter of the scripts
+
+}
+
+// Script step
+// Either an operation, or a block of them run depending
+// on a condition, which is checked only when reached
+
+#[derive(Clone, Debug)]
+pub enum ScriptStep {
+
+    Operation(ArgOperation),  // Get or set a given UEFI value
+
+    // Steps to run if the condition holds, and otherwise
+    Block { condition: Condition, then: Vec<ScriptStep>, otherwise: Vec<ScriptStep> },
+
+}
+
+// Implementation
+// for script steps
+impl ScriptStep {
+
+    // Validation of the operations, including those in the blocks,
+    // whichever way the condition turns out when run
+    pub fn validate(&self) -> Result<(), AppError> {
+        match self {
+            Self::Operation(op) => op.validate(),
+            Self::Block { then, otherwise, .. } =>
+                then.iter().chain(otherwise).try_for_each(|step| step.validate()),
+        }
+    }
 
 }
 
@@ -148,13 +367,34 @@ pub enum Arg {
 #[derive(Debug, Default)]
 pub struct Args {
 
-    // Operation arguments
-    pub op: Vec<ArgOperation>,
+    // Operation arguments, and the blocks
+    // of them checking the variables
+    pub op: Vec<ScriptStep>,
+
+    // Boot entry commands
+    pub boot: Vec<BootCommand>,
+
+    // Input files to read
+    // the script from
+    pub input: Vec<CString16>,
+
+    // Output file to write
+    // the results to, if any
+    pub output: Option<CString16>,
+
+    // Parameters to override
+    // in the input files
+    pub params: Vec<ScriptParam>,
 
     // Option arguments
     // Application-scope state
+    pub config: bool,
+    pub encoding: TextEncoding,
     pub force: bool,
-    pub restart: bool,
+    pub format: OutputFormat,
+    pub hii: bool,
+    pub quiet: bool,
+    pub restart: Option<RestartType>,
     pub simulate: bool,
     pub usage: bool,
 
@@ -189,14 +429,31 @@ impl Args {
 #[derive(Debug)]
 pub enum InputEntry {
 
+    Boot(BootCommand),        // Manage the boot entries
     Operation(ArgOperation),  // Get or set a given UEFI value
     Option(ArgOption),        // Set state in application scope
 
+    // Set a constant to be used in the expressions that follow,
+    // either fixed, or a parameter the command line can override
+    Constant { name: String, value: usize, param: bool },
+
+    // Declare a structure to be bound to the definitions that follow
+    Layout(TargetLayout),
+
     // Define a target to be referenced elsewhere in the file
     TargetDefinition { name: CString16, target: OperationTarget },
 
-    // Reference of a target defined elsewhere in the file
-    TargetReference { name: CString16, action: OperationType },
+    // Reference of a target defined elsewhere in the file,
+    // possibly to an element or a field of it
+    TargetReference { name: CString16, path: TargetPath, action: OperationType, hii: bool },
+
+    // Conditional block checking the variables, left
+    // for when the operations are run: its start with
+    // the condition, the else directive, and its end
+    If(Condition),
+    Else,
+    EndIf,
+
 }
 
 // Implementation
@@ -215,24 +472,254 @@ impl InputEntry {
 
     }
 
-    // Retrieval of an argument for the operation
-    pub fn as_op(&self) -> &ArgOperation {
+}
 
-        match self {
-            Self::Operation(op) => op,
+// Input line
+// Entry parsed from a line of the input, along with where it came from
 
-            // Fail if attempted on an incorrect entry type
-            _ => panic!("{}", msg::ERR_INT_OP)
+#[derive(Debug)]
+pub struct InputLine {
+    pub entry: InputEntry,  // What the line holds
+    pub line: usize,        // Line number, from one
+    pub source: String,     // File path, or the standard input
+    pub text: String,       // The line as written, for the errors
+}
+
+// Input scope
+// What the lines parsed so far leave for those that follow, across the files
+
+#[derive(Debug, Default)]
+pub struct InputScope {
+    pub consts: Vec<(String, usize)>,             // Constants set
+    pub defs: Vec<(CString16, OperationTarget)>,  // Targets defined
+    pub layouts: Vec<TargetLayout>,               // Structures declared
+}
+
+// Implementation
+// for input scopes
+impl InputScope {
+
+    // Constants to use in a value assigned to a definition, or compared with
+    // it: those set so far, and the names of its values, if it is defined,
+    // which take precedence, same as a constant set later
+    pub fn consts_for(&self, name: &CStr16) -> Vec<(String, usize)> {
+        let values = self.defs.iter().rev().find(|(def_name, _)| **def_name == *name)
+            .map(|(_, target)| target.values.as_slice()).unwrap_or_default();
+        script::with_values(&self.consts, values)
+    }
+
+}
+
+// Condition
+// Checked by the conditional blocks and the requirements in the input,
+// comparing the variables given the same way as the operations
+
+pub type Condition = script::Condition<OperationTarget>;
+
+// Interactive mode command
+// Entered at the prompt, in addition to the input entries
 
+pub enum InteractiveCommand {
+
+    // Show all contents of a variable
+    Dump { name: CString16, id: Option<usize> },
+
+    Entry(InputEntry),  // Run an input entry right away
+    Exit,               // End the session
+    Help,               // Show the available commands
+    List,               // Show the definitions
+
+}
+
+// Output
+// Where the results go: the console, and optionally a file
+
+#[derive(Debug, Default)]
+pub struct Output {
+    pub file: Option<Vec<String>>,  // Lines kept to write to the file, if any
+    pub json: Option<JsonOutput>,   // Document being built, if machine-readable
+    pub quiet: bool,                // Not shown on the console
+}
+
+// Implementation
+impl Output {
+
+    // Outputs an error message, shown on the console, or
+    // recorded in the document if the output is machine-readable
+    pub fn error(&mut self, prefix: &str, e: &AppError) {
+
+        // Record in the document, if any
+        if let Some(json) = &mut self.json {
+            json.errors.push(format!("{prefix}: {e}"));
         }
 
+        // Show on the console, unless it is where the document goes
+        if self.json.is_none() || self.quiet {
+            println!("{prefix}: {e}");
+        }
+
+    }
+
+    // Completes the document, if the output is machine-readable,
+    // and outputs it the same way as a line of the results
+    pub fn finish(&mut self) {
+        if let Some(json) = self.json.take() {
+            self.line(json.to_json());
+        }
     }
 
+    // Outputs a line of the results
+    pub fn line(&mut self, line: String) {
+
+        // Keep for the document instead, if machine-readable
+        if let Some(json) = &mut self.json {
+            json.output.push(line);
+            return;
+        }
+
+        // Show on the console, unless quiet
+        if !self.quiet {
+            println!("{line}");
+        }
+
+        // Keep for the file, if any
+        if let Some(lines) = &mut self.file {
+            lines.push(line);
+        }
+
+    }
+
+}
+
+// JSON output
+// Machine-readable results, output as a single document when complete
+
+#[derive(Debug, Default)]
+pub struct JsonOutput {
+    pub errors: Vec<String>,               // Error messages, as on the console
+    pub operations: Vec<OperationRecord>,  // Outcome of each operation
+    pub output: Vec<String>,               // Other results, as they would be shown
+    pub simulate: bool,                    // Nothing was actually written
+    pub system: SystemInfo,                // Firmware and board identification
+}
+
+// Implementation
+impl JsonOutput {
+
+    // Formats the whole document, one operation per line
+    pub fn to_json(&self) -> String {
+
+        // Formats an array of items already in the JSON format
+        let array = |items: Vec<String>| if items.is_empty() {
+            "[]".to_owned()
+        } else {
+            format!("[\n    {}\n  ]", items.join(",\n    "))
+        };
+
+        // Formats an array of strings
+        let strings = |lines: &[String]| array(lines.iter().map(|line| quote_json(line)).collect());
+
+        // Board identification, if known, under the same names as in the conditions
+        let smbios = self.system.smbios.as_ref().map_or_else(|| config::JSON_NULL.to_owned(), |smbios|
+            format!("{{\"{}\": {}, \"{}\": {}, \"{}\": {}, \"{}\": {}, \"{}\": {}}}",
+                config::COND_KEY_MANUFACTURER, quote_json(&smbios.system_manufacturer),
+                config::COND_KEY_PRODUCT, quote_json(&smbios.system_product),
+                config::COND_KEY_BOARD, quote_json(&smbios.board_product),
+                config::COND_KEY_BIOS, quote_json(&smbios.bios_version),
+                config::COND_KEY_BIOS_DATE, quote_json(&smbios.bios_date)));
+
+        format!("{{\n  \"firmware\": {{\"vendor\": {}, \"revision\": {}, \"uefi\": {}}},\n  \
+            \"smbios\": {smbios},\n  \"simulate\": {},\n  \"operations\": {},\n  \"output\": {},\n  \
+            \"errors\": {}\n}}",
+            quote_json(&self.system.firmware_vendor), quote_json(&self.system.firmware_revision),
+            quote_json(&self.system.uefi_revision), self.simulate,
+            array(self.operations.iter().map(OperationRecord::to_json).collect()),
+            strings(&self.output), strings(&self.errors))
+
+    }
+
+}
+
+// Operation record
+// Outcome of an operation, for the machine-readable output
+
+#[derive(Debug)]
+pub struct OperationRecord {
+    pub error: Option<String>,      // Error message, if failed
+    pub new: Option<UefiValue>,     // Value requested, if setting
+    pub old: Option<UefiValue>,     // Value before the operation, if read
+    pub status: OperationStatus,    // What happened
+    pub target: OperationTarget,    // Variable, offset and size
+    pub vendor: Option<[u8; 16]>,   // Vendor GUID of the variable, if found
+}
+
+// Implementation
+impl OperationRecord {
+
+    // Formats as a JSON object, on a single line
+    pub fn to_json(&self) -> String {
+
+        // Formats an optional value, of the operation size
+        let value = |value: &Option<UefiValue>| value.as_ref()
+            .map(|value| quote_json(&value.to_string_with_size(self.target.size)))
+            .unwrap_or_else(|| config::JSON_NULL.to_owned());
+
+        format!("{{\"name\": {}, \"guid\": {}, \"id\": {}, \"offset\": {}, \"size\": {}, \
+            \"old\": {}, \"new\": {}, \"status\": \"{}\", \"error\": {}}}",
+            quote_json(&self.target.name.to_string()),
+            self.vendor.map(|vendor| quote_json(&format_guid(&vendor)))
+                .unwrap_or_else(|| config::JSON_NULL.to_owned()),
+            self.target.id.map(|id| id.to_string())
+                .unwrap_or_else(|| config::JSON_NULL.to_owned()),
+            self.target.offset, self.target.size, value(&self.old), value(&self.new), self.status,
+            self.error.as_deref().map(quote_json)
+                .unwrap_or_else(|| config::JSON_NULL.to_owned()))
+
+    }
+
+}
+
+// Operation status
+// What happened as a result of an operation
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OperationStatus {
+
+    Failed,   // Could not be performed
+    Read,     // Value retrieved
+    Skipped,  // Value already set, nothing written
+    Written,  // Value written, or would be if simulating
+
+}
+
+// Implementation: formatting for display,
+// as the status names in the JSON output
+impl Display for OperationStatus {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", match self {
+            Self::Failed => config::JSON_STATUS_FAILED,
+            Self::Read => config::JSON_STATUS_READ,
+            Self::Skipped => config::JSON_STATUS_SKIPPED,
+            Self::Written => config::JSON_STATUS_WRITTEN,
+        })
+    }
+
+}
+
+// HII configuration response
+// Values collected for a UEFI variable, to be output together
+
+pub struct ConfigResponse {
+    pub config: ConfigString,  // Configuration string with the values
+    pub id: Option<usize>,     // Optional to tell namesakes
+    pub name: CString16,       // Name of the UEFI variable
 }
 
 // UEFI Value
 // Byte array at a given offset within a UEFI variable
 
+#[derive(Clone, Debug)]
 pub struct UefiValue(pub Vec<u8>);
 
 // Implementation
@@ -240,17 +727,17 @@ impl UefiValue {
 
     // Assignment from a given value and length
     pub fn from_usize(value: usize, length: usize) -> Self {
-        let value = value & ((1 << (length * 8)) - 1);
-        Self(value.to_le_bytes()[0 .. length].to_vec())
+        Self(hii::value_to_bytes(value, length))
+    }
+
+    // Retrieval as a number, least significant byte first
+    pub fn to_usize(&self) -> usize {
+        hii::value_from_bytes(&self.0)
     }
 
     // Retrieval as a string of a given length
     pub fn to_string_with_size(&self, length: usize) -> String {
-        let mut bytes = [0; 8];
-        bytes[0 .. self.0.len()].copy_from_slice(&self.0);
-
-        format!("{:#0size$x}", usize::from_ne_bytes(bytes), size = 2 + length * 2)
-
+        format!("{:#0size$x}", self.to_usize(), size = 2 + length * 2)
     }
 }
 
@@ -259,11 +746,7 @@ impl UefiValue {
 impl Display for UefiValue {
 
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        let mut bytes = [0; 8];
-        bytes[0 .. self.0.len()].copy_from_slice(&self.0);
-
-        write!(f, "{:#04x}", usize::from_ne_bytes(bytes))
-
+        write!(f, "{:#04x}", self.to_usize())
     }
 
 }
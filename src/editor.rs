@@ -0,0 +1,453 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: Editor
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Provides a full-screen hex editor for UEFI variables
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{borrow::ToOwned, format, string::{String, ToString}, vec::Vec};
+use core::fmt::Write;
+use uefi::{CStr16, prelude::*,
+    proto::console::text::{Color, Key, Output, ScanCode},
+    table::runtime::VariableAttributes};
+use uefi_services::println;
+
+// Symbols from other modules
+use crate::config;
+use crate::config::locale as msg;
+use crate::data::{ArgOperation, OperationTarget, OperationType, UefiValue, UefiVariable};
+use crate::error::AppError;
+use crate::firmware::{export_hii_packages, get_global_variable, get_whole_variable,
+    read_key, set_value};
+use crate::ifr;
+
+// Colors (foreground, background)
+const COLOR_CHANGED: (Color, Color) = (Color::Yellow, Color::Black);    // Byte not yet written
+const COLOR_CURSOR:  (Color, Color) = (Color::Black, Color::LightGray);  // Byte under the cursor
+const COLOR_NORMAL:  (Color, Color) = (Color::LightGray, Color::Black);  // Everything else
+const COLOR_STATUS:  (Color, Color) = (Color::Black, Color::Cyan);       // Title and key lines
+
+// Screen size to assume if the console does not report it
+const SCREEN_DEFAULT: (usize, usize) = (80, 25);
+
+// Lines taken by other than the bytes: title, information and keys
+const SCREEN_OTHER_LINES: usize = 3;
+
+// Setting prompt
+// Setup question stored in a range of bytes
+
+struct Prompt {
+    offset: usize,  // Offset within the variable
+    size: usize,    // Value size in bytes
+    text: String,   // Prompt shown in setup
+}
+
+// Editor state
+// Contents being edited and the position within
+
+struct Editor {
+    content: Vec<u8>,      // Contents with the changes
+    cursor: usize,         // Offset of the byte under the cursor
+    high: bool,            // Next digit goes to the high half of the byte
+    message: String,       // Outcome of the last action
+    original: Vec<u8>,     // Contents as last read or written
+    prompts: Vec<Prompt>,  // Setup questions stored in the variable
+    top: usize,            // First line shown
+}
+
+// Implementation
+impl Editor {
+
+    // Number of bytes changed but not yet written
+    fn changed(&self) -> usize {
+        self.content.iter().zip(&self.original).filter(|(a, b)| a != b).count()
+    }
+
+    // Moves the cursor by a given number of bytes, within the contents
+    fn move_by(&mut self, delta: isize) {
+        self.cursor = self.cursor.saturating_add_signed(delta).min(self.content.len() - 1);
+        self.high = true;
+    }
+
+    // Enters a hexadecimal digit into the byte under the cursor,
+    // moving on to the next byte after the second digit
+    fn enter(&mut self, digit: u8) {
+        let byte = &mut self.content[self.cursor];
+        if self.high {
+            *byte = *byte & 0x0F | digit << 4;
+            self.high = false;
+        } else {
+            *byte = *byte & 0xF0 | digit;
+            self.move_by(1);
+        }
+    }
+
+    // Writes the changes using the same path as the operations, grouping
+    // the adjacent changed bytes into values as large as allowed, returns
+    // the operations performed, formatted the same way as for the output
+    fn write(&mut self, system_table: &SystemTable<Boot>, var: &UefiVariable,
+        id: Option<usize>, simulate: bool) -> Result<Vec<String>, AppError> {
+
+        // Set up output and helper variables
+        let mut lines = Vec::new();
+        let mut offset = 0;
+
+        // Look for the changed bytes
+        while offset < self.content.len() {
+
+            // Skip unchanged
+            if self.content[offset] == self.original[offset] {
+                offset += 1;
+                continue;
+            }
+
+            // Extend over the adjacent changed bytes
+            let mut size = 1;
+            while size < config::VALUE_SIZE_MAX && offset + size < self.content.len()
+                && self.content[offset + size] != self.original[offset + size] {
+                size += 1;
+            }
+
+            // Write the value, and keep it as written
+            let value = UefiValue(self.content[offset .. offset + size].to_vec());
+            set_value(system_table, &var.name, id, offset, size, &value, false, false, simulate)?;
+            self.original[offset .. offset + size].copy_from_slice(&value.0);
+
+            // Format the operation
+            let mut bytes = [0; 8];
+            bytes[0 .. size].copy_from_slice(&value.0);
+            let op = ArgOperation { action: OperationType::Set(usize::from_le_bytes(bytes)),
+                hii: false, reference: None,
+                target: OperationTarget { id, name: var.name.clone(), offset, size,
+                    values: Vec::new(), layout: None, count: None, vendor: None } };
+            lines.push(op.to_string_with_val(&value));
+
+            // Move on past the value
+            offset += size;
+
+        }
+
+        // Return the operations
+        Ok(lines)
+
+    }
+
+    // Draws the whole screen
+    fn draw(&mut self, out: &mut Output, title: &str, (columns, rows): (usize, usize)) {
+
+        // Lines available for the bytes
+        let lines = rows - SCREEN_OTHER_LINES;
+
+        // Scroll to keep the cursor visible
+        let line = self.cursor / config::EDITOR_LINE_SIZE;
+        if line < self.top {
+            self.top = line;
+        } else if line >= self.top + lines {
+            self.top = line + 1 - lines;
+        }
+
+        // Color of a given byte
+        let color = |i: usize| if i == self.cursor {
+            COLOR_CURSOR
+        } else if self.content[i] != self.original[i] {
+            COLOR_CHANGED
+        } else {
+            COLOR_NORMAL
+        };
+
+        // Title
+        put(out, (0, 0), COLOR_STATUS, &pad(title, columns));
+
+        // Bytes, in hexadecimal and as characters
+        for row in 0 .. lines {
+
+            // Start of the line, if any
+            let start = (self.top + row) * config::EDITOR_LINE_SIZE;
+            let end = (start + config::EDITOR_LINE_SIZE).min(self.content.len());
+
+            // Parts of the line in different colors
+            let mut parts = Vec::new();
+
+            // Offset, if there are bytes left
+            if start < end {
+                parts.push((COLOR_NORMAL, format!("{start:#06x}  ")));
+
+                // Hexadecimal
+                for i in start .. start + config::EDITOR_LINE_SIZE {
+                    if i < end {
+                        parts.push((color(i), format!("{:02x}", self.content[i])));
+                        parts.push((COLOR_NORMAL, " ".to_owned()));
+                    } else {
+                        parts.push((COLOR_NORMAL, "   ".to_owned()));
+                    }
+                }
+
+                // Characters
+                parts.push((COLOR_NORMAL, " ".to_owned()));
+                for i in start .. end {
+                    let b = self.content[i];
+                    let c = if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' };
+                    parts.push((color(i), c.to_string()));
+                }
+
+            }
+
+            // Write the parts one after another
+            let mut column = 0;
+            for (color, text) in parts {
+                put(out, (column, row + 1), color, &text);
+                column += text.chars().count();
+            }
+
+            // Clear the rest of the line
+            put(out, (column, row + 1), COLOR_NORMAL, &pad("", columns.saturating_sub(column)));
+
+        }
+
+        // Byte under the cursor, with its value before the change
+        let byte = self.content[self.cursor];
+        let mut info = format!("{:#06x}: {byte:#04x}", self.cursor);
+        if byte != self.original[self.cursor] {
+            info.push_str(&format!(" ({} {:#04x})", msg::EDITOR_ORIGINAL, self.original[self.cursor]));
+        }
+
+        // Setup questions stored there, if any
+        for prompt in self.prompts.iter()
+            .filter(|p| (p.offset .. p.offset + p.size).contains(&self.cursor)) {
+            info.push_str(&format!("  {}", prompt.text));
+            if prompt.size > 1 {
+                info.push_str(&format!(" {}{:#06x}{}{}{}", config::CHAR_ARG_POS, prompt.offset,
+                    config::CHAR_ARG_BKT_L, prompt.size, config::CHAR_ARG_BKT_R));
+            }
+        }
+
+        // Outcome of the last action
+        if !self.message.is_empty() {
+            info.push_str(&format!("  {} {}", config::CHAR_INPUT_COMMENT, self.message));
+        }
+
+        // Information and keys
+        put(out, (0, rows - 2), COLOR_NORMAL, &pad(&info, columns));
+        put(out, (0, rows - 1), COLOR_STATUS, &pad(msg::EDITOR_KEYS, columns));
+
+    }
+
+}
+
+// Public Functions
+
+// Opens a UEFI variable in a full-screen hex editor, where changes
+// are highlighted until written, and can be undone until then
+pub fn run_editor(system_table: &SystemTable<Boot>,
+    name: &CStr16, id: Option<usize>, simulate: bool) -> Result<(), AppError> {
+
+    // Attempt to retrieve the specified variable
+    let var = get_whole_variable(system_table, name, id)?;
+
+    // Authenticated variables can only be written with a signed update
+    if var.attributes.contains(VariableAttributes::TIME_BASED_AUTHENTICATED_WRITE_ACCESS) {
+        return Err(AppError::UefiVarAuth(var.name.to_string()));
+    }
+
+    // Nothing to edit in an empty variable
+    if var.content.is_empty() {
+        println!("# {name}: {}", msg::EDITOR_EMPTY);
+        return Ok(());
+    }
+
+    // Set up the editor state
+    let mut editor = Editor {
+        content: var.content.clone(), cursor: 0, high: true, message: String::new(),
+        original: var.content.clone(), prompts: find_prompts(system_table, &var), top: 0 };
+
+    // Obtain the console for reading keys and drawing the screen
+    // Note: unlike the rest of the application, the editor needs
+    // mutable access, which is why a separate copy is obtained
+    let mut console = uefi_services::system_table();
+
+    // Determine the screen size, the smallest one
+    // has to leave at least a single line for the bytes
+    let screen = console.stdout().current_mode().ok().flatten()
+        .map_or(SCREEN_DEFAULT, |mode| (mode.columns(), mode.rows()));
+    if screen.1 <= SCREEN_OTHER_LINES {
+        return Err(AppError::EditorScreen);
+    }
+
+    // Prepare the screen
+    let _ = console.stdout().enable_cursor(false);
+    let _ = console.stdout().clear();
+
+    // Operations written, to be shown when done
+    let mut written = Vec::new();
+
+    // Asked once to discard the changes
+    let mut discard = false;
+
+    // Keep editing until asked to quit
+    loop {
+
+        // Title, with the number of bytes changed
+        let title = format!(" {}: {} {}, {} {}{}", var.name,
+            editor.content.len(), msg::EDITOR_BYTES, editor.changed(), msg::EDITOR_CHANGED,
+            if simulate { msg::EDITOR_SIMULATE } else { "" });
+
+        // Draw the screen and wait for a key
+        editor.draw(console.stdout(), &title, screen);
+        let key = read_key(&mut console);
+
+        // Clear the outcome of the previous action
+        editor.message.clear();
+
+        // Number of bytes in a page
+        let page = ((screen.1 - SCREEN_OTHER_LINES) * config::EDITOR_LINE_SIZE) as isize;
+        let line = config::EDITOR_LINE_SIZE as isize;
+
+        match key {
+
+            // Move the cursor
+            Key::Special(ScanCode::LEFT) => editor.move_by(-1),
+            Key::Special(ScanCode::RIGHT) => editor.move_by(1),
+            Key::Special(ScanCode::UP) => editor.move_by(-line),
+            Key::Special(ScanCode::DOWN) => editor.move_by(line),
+            Key::Special(ScanCode::PAGE_UP) => editor.move_by(-page),
+            Key::Special(ScanCode::PAGE_DOWN) => editor.move_by(page),
+
+            // Move to the start or the end of the line
+            Key::Special(ScanCode::HOME) => {
+                editor.move_by(-((editor.cursor % config::EDITOR_LINE_SIZE) as isize))
+            }
+            Key::Special(ScanCode::END) => {
+                editor.move_by(line - 1 - (editor.cursor % config::EDITOR_LINE_SIZE) as isize)
+            }
+
+            // Undo the change to the byte under the cursor
+            Key::Special(ScanCode::DELETE) => {
+                editor.content[editor.cursor] = editor.original[editor.cursor];
+                editor.high = true;
+            }
+
+            // Undo all the changes
+            Key::Special(ScanCode::FUNCTION_3) => {
+                editor.content.copy_from_slice(&editor.original);
+                editor.high = true;
+                editor.message = msg::EDITOR_UNDONE.to_owned();
+            }
+
+            // Write the changes
+            Key::Special(ScanCode::FUNCTION_2) => {
+                match editor.write(system_table, &var, id, simulate) {
+                    Ok(mut lines) => {
+                        editor.message = format!("{} {} {}",
+                            msg::EDITOR_WRITTEN[0], lines.len(), msg::EDITOR_WRITTEN[1]);
+                        written.append(&mut lines);
+                    }
+                    Err(e) => editor.message = e.to_string(),
+                }
+            }
+
+            // Quit, asking first if there are changes not written
+            Key::Special(ScanCode::ESCAPE) => {
+                if discard || editor.changed() == 0 {
+                    break;
+                }
+                editor.message = msg::EDITOR_DISCARD.to_owned();
+                discard = true;
+                continue;
+            }
+
+            // Enter a hexadecimal digit
+            Key::Printable(c) => {
+                if let Some(digit) = char::from(c).to_digit(16) {
+                    editor.enter(digit as u8);
+                }
+            }
+
+            // Ignore anything else
+            _ => {}
+
+        }
+
+        // Any other key cancels quitting
+        discard = false;
+
+    }
+
+    // Restore the screen
+    let _ = console.stdout().set_color(COLOR_NORMAL.0, COLOR_NORMAL.1);
+    let _ = console.stdout().clear();
+    let _ = console.stdout().enable_cursor(true);
+
+    // Show the operations written, so that they can be used as input
+    for line in written {
+        println!("{line}");
+    }
+
+    // Return no error
+    Ok(())
+
+}
+
+// Private Functions
+
+// Finds the setup questions stored in a UEFI variable, with the prompts
+// in the platform language, using the forms and strings in the HII database
+// Note: an empty list is returned if the forms cannot be obtained
+fn find_prompts(system_table: &SystemTable<Boot>, var: &UefiVariable) -> Vec<Prompt> {
+
+    // Export the packages
+    let Ok(packages) = export_hii_packages(system_table) else {
+        return Vec::new();
+    };
+
+    // Determine the platform language, which is a terminated ASCII string
+    let language = get_global_variable(system_table, config::VAR_PLATFORM_LANG).ok().flatten()
+        .map(|bytes| bytes.iter().take_while(|&&b| b != 0).map(|&b| b as char).collect::<String>())
+        .filter(|language| !language.is_empty())
+        .unwrap_or_else(|| config::HII_LANG_DEFAULT.to_owned());
+
+    // Variable name and vendor GUID to match
+    let name = var.name.to_string();
+    let vendor = var.vendor.0.to_bytes();
+
+    // Go through the questions of all the form sets
+    let strings = ifr::find_string_packages(&packages);
+    let mut prompts = Vec::new();
+    for form_set in ifr::find_form_packages(&packages) {
+
+        // Strings in the platform language are needed
+        let Some(strings) = ifr::find_strings_for(&form_set, &strings, &language) else {
+            continue;
+        };
+
+        // Questions stored in the variable
+        for question in &form_set.questions {
+            if form_set.var_store(question.var_store)
+                .is_some_and(|v| v.name == name && v.guid == vendor) {
+                prompts.push(Prompt { offset: question.offset as usize,
+                    size: question.size, text: strings.get(question.prompt).to_owned() });
+            }
+        }
+
+    }
+
+    // Return the result
+    prompts
+
+}
+
+// Pads or truncates text to a given width
+// Note: the last column is left out, since writing
+// there might scroll the screen on some consoles
+fn pad(text: &str, width: usize) -> String {
+    let width = width.saturating_sub(1);
+    format!("{:<width$}", text.chars().take(width).collect::<String>())
+}
+
+// Writes text at a given position in a given color
+// Note: drawing errors are ignored, there is no way to show them anyway
+fn put(out: &mut Output, (column, row): (usize, usize), color: (Color, Color), text: &str) {
+    let _ = out.set_cursor_position(column, row);
+    let _ = out.set_color(color.0, color.1);
+    let _ = out.write_str(text);
+}
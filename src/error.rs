@@ -8,13 +8,14 @@
 
 // Declare fully-qualified symbols
 // to be used in the local scope
-use alloc::string::String;
+use alloc::{borrow::ToOwned, boxed::Box, string::String, vec, vec::Vec};
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use uefi::{data_types::FromSliceWithNulError, proto::loaded_image::LoadOptionsError, Status};
 
 // Symbols from other modules
 use crate::config;
 use crate::config::locale as msg;
+use crate::script::{ErrorOffset, ScriptError};
 
 // Error list
 // Note: some errors handled directly,
@@ -25,54 +26,193 @@ pub enum AppError {
     // Args
     Arg(String),
     ArgAss,
+    ArgBoot(String),
+    ArgConst(String),
+    ArgEncoding(String),
+    ArgExpr(String),
+    ArgExprRange(String),
+    ArgFormat(String),
+    ArgInput,
     ArgMore(String),
     ArgNone,
     ArgNumDec(String),
     ArgNumHex(String),
     ArgNumHexPrefix(String),
     ArgOpt,
+    ArgOutput,
+    ArgParam(String),
     ArgPos,
     ArgPosBktL,
     ArgPosBktR,
+    ArgRestart(String),
+    ArgSize(usize),
     ArgSizeLimit(String),
     ArgSizeMismatch(usize, usize),
     ArgVarBktL,
     ArgVarBktR,
+    ArgVarGuid(String),
+
+    // Boot entries
+    BootAuth(String),
+    BootExists(String),
+    BootOption(String),
+    BootOrder(String),
+    BootPath(String),
+    BootPathFind,
+
+    // Editor
+    EditorScreen,
+
+    // File
+    FileOpen(String, Status),
+    FileRead(String, Status),
+    FileText(String),
+    FileWrite(String, Status),
 
     // Input
     Input(String),
+    InputConfig(String),
+    InputConfigNone,
+    InputCond(String),
+    InputCondKey(String),
+    InputCondText(String),
+    InputCondVersion(String),
+    InputConst(String),
     InputDef(String),
     InputDefSet(String),
+    InputDefValues(String),
+    InputCommand(String),
+    InputElse,
+    InputEndif,
+    InputIf,
+    InputIfEntry,
+    InputInclude(String),
+    InputIncludeCycle(String),
+    InputIncludeDepth,
+    InputLine(InputLocation, Box<AppError>),
+    InputMany(Vec<AppError>),
     InputNone,
+    InputOffset(usize, Box<AppError>),
     InputOpt(String),
     //InputRead,           // firmware::read_stream()
     InputRef(String),
     InputRefNone(String),
+    InputRefPath(String),
+    InputRequire(String, String),
+    InputRequireValue,
+    InputStruct(String),
+    InputStructNone(String),
 
     // Internal
     //IntDef,              // data::InputEntry::as_def()
-    //IntOp,               // data::InputEntry::as_op()
     //IntSplit,            // string::CStr16Ext::split()
 
     // UEFI
     //UefiInit,            // main::main()
+    UefiHiiExport(Status),
+    UefiHiiDbExport(Status),
+    UefiHiiDbFind,
+    UefiHiiDbOpen,
+    UefiHiiExtract(String, Status),
+    UefiHiiFind,
+    UefiHiiNone(String),
+    UefiHiiOpen,
+    UefiHiiRoute(String, Status),
+    UefiHiiValue(String),
     UefiLoad,
     UefiLoadOpt(LoadOptionsError),
     UefiPathConv,
     UefiPathFind,
     UefiPathNone,
     UefiPathOpen,
+    UefiRestartSetup,
+    UefiVarAuth(String),
     UefiVarConv(FromSliceWithNulError),
+    UefiVarDelete(String, Status),
     UefiVarGet(String, Status),
     UefiVarGetMany,
     UefiVarGetNone(String),
     UefiVarList(Status),
     UefiVarSet(String, Status),
+    UefiVarSetAuth(String, Status),
     UefiVarSize((usize, usize), usize),
     UefiVarSizeGet(String, Status),
 
 }
 
+// Location in the input
+// Where an error was found, shown along with the line itself
+
+#[derive(Debug)]
+pub struct InputLocation {
+    pub column: usize,   // Column number, from one
+    pub line: usize,     // Line number, from one
+    pub source: String,  // File path, or the standard input
+    pub text: String,    // The line as written, including any comment
+}
+
+// Implementation
+impl AppError {
+
+    // Adds the location in the input to the error, given the line as written,
+    // with the column where the part of the line the error refers to starts,
+    // taken from the offset the error carries, if any, or otherwise the start
+    // of the line; an error that already has a location points into
+    // an included file
+    pub fn at(self, source: &str, line: usize, text: &str) -> Self {
+
+        // Keep the location already present
+        if let Self::InputLine(..) | Self::InputMany(_) = self {
+            return self;
+        }
+
+        // Take the offset into the entry, if any
+        let (offset, e) = match self {
+            Self::InputOffset(offset, e) => (offset, *e),
+            e => (0, e),
+        };
+
+        // Add the location, with the column counted in characters
+        Self::InputLine(InputLocation { column: offset + 1,
+            line, source: source.to_owned(), text: text.to_owned() }, Box::new(e))
+
+    }
+
+    // Combines the errors found in a single pass, unless there is just one
+    pub fn many(errors: Vec<AppError>) -> Self {
+
+        // Flatten any errors already combined
+        let mut errors = errors.into_iter().flat_map(|e| match e {
+            Self::InputMany(errors) => errors,
+            e => vec![e],
+        }).collect::<Vec<_>>();
+
+        // Return a single error as it is
+        if errors.len() == 1 {
+            errors.remove(0)
+        } else {
+            Self::InputMany(errors)
+        }
+
+    }
+
+}
+
+// Implementation
+// for moving the errors within the input
+impl ErrorOffset for AppError {
+
+    // The location already added is kept as it is
+    fn offset(self, offset: usize) -> Self {
+        match self {
+            Self::InputLine(..) | Self::InputMany(_) => self,
+            Self::InputOffset(inner, e) => Self::InputOffset(inner + offset, e),
+            e => Self::InputOffset(offset, Box::new(e)),
+        }
+    }
+
+}
+
 // Error display implementation
 impl Display for AppError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
@@ -90,6 +230,44 @@ impl Display for AppError {
                 write!(f, "{} ({}) {}", msg::ERR_ARG_ASS[0], config::CHAR_ARG_ASS, msg::ERR_ARG_POS[1])
             }
 
+            // Boot entry command malformed
+            Self::ArgBoot(string) => {
+                write!(f, "{} \"{string}\"", msg::ERR_ARG_BOOT)
+            }
+
+            // Constant not set
+            Self::ArgConst(string) => {
+                write!(f, "{} \"{string}\"", msg::ERR_ARG_CONST)
+            }
+
+            // Unknown encoding
+            Self::ArgEncoding(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_ARG_ENCODING[0], msg::ERR_ARG_ENCODING[1])
+            }
+
+            // Malformed expression
+            Self::ArgExpr(string) => {
+                write!(f, "{} \"{string}\"", msg::ERR_ARG_EXPR)
+            }
+
+            // Expression out of range
+            Self::ArgExprRange(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_ARG_EXPR_RANGE[0], msg::ERR_ARG_EXPR_RANGE[1])
+            }
+
+            // Unknown output format
+            Self::ArgFormat(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_ARG_FORMAT[0], msg::ERR_ARG_FORMAT[1])
+            }
+
+            // Input file path missing
+            Self::ArgInput => {
+                write!(f, "{} ({} {})", msg::ERR_ARG_INPUT, config::OPT_ARG_INPUT, config::OPT_ARG_INPUT_LONG)
+            }
+
             // More expected
             Self::ArgMore(string) => {
                 write!(f, "{}: {string}", msg::ERR_ARG_MORE)
@@ -130,6 +308,17 @@ impl Display for AppError {
                 write!(f, "{}", msg::ERR_ARG_OPT)
             }
 
+            // Output file path missing
+            Self::ArgOutput => {
+                write!(f, "{} ({} {})", msg::ERR_ARG_OUTPUT, config::OPT_ARG_OUTPUT, config::OPT_ARG_OUTPUT_LONG)
+            }
+
+            // Parameter not declared
+            Self::ArgParam(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_ARG_PARAM[0], msg::ERR_ARG_PARAM[1])
+            }
+
             // Incorrect offset specification
             Self::ArgPos => {
                 write!(f, "{} ({}) {}", msg::ERR_ARG_POS[0], config::CHAR_ARG_POS, msg::ERR_ARG_POS[1])
@@ -145,6 +334,22 @@ impl Display for AppError {
                 write!(f, "{}", msg::ERR_ARG_VAR_BKT_R)
             }
 
+            // Variable vendor GUID
+            Self::ArgVarGuid(string) => {
+                write!(f, "{}: \"{string}\"", msg::ERR_ARG_VAR_GUID)
+            }
+
+            // Unknown restart type
+            Self::ArgRestart(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_ARG_RESTART[0], msg::ERR_ARG_RESTART[1])
+            }
+
+            // Value size out of range
+            Self::ArgSize(size) => {
+                write!(f, "{} {size} {}", msg::ERR_ARG_SIZE[0], msg::ERR_ARG_SIZE[1])
+            }
+
             // Number too large
             Self::ArgSizeLimit(string) => {
                 write!(f, "{} {string} {}",
@@ -158,6 +363,67 @@ impl Display for AppError {
                     msg::ERR_ARG_SIZE_MISMATCH[2], width = 2 + size * 2)
             }
 
+            // Boot entries
+
+            // Not an authenticated variable update
+            Self::BootAuth(path) => {
+                write!(f, "{}: \"{path}\"", msg::ERR_BOOT_AUTH)
+            }
+
+            // Load option already exists
+            Self::BootExists(name) => {
+                write!(f, "{}: {name}", msg::ERR_BOOT_EXISTS)
+            }
+
+            // Load option malformed
+            Self::BootOption(name) => {
+                write!(f, "{}: {name}", msg::ERR_BOOT_OPTION)
+            }
+
+            // Load option order malformed
+            Self::BootOrder(name) => {
+                write!(f, "{}: {name}", msg::ERR_BOOT_ORDER)
+            }
+
+            // Device path conversion from text
+            Self::BootPath(path) => {
+                write!(f, "{}: \"{path}\"", msg::ERR_BOOT_PATH)
+            }
+
+            // Failed to locate device-path-from-text protocol
+            Self::BootPathFind => {
+                write!(f, "{}", msg::ERR_BOOT_PATH_FIND)
+            }
+
+            // Editor
+
+            // Screen too small
+            Self::EditorScreen => {
+                write!(f, "{}", msg::ERR_EDITOR_SCREEN)
+            }
+
+            // File
+
+            // Failed to open file
+            Self::FileOpen(path, status) => {
+                write!(f, "{}: \"{path}\" ({status:?})", msg::ERR_FILE_OPEN)
+            }
+
+            // Failed to read file
+            Self::FileRead(path, status) => {
+                write!(f, "{}: \"{path}\" ({status:?})", msg::ERR_FILE_READ)
+            }
+
+            // File not in a supported text encoding
+            Self::FileText(path) => {
+                write!(f, "{}: \"{path}\"", msg::ERR_FILE_TEXT)
+            }
+
+            // Failed to write file
+            Self::FileWrite(path, status) => {
+                write!(f, "{}: \"{path}\" ({status:?})", msg::ERR_FILE_WRITE)
+            }
+
             // Input
 
             // Parse error (input)
@@ -165,6 +431,44 @@ impl Display for AppError {
                 write!(f, "{}: {string}", msg::ERR_INPUT)
             }
 
+            // HII configuration string malformed
+            Self::InputConfig(string) => {
+                write!(f, "{} \"{string}\"", msg::ERR_INPUT_CONFIG)
+            }
+
+            // No HII configuration strings
+            Self::InputConfigNone => {
+                write!(f, "{}", msg::ERR_INPUT_CONFIG_NONE)
+            }
+
+            // Condition malformed, from where it went wrong
+            Self::InputCond(string) => {
+                write!(f, "{}: \"{string}\"", msg::ERR_INPUT_COND)
+            }
+
+            // Condition on an unknown property
+            Self::InputCondKey(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_INPUT_COND_KEY[0], msg::ERR_INPUT_COND_KEY[1])
+            }
+
+            // Condition orders text
+            Self::InputCondText(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_INPUT_COND_TEXT[0], msg::ERR_INPUT_COND_TEXT[1])
+            }
+
+            // Condition version malformed
+            Self::InputCondVersion(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_INPUT_COND_VERSION[0], msg::ERR_INPUT_COND_VERSION[1])
+            }
+
+            // Constant malformed
+            Self::InputConst(string) => {
+                write!(f, "{}: \"{string}\"", msg::ERR_INPUT_CONST)
+            }
+
             // Input definition malformed
             Self::InputDef(string) => {
                 write!(f, "{} \"{string}\"", msg::ERR_INPUT_DEF)
@@ -176,11 +480,80 @@ impl Display for AppError {
                     msg::ERR_INPUT_DEF_SET[0], msg::ERR_INPUT_DEF_SET[1])
             }
 
+            // Names of the values of a definition malformed
+            Self::InputDefValues(string) => {
+                write!(f, "{}: \"{string}\"", msg::ERR_INPUT_DEF_VALUES)
+            }
+
+            // Malformed command (interactive mode)
+            Self::InputCommand(string) => {
+                write!(f, "{}: \"{string}\"", msg::ERR_INPUT_COMMAND)
+            }
+
+            // Else without an if
+            Self::InputElse => {
+                write!(f, "{}", msg::ERR_INPUT_ELSE)
+            }
+
+            // Endif without an if
+            Self::InputEndif => {
+                write!(f, "{}", msg::ERR_INPUT_ENDIF)
+            }
+
+            // If without an endif
+            Self::InputIf => {
+                write!(f, "{}", msg::ERR_INPUT_IF)
+            }
+
+            // Not an operation in a block checking the variables
+            Self::InputIfEntry => {
+                write!(f, "{}", msg::ERR_INPUT_IF_ENTRY)
+            }
+
+            // Include directive without a path
+            Self::InputInclude(string) => {
+                write!(f, "{}: \"{string}\"", msg::ERR_INPUT_INCLUDE)
+            }
+
+            // File includes itself, directly or not
+            Self::InputIncludeCycle(string) => {
+                write!(f, "{} \"{string}\" {}",
+                    msg::ERR_INPUT_INCLUDE_CYCLE[0], msg::ERR_INPUT_INCLUDE_CYCLE[1])
+            }
+
+            // Files included too deep
+            Self::InputIncludeDepth => {
+                write!(f, "{} ({})", msg::ERR_INPUT_INCLUDE_DEPTH, config::INPUT_INCLUDE_DEPTH_MAX)
+            }
+
+            // Error at a given location, followed by the line
+            // and a marker under the column, the whitespace
+            // before it kept as is, so that the tabs line up
+            Self::InputLine(location, e) => {
+                let marker = location.text.chars().take(location.column - 1)
+                    .map(|c| if c == config::CHAR_BLANK_TAB { c } else { config::CHAR_BLANK_SPACE })
+                    .collect::<String>();
+                write!(f, "{}:{}:{}: {e}\n{}{}\n{}{marker}{}", location.source,
+                    location.line, location.column, config::INPUT_ERROR_INDENT, location.text,
+                    config::INPUT_ERROR_INDENT, config::INPUT_ERROR_MARKER)
+            }
+
+            // Errors throughout the input, one after another
+            Self::InputMany(errors) => {
+                write!(f, "{} {}", errors.len(), msg::ERR_INPUT_MANY)?;
+                errors.iter().try_for_each(|e| write!(f, "\n{e}"))
+            }
+
             // No input or command-line arguments
             Self::InputNone => {
                 write!(f, "{}", msg::ERR_INPUT_NONE)
             }
 
+            // Error in a part of the input, the offset only used for the location
+            Self::InputOffset(_, e) => {
+                write!(f, "{e}")
+            }
+
             // Input option unrecognized
             Self::InputOpt(string) => {
                 write!(f, "{} \"{string}\"", msg::ERR_INPUT_OPT)
@@ -196,8 +569,84 @@ impl Display for AppError {
                 write!(f, "{} \"{string}\"", msg::ERR_INPUT_REF_NONE)
             }
 
+            // Element or field selected by a reference not in the definition
+            Self::InputRefPath(string) => {
+                write!(f, "{}: \"{string}\"", msg::ERR_INPUT_REF_PATH)
+            }
+
+            // Requirement not met, followed by the system information
+            Self::InputRequire(string, system) => {
+                write!(f, "{} \"{string}\" {} {system}",
+                    msg::ERR_INPUT_REQUIRE[0], msg::ERR_INPUT_REQUIRE[1])
+            }
+
+            // Requirement checking the variables
+            Self::InputRequireValue => {
+                write!(f, "{}", msg::ERR_INPUT_REQUIRE_VALUE)
+            }
+
+            // Structure malformed
+            Self::InputStruct(string) => {
+                write!(f, "{}: \"{string}\"", msg::ERR_INPUT_STRUCT)
+            }
+
+            // Structure not declared
+            Self::InputStructNone(string) => {
+                write!(f, "{} \"{string}\"", msg::ERR_INPUT_STRUCT_NONE)
+            }
+
             // UEFI
 
+            // Failed to export HII configuration
+            Self::UefiHiiExport(status) => {
+                write!(f, "{} ({status:?})", msg::ERR_UEFI_HII_EXPORT)
+            }
+
+            // Failed to export HII packages
+            Self::UefiHiiDbExport(status) => {
+                write!(f, "{} ({status:?})", msg::ERR_UEFI_HII_DB_EXPORT)
+            }
+
+            // Failed to locate HII database protocol
+            Self::UefiHiiDbFind => {
+                write!(f, "{}", msg::ERR_UEFI_HII_DB_FIND)
+            }
+
+            // Failed to initialize HII database protocol
+            Self::UefiHiiDbOpen => {
+                write!(f, "{}", msg::ERR_UEFI_HII_DB_OPEN)
+            }
+
+            // Failed to extract HII configuration
+            Self::UefiHiiExtract(name, status) => {
+                write!(f, "{}: \"{name}\" ({status:?})", msg::ERR_UEFI_HII_EXTRACT)
+            }
+
+            // Failed to locate HII configuration routing protocol
+            Self::UefiHiiFind => {
+                write!(f, "{}", msg::ERR_UEFI_HII_FIND)
+            }
+
+            // Variable not exposed through HII
+            Self::UefiHiiNone(name) => {
+                write!(f, "{}: \"{name}\"", msg::ERR_UEFI_HII_NONE)
+            }
+
+            // Failed to initialize HII configuration routing protocol
+            Self::UefiHiiOpen => {
+                write!(f, "{}", msg::ERR_UEFI_HII_OPEN)
+            }
+
+            // Failed to route HII configuration
+            Self::UefiHiiRoute(name, status) => {
+                write!(f, "{}: \"{name}\" ({status:?})", msg::ERR_UEFI_HII_ROUTE)
+            }
+
+            // Value missing from HII configuration
+            Self::UefiHiiValue(name) => {
+                write!(f, "{}: \"{name}\"", msg::ERR_UEFI_HII_VALUE)
+            }
+
             // Failed to initialize loaded-image protocol
             Self::UefiLoad => {
                 write!(f, "{}", msg::ERR_UEFI_LOAD)
@@ -228,11 +677,26 @@ impl Display for AppError {
                 write!(f, "{}", msg::ERR_UEFI_PATH_OPEN)
             }
 
+            // Rebooting into firmware setup not supported
+            Self::UefiRestartSetup => {
+                write!(f, "{}", msg::ERR_UEFI_RESTART_SETUP)
+            }
+
+            // Variable only accepts authenticated writes
+            Self::UefiVarAuth(name) => {
+                write!(f, "{}: \"{name}\"", msg::ERR_UEFI_VAR_AUTH)
+            }
+
             // Failed to convert variable name
             Self::UefiVarConv(details) => {
                 write!(f, "{}: {details:?}", msg::ERR_UEFI_VAR_CONV)
             }
 
+            // Failed to delete variable
+            Self::UefiVarDelete(name, status) => {
+                write!(f, "{}: \"{name}\" ({status:?})", msg::ERR_UEFI_VAR_DELETE)
+            }
+
             // Failed to get variable
             Self::UefiVarGet(name, status) => {
                 write!(f, "{}: \"{name}\" ({status:?})", msg::ERR_UEFI_VAR_GET)
@@ -258,6 +722,21 @@ impl Display for AppError {
                 write!(f, "{}: \"{name}\" ({status:?})", msg::ERR_UEFI_VAR_SET)
             }
 
+            // Failed to write authenticated update,
+            // explaining the likely cause where known
+            Self::UefiVarSetAuth(name, status) => {
+                write!(f, "{}: \"{name}\" ({status:?})", msg::ERR_UEFI_VAR_SET_AUTH)?;
+                if *status == Status::SECURITY_VIOLATION {
+                    write!(f, ": {}", msg::ERR_UEFI_VAR_SET_AUTH_SECURITY)
+                } else if *status == Status::INVALID_PARAMETER {
+                    write!(f, ": {}", msg::ERR_UEFI_VAR_SET_AUTH_PARAM)
+                } else if *status == Status::WRITE_PROTECTED {
+                    write!(f, ": {}", msg::ERR_UEFI_VAR_SET_AUTH_PROTECTED)
+                } else {
+                    Ok(())
+                }
+            }
+
             // Size too small given offset and length
             Self::UefiVarSize((offset, length), size) => {
                 write!(f, "{} {size:#06x} {} {offset:#06x} {} {length}",
@@ -286,3 +765,45 @@ impl From<FromSliceWithNulError> for AppError {
     }
 
 }
+
+// Implementation of the conversion from the errors in parsing
+// the parts of the input shared with the host, one to one
+impl From<ScriptError> for AppError {
+
+    fn from(value: ScriptError) -> Self {
+
+        // Map each error to the one of the same name
+        match value {
+            ScriptError::ArgConst(string) => Self::ArgConst(string),
+            ScriptError::ArgExpr(string) => Self::ArgExpr(string),
+            ScriptError::ArgExprRange(string) => Self::ArgExprRange(string),
+            ScriptError::ArgMore(string) => Self::ArgMore(string),
+            ScriptError::ArgNumDec(string) => Self::ArgNumDec(string),
+            ScriptError::ArgNumHex(string) => Self::ArgNumHex(string),
+            ScriptError::ArgNumHexPrefix(string) => Self::ArgNumHexPrefix(string),
+            ScriptError::ArgPosBktL => Self::ArgPosBktL,
+            ScriptError::ArgPosBktR => Self::ArgPosBktR,
+            ScriptError::ArgSize(size) => Self::ArgSize(size),
+            ScriptError::ArgSizeLimit(string) => Self::ArgSizeLimit(string),
+            ScriptError::ArgSizeMismatch(value, size) => Self::ArgSizeMismatch(value, size),
+            ScriptError::InputCond(string) => Self::InputCond(string),
+            ScriptError::InputCondKey(string) => Self::InputCondKey(string),
+            ScriptError::InputCondText(string) => Self::InputCondText(string),
+            ScriptError::InputCondVersion(string) => Self::InputCondVersion(string),
+            ScriptError::InputConst(string) => Self::InputConst(string),
+            ScriptError::InputDef(string) => Self::InputDef(string),
+            ScriptError::InputDefValues(string) => Self::InputDefValues(string),
+            ScriptError::InputElse => Self::InputElse,
+            ScriptError::InputEndif => Self::InputEndif,
+            ScriptError::InputOffset(offset, e) => Self::InputOffset(offset, Box::new((*e).into())),
+            ScriptError::InputOpt(string) => Self::InputOpt(string),
+            ScriptError::InputRef(string) => Self::InputRef(string),
+            ScriptError::InputRequire(condition, system) => Self::InputRequire(condition, system),
+            ScriptError::InputRequireValue => Self::InputRequireValue,
+            ScriptError::InputStruct(string) => Self::InputStruct(string),
+            ScriptError::InputStructNone(string) => Self::InputStructNone(string),
+        }
+
+    }
+
+}
@@ -7,25 +7,33 @@
 // Performs UEFI operations such as querying and setting UEFI variables
 
 // Declare fully-qualified symbols to be used in the local scope
-use alloc::{borrow::ToOwned, string::ToString, vec, vec::Vec};
-use core::ptr::null_mut;
+use alloc::{borrow::ToOwned, format, string::{String, ToString}, vec, vec::Vec};
+use core::{ptr::null_mut, slice};
 use uefi::{Char16, CStr16, CString16, Identify, Status,
+    data_types::{EqStrUntilNul, chars::NUL_16},
     proto::{ // Protocols
-        console::text::{Input, Key},
-        device_path::text::{AllowShortcuts, DevicePathToText, DisplayOnly},
-        loaded_image::LoadedImage},
+        console::text::{Input, Key, ScanCode},
+        device_path::{DevicePath, text::{
+            AllowShortcuts, DevicePathFromText, DevicePathToText, DisplayOnly}},
+        loaded_image::LoadedImage,
+        media::file::{File, FileAttribute, FileInfo, FileMode}},
     table::{ // Tables
         Boot, SystemTable,
-        boot::{BootServices, SearchType},
-        runtime::{ResetType, VariableKey}}};
-use uefi_services::println;
+        boot::{BootServices, ScopedProtocol, SearchType}, cfg,
+        runtime::{ResetType, VariableAttributes, VariableKey, VariableVendor}}};
+use uefi_services::{print, println};
 
 // Symbols from other modules
+use crate::boot::{self, LoadOptionId, LoadOptionType};
 use crate::config;
 use crate::config::locale as msg;
-use crate::data::{UefiValue, UefiVariable};
+use crate::data::{RestartType, UefiValue, UefiVariable};
 use crate::error::AppError;
-use crate::string::CStr16Ext;
+use crate::guid::format_guid;
+use crate::hii::{self, ConfigBlock, ConfigHeader, ConfigString};
+use crate::protocol::{HiiConfigRouting, HiiDatabase, Shell};
+use crate::smbios::{self, EntryPoint, SmbiosInfo};
+use crate::string::{CStr16Ext, char16_vec_to_cstring16};
 
 // Public System Functions
 
@@ -77,17 +85,6 @@ pub fn get_image_path(system_table: &SystemTable<Boot>) -> Result<CString16, App
         .open_protocol_exclusive::<LoadedImage>(boot_services.image_handle())
         .map_err(|_| AppError::UefiLoad)?;
 
-    // Obtain a device-path-to-text protocol handle
-    let device_path_to_text_handle = *boot_services
-        .locate_handle_buffer(SearchType::ByProtocol(&DevicePathToText::GUID))
-        .map_err(|_| AppError::UefiPathFind)?
-        .first().unwrap();
-
-    // Open the device-path-to-text protocol
-    let device_path_to_text = boot_services
-        .open_protocol_exclusive::<DevicePathToText>(device_path_to_text_handle)
-        .map_err(|_| AppError::UefiPathOpen)?;
-
     // Retrieve the image device path, error out if empty
     let image_device_path = match loaded_image.file_path() {
         Some(image_device_path) => image_device_path,
@@ -95,14 +92,7 @@ pub fn get_image_path(system_table: &SystemTable<Boot>) -> Result<CString16, App
     };
 
     // Convert the image device path to text
-    let image_device_path_text =
-        device_path_to_text.convert_device_path_to_text(
-            boot_services, image_device_path,
-            DisplayOnly(true), AllowShortcuts(false))
-        .map_err(|_| AppError::UefiPathConv)?;
-
-    // Return the converted text as a string
-    Ok(CString16::from(&*image_device_path_text))
+    convert_device_path_to_text(boot_services, image_device_path)
 
 }
 
@@ -135,12 +125,234 @@ pub fn load_options(system_table: &SystemTable<Boot>) -> Result<Vec<CString16>,
 
     // All option arguments must begin with a CHAR_ARG_OPT, i.e. minus (-) sign
     // All operation arguments must contain a CHAR_ARG_POS, i.e. colon (:) sign
+    // All parameter overrides must contain a CHAR_ARG_ASS, i.e. equals (=) sign
+    // The only exception is the file path following the input or output file option
     // Non-conforming arguments will be silently discarded
 
     // Return the options, split into a CString16 vector
-    Ok(options.split(config::CHAR_ARG_SEP).into_iter().filter(
-        |s| s.has_first(config::CHAR_ARG_OPT)
-        || s.has(config::CHAR_ARG_POS)).collect())
+    let mut path = false;
+    Ok(options.split(config::CHAR_ARG_SEP).into_iter().filter(|s| {
+        let keep = path || s.has_first(config::CHAR_ARG_OPT) || s.has(config::CHAR_ARG_POS)
+            || s.has(config::CHAR_ARG_ASS);
+        path = !path && [config::OPT_ARG_INPUT, config::OPT_ARG_INPUT_LONG,
+            config::OPT_ARG_OUTPUT, config::OPT_ARG_OUTPUT_LONG]
+            .iter().any(|option| s.eq_str_until_nul(option));
+        keep
+    }).collect())
+
+}
+
+// Retrieves the board identification from the SMBIOS tables, located
+// through the system configuration table, preferring the 64-bit entry
+// point (SMBIOS 3.x), returns nothing if there are no tables,
+// or they do not identify the board at all
+pub fn get_smbios_info(system_table: &SystemTable<Boot>) -> Option<SmbiosInfo> {
+
+    // Try each entry point in turn, as either may be missing or malformed
+    [cfg::SMBIOS3_GUID, cfg::SMBIOS_GUID].iter().find_map(|guid| {
+
+        // Find the entry point in the configuration table
+        let address = system_table.config_table().iter()
+            .find(|entry| entry.guid == *guid)?.address.cast::<u8>();
+
+        // Determine its length from the first bytes, then parse it whole
+        let header = unsafe { slice::from_raw_parts(address, smbios::ENTRY_HEADER_SIZE) };
+        let entry = EntryPoint::parse(unsafe {
+            slice::from_raw_parts(address, EntryPoint::length(header)?) })?;
+
+        // Parse the structure table it points to, identity-mapped as all memory
+        let table = unsafe { slice::from_raw_parts(
+            usize::try_from(entry.address).ok()? as *const u8, entry.length) };
+        Some(SmbiosInfo::parse(table)).filter(|info| !info.is_empty())
+
+    })
+
+}
+
+// Reads a file from the volume the application was loaded from,
+// with the path given from the root of that volume
+pub fn read_file(system_table: &SystemTable<Boot>, path: &CStr16) -> Result<Vec<u8>, AppError> {
+
+    // Store a reference to UEFI Boot Services
+    let boot_services = system_table.boot_services();
+
+    // Open the file system, and the file within it
+    let error = |status| AppError::FileOpen(path.to_string(), status);
+    let mut file_system = boot_services.get_image_file_system(boot_services.image_handle())
+        .map_err(|e| error(e.status()))?;
+    let mut file = file_system.open_volume()
+        .and_then(|mut root| root.open(path, FileMode::Read, FileAttribute::empty()))
+        .map_err(|e| error(e.status()))?
+        .into_regular_file()
+        .ok_or_else(|| error(Status::INVALID_PARAMETER))?;
+
+    // Retrieve the size of the file
+    let size = file.get_boxed_info::<FileInfo>()
+        .map_err(|e| AppError::FileRead(path.to_string(), e.status()))?
+        .file_size() as usize;
+
+    // Read the file into a buffer of that size
+    let mut buffer = vec![0; size];
+    let read = file.read(&mut buffer)
+        .map_err(|e| AppError::FileRead(path.to_string(), e.status()))?;
+    buffer.truncate(read);
+
+    // Return the contents
+    Ok(buffer)
+
+}
+
+// Reads a script file, or another file given by the user, such as
+// a signed update, with the path relative to the current directory
+// if run from the shell, or otherwise from the root of the volume
+// the application was loaded from, such as when run as a boot option
+pub fn read_script_file(system_table: &SystemTable<Boot>, path: &CStr16)
+    -> Result<Vec<u8>, AppError> {
+
+    // Store a reference to UEFI Boot Services
+    let boot_services = system_table.boot_services();
+
+    // Look for the shell, and fall back to the volume if absent
+    let Ok(shell) = boot_services.get_handle_for_protocol::<Shell>()
+        .and_then(|handle| boot_services.open_protocol_exclusive::<Shell>(handle)) else {
+        return read_file(system_table, path);
+    };
+
+    // Let the shell resolve the path and read the file
+    shell.read_file_by_name(path).map_err(|(status, opened)| if opened {
+        AppError::FileRead(path.to_string(), status)
+    } else {
+        AppError::FileOpen(path.to_string(), status)
+    })
+
+}
+
+// Writes a file, replacing it if it exists, with the path relative to
+// the current directory if run from the shell, or otherwise from the
+// root of the volume the application was loaded from
+pub fn write_file(system_table: &SystemTable<Boot>, path: &CStr16, bytes: &[u8])
+    -> Result<(), AppError> {
+
+    // Store a reference to UEFI Boot Services
+    let boot_services = system_table.boot_services();
+
+    // Set up the error handling
+    let error_open = |status| AppError::FileOpen(path.to_string(), status);
+    let error_write = |status| AppError::FileWrite(path.to_string(), status);
+
+    // Let the shell resolve the path and write the file, if present
+    if let Ok(shell) = boot_services.get_handle_for_protocol::<Shell>()
+        .and_then(|handle| boot_services.open_protocol_exclusive::<Shell>(handle)) {
+        return shell.write_file_by_name(path, bytes).map_err(|(status, opened)|
+            if opened { error_write(status) } else { error_open(status) });
+    }
+
+    // Otherwise, open the file system
+    let mut root = boot_services.get_image_file_system(boot_services.image_handle())
+        .and_then(|mut file_system| file_system.open_volume())
+        .map_err(|e| error_open(e.status()))?;
+
+    // Remove the existing file, if any, which would otherwise
+    // keep any of its previous contents past the new ones
+    if let Ok(file) = root.open(path, FileMode::ReadWrite, FileAttribute::empty()) {
+        file.delete().map_err(|e| error_write(e.status()))?;
+    }
+
+    // Create the file, and write the contents
+    let mut file = root.open(path, FileMode::CreateReadWrite, FileAttribute::empty())
+        .map_err(|e| error_open(e.status()))?
+        .into_regular_file()
+        .ok_or_else(|| error_open(Status::INVALID_PARAMETER))?;
+    file.write(bytes)
+        .map_err(|e| error_write(e.status()))?;
+    file.flush()
+        .map_err(|e| error_write(e.status()))
+
+}
+
+// Waits for a key to be pressed, and returns it
+pub fn read_key(system_table: &mut SystemTable<Boot>) -> Key {
+
+    loop {
+
+        // Return the key if there is one,
+        // otherwise check again after a while
+        match system_table.stdin().read_key().expect(msg::ERR_INPUT_READ) {
+            Some(key) => return key,
+            None => system_table.boot_services().stall(config::KEY_POLL_INTERVAL),
+        }
+
+    }
+
+}
+
+// Reads a line typed at the keyboard, showing it as it is typed,
+// with the previous lines from the history recalled by Up and Down
+pub fn read_line(system_table: &mut SystemTable<Boot>, history: &[CString16]) -> CString16 {
+
+    // Replaces the line shown with another one
+    let replace = |line: &mut Vec<Char16>, other: &[Char16]| {
+        for _ in 0 .. line.len() {
+            print!("{0} {0}", config::CHAR_CTL_BS);
+        }
+        *line = other.to_vec();
+        print!("{}", line.iter().map(|&c| char::from(c)).collect::<String>());
+    };
+
+    // Set up the line, and the position in the history,
+    // which is past its end for the line being typed
+    let mut line = Vec::new();
+    let mut index = history.len();
+
+    // Keep reading until Enter
+    loop {
+        match read_key(system_table) {
+
+            // Enter ends the line
+            Key::Printable(c) if c == Char16::try_from(config::CHAR_CTL_CR).unwrap()
+                || c == Char16::try_from(config::CHAR_CTL_LF).unwrap() => {
+                println!();
+                break;
+            }
+
+            // Backspace removes the last character, if any
+            Key::Printable(c) if c == Char16::try_from(config::CHAR_CTL_BS).unwrap()
+                && !line.is_empty() => {
+                line.pop();
+                print!("{0} {0}", config::CHAR_CTL_BS);
+            }
+
+            // Other characters are added, except control characters
+            Key::Printable(c) if !char::from(c).is_control() => {
+                line.push(c);
+                print!("{}", char::from(c));
+            }
+
+            // Recall the previous line in the history
+            Key::Special(ScanCode::UP) if index > 0 => {
+                index -= 1;
+                replace(&mut line, history[index].as_slice());
+            }
+
+            // Recall the next line, or return to an empty one
+            Key::Special(ScanCode::DOWN) if index < history.len() => {
+                index += 1;
+                replace(&mut line, history.get(index).map_or(&[], |s| s.as_slice()));
+            }
+
+            // Clear the line
+            Key::Special(ScanCode::ESCAPE) => replace(&mut line, &[]),
+
+            // Ignore anything else
+            _ => {}
+
+        }
+
+    }
+
+    // Return the line, terminated
+    line.push(NUL_16);
+    char16_vec_to_cstring16(line)
 
 }
 
@@ -182,19 +394,364 @@ pub fn read_stream(input: &mut Input) -> CString16 {
 
 }
 
-// Restarts the system
-pub fn restart_system(system_table: &SystemTable<Boot>) -> () {
+// Restarts the system in a given way, returns the error status if failed
+// Note: to reboot into firmware setup, the OS indications are set first,
+// which is skipped when simulating, but the restart still takes place
+pub fn restart_system(system_table: &SystemTable<Boot>,
+    restart_type: RestartType, simulate: bool) -> Result<(), AppError> {
+
+    // Determine the reset type
+    let reset_type = match restart_type {
+        RestartType::Cold => ResetType::COLD,
+        RestartType::Platform => ResetType::PLATFORM_SPECIFIC,
+        RestartType::Shutdown => ResetType::SHUTDOWN,
+        RestartType::Warm => ResetType::WARM,
+
+        // Ask the firmware to stop in setup on the next boot
+        RestartType::Setup => {
+
+            // Make sure the firmware supports it
+            let supported = get_global_variable(system_table, config::VAR_OS_INDICATIONS_SUPPORTED)?
+                .map_or(0, |bytes| read_u64(&bytes));
+            if supported & config::OS_INDICATIONS_BOOT_TO_FW_UI == 0 {
+                return Err(AppError::UefiRestartSetup);
+            }
+
+            // Add the indication to those already set, if any
+            let indications = get_global_variable(system_table, config::VAR_OS_INDICATIONS)?
+                .map_or(0, |bytes| read_u64(&bytes));
+            set_global_variable(system_table, config::VAR_OS_INDICATIONS,
+                &(indications | config::OS_INDICATIONS_BOOT_TO_FW_UI).to_le_bytes(), simulate)?;
+
+            // Any reset type will do, but cold is the most common
+            ResetType::COLD
+
+        }
+
+    };
+
+    // Reset, never returns
+    system_table.runtime_services()
+        .reset(reset_type, Status::SUCCESS, None)
+
+}
+
+// Public Boot Functions
+
+// Converts a device path, such as the one in a load option, to text
+pub fn device_path_to_text(system_table: &SystemTable<Boot>, bytes: &[u8])
+    -> Result<CString16, AppError> {
+
+    // Make sure the device path is well-formed, since
+    // the protocol would read past its end otherwise
+    if boot::device_path_length(bytes).is_none() {
+        return Err(AppError::UefiPathConv);
+    }
+
+    // Convert the device path to text
+    convert_device_path_to_text(system_table.boot_services(),
+        unsafe { DevicePath::from_ffi_ptr(bytes.as_ptr().cast()) })
+
+}
+
+// Deletes a UEFI global variable (EFI_GLOBAL_VARIABLE vendor GUID),
+// unless simulating, returns the error status
+pub fn delete_global_variable(system_table: &SystemTable<Boot>,
+    var_name: &str, simulate: bool) -> Result<(), AppError> {
+
+    // Do nothing if simulating
+    if simulate {
+        return Ok(());
+    }
+
+    // Attempt to delete the variable, handling a possible error
+    let var_name = CString16::try_from(var_name).unwrap();
+    system_table.runtime_services()
+        .delete_variable(&var_name, &VariableVendor::GLOBAL_VARIABLE)
+        .map_err(|e| AppError::UefiVarDelete(var_name.to_string(), e.status()))
+
+}
+
+// Retrieves a UEFI global variable (EFI_GLOBAL_VARIABLE vendor GUID),
+// returns its contents, nothing if absent, or the error status
+pub fn get_global_variable(system_table: &SystemTable<Boot>,
+    var_name: &str) -> Result<Option<Vec<u8>>, AppError> {
+
+    // Store a reference to UEFI Runtime Services
+    let runtime_services = system_table.runtime_services();
+
+    // Variable names used here are all plain ASCII
+    let var_name = CString16::try_from(var_name).unwrap();
+
+    // Retrieve the size of the variable, which also tells if it exists
+    let size = match runtime_services
+        .get_variable_size(&var_name, &VariableVendor::GLOBAL_VARIABLE) {
+        Ok(size) => size,
+        Err(e) if e.status() == Status::NOT_FOUND => return Ok(None),
+        Err(e) => return Err(AppError::UefiVarSizeGet(var_name.to_string(), e.status())),
+    };
+
+    // Allocate a buffer the size of the variable
+    let mut buffer = vec![0; size];
+
+    // Retrieve the variable into the buffer using UEFI Runtime Services
+    runtime_services.get_variable(&var_name, &VariableVendor::GLOBAL_VARIABLE, &mut buffer)
+        .map_err(|e| AppError::UefiVarGet(var_name.to_string(), e.status()))?;
+
+    // Return the contents
+    Ok(Some(buffer))
+
+}
+
+// Lists the load options of all types (Boot####, Driver####,
+// SysPrep####) present, sorted by type and then by number
+pub fn get_load_options(system_table: &SystemTable<Boot>)
+    -> Result<Vec<LoadOptionId>, AppError> {
+
+    // Retrieve variable name list from UEFI Runtime Services
+    let keys = system_table.runtime_services().variable_keys()
+        .map_err(|e| AppError::UefiVarList(e.status()))?;
+
+    // Only keep the global variables named like load options
+    let mut ids = keys.into_iter()
+        .filter(|k| k.vendor.0 == VariableVendor::GLOBAL_VARIABLE.0)
+        .filter_map(|k| LoadOptionId::from_variable_name(&k.name().ok()?.to_string()))
+        .collect::<Vec<_>>();
+
+    // Sort by type first, then by number
+    ids.sort_by_key(|id| (LoadOptionType::ALL.iter()
+        .position(|&kind| kind == id.kind), id.number));
+
+    // Return the list
+    Ok(ids)
+
+}
+
+// Retrieves the contents of a Secure Boot signature database (PK, KEK,
+// db, dbx, dbt, MokList), located through the variable enumeration,
+// returns nothing if absent
+pub fn get_signature_database(system_table: &SystemTable<Boot>,
+    var_name: &str) -> Result<Option<Vec<u8>>, AppError> {
+
+    // Determine the vendor, since the names are common words
+    // and the variables could otherwise be mistaken for namesakes
+    let vendor = get_signature_database_vendor(var_name);
+
+    // Find the variable with the matching vendor among the namesakes,
+    // the position on the list being the identifier to retrieve it by
+    let var_name = CString16::try_from(var_name).unwrap();
+    match get_variable_keys(system_table, &var_name)?
+        .iter().position(|k| k.vendor.0 == vendor.0) {
+        Some(id) => Ok(Some(get_variable(system_table, &var_name, Some(id))?.content)),
+        None => Ok(None),
+    }
+
+}
+
+// Writes a signed update to a Secure Boot signature database (PK, KEK,
+// db, dbx, dbt), either replacing or appending to the contents, unless
+// simulating, returns the error status
+pub fn set_signature_database(system_table: &SystemTable<Boot>,
+    var_name: &str, update: &[u8], append: bool, simulate: bool) -> Result<(), AppError> {
+
+    // Do nothing if simulating
+    if simulate {
+        return Ok(());
+    }
+
+    // The databases are all non-volatile, accessible both at boot time
+    // and at runtime, and only accept time-based authenticated writes
+    let mut attributes = VariableAttributes::NON_VOLATILE
+        | VariableAttributes::BOOTSERVICE_ACCESS
+        | VariableAttributes::RUNTIME_ACCESS
+        | VariableAttributes::TIME_BASED_AUTHENTICATED_WRITE_ACCESS;
+    if append {
+        attributes |= VariableAttributes::APPEND_WRITE;
+    }
+
+    // Attempt to set the variable, the firmware verifying the signature,
+    // and handling a possible error
+    let vendor = get_signature_database_vendor(var_name);
+    let var_name = CString16::try_from(var_name).unwrap();
+    system_table.runtime_services()
+        .set_variable(&var_name, &vendor, attributes, update)
+        .map_err(|e| AppError::UefiVarSetAuth(var_name.to_string(), e.status()))
+
+}
+
+// Sets a UEFI global variable (EFI_GLOBAL_VARIABLE vendor GUID),
+// unless simulating, returns the error status
+pub fn set_global_variable(system_table: &SystemTable<Boot>,
+    var_name: &str, content: &[u8], simulate: bool) -> Result<(), AppError> {
+
+    // Do nothing if simulating
+    if simulate {
+        return Ok(());
+    }
+
+    // Load options and their order are all non-volatile,
+    // and accessible both at boot time and at runtime
+    let attributes = VariableAttributes::NON_VOLATILE
+        | VariableAttributes::BOOTSERVICE_ACCESS
+        | VariableAttributes::RUNTIME_ACCESS;
+
+    // Attempt to set the variable, handling a possible error
+    let var_name = CString16::try_from(var_name).unwrap();
     system_table.runtime_services()
-        .reset(ResetType::WARM, Status::SUCCESS, None)
+        .set_variable(&var_name, &VariableVendor::GLOBAL_VARIABLE, attributes, content)
+        .map_err(|e| AppError::UefiVarSet(var_name.to_string(), e.status()))
+
+}
+
+// Converts text to a device path, such as for a new load option
+pub fn text_to_device_path(system_table: &SystemTable<Boot>, text: &CStr16)
+    -> Result<Vec<u8>, AppError> {
+
+    // Store a reference to UEFI Boot Services
+    let boot_services = system_table.boot_services();
+
+    // Obtain a device-path-from-text protocol handle
+    let device_path_from_text_handle = boot_services
+        .get_handle_for_protocol::<DevicePathFromText>()
+        .map_err(|_| AppError::BootPathFind)?;
+
+    // Open the device-path-from-text protocol
+    let device_path_from_text = boot_services
+        .open_protocol_exclusive::<DevicePathFromText>(device_path_from_text_handle)
+        .map_err(|_| AppError::BootPathFind)?;
+
+    // Convert the text to a device path
+    let device_path = device_path_from_text.convert_text_to_device_path(text)
+        .map_err(|_| AppError::BootPath(text.to_string()))?;
+    let device_path = device_path.as_ffi_ptr().cast::<u8>();
+
+    // Walk the nodes to determine the length, as the
+    // protocol returns a pointer to the pool memory only
+    let mut length = 0;
+    loop {
+
+        // Each node begins with a type, a subtype and its length
+        let header = unsafe { slice::from_raw_parts(
+            device_path.add(length), boot::DEVICE_PATH_HEADER_SIZE) };
+        let node_length = u16::from_le_bytes([header[2], header[3]]) as usize;
+        length += node_length;
+
+        // Stop at the end node, or on a malformed one
+        if (header[0], header[1]) == (boot::DEVICE_PATH_END_TYPE, boot::DEVICE_PATH_END_ENTIRE)
+            || node_length < boot::DEVICE_PATH_HEADER_SIZE {
+            break;
+        }
+
+    }
+
+    // Copy the device path, then release the pool memory
+    let bytes = unsafe { slice::from_raw_parts(device_path, length) }.to_vec();
+    boot_services.free_pool(device_path.cast_mut())
+        .map_err(|_| AppError::BootPath(text.to_string()))?;
+
+    // Make sure the device path is well-formed
+    match boot::device_path_length(&bytes) {
+        Some(_) => Ok(bytes),
+        None => Err(AppError::BootPath(text.to_string())),
+    }
+
 }
 
 // Public Variable Functions
 
-// Queries a UEFI variable at a given offset and size,
-// returns the value and the operation error status
+// Exports all the HII package lists, such as the forms and strings
+// describing the setup questions stored in the UEFI variables
+pub fn export_hii_packages(system_table: &SystemTable<Boot>) -> Result<Vec<u8>, AppError> {
+
+    // Store a reference to UEFI Boot Services
+    let boot_services = system_table.boot_services();
+
+    // Obtain an HII database protocol handle
+    let hii_database_handle = boot_services
+        .get_handle_for_protocol::<HiiDatabase>()
+        .map_err(|_| AppError::UefiHiiDbFind)?;
+
+    // Open the HII database protocol
+    let hii_database = boot_services
+        .open_protocol_exclusive::<HiiDatabase>(hii_database_handle)
+        .map_err(|_| AppError::UefiHiiDbOpen)?;
+
+    // Export the package lists, handling a possible error
+    hii_database.export_package_lists()
+        .map_err(AppError::UefiHiiDbExport)
+
+}
+
+// Identifies a UEFI variable for HII configuration, falling back
+// to an empty device path if not exposed through HII, so that
+// the configuration can still be exchanged with other tools
+pub fn get_config_header_for(system_table: &SystemTable<Boot>,
+    var_name: &CStr16, var_id: Option<usize>) -> Result<ConfigHeader, AppError> {
+
+    // Attempt to retrieve the specified variable
+    let var = get_variable(&system_table, var_name, var_id)?;
+
+    // Store a reference to UEFI Boot Services
+    let boot_services = system_table.boot_services();
+
+    // Look up the variable store among the HII configuration
+    Ok(open_config_routing(boot_services)
+        .and_then(|config_routing| get_config_header(boot_services, &config_routing, &var))
+
+        // Or identify it by the vendor GUID and name only
+        .unwrap_or_else(|_| ConfigHeader {
+            guid: var.vendor.0.to_bytes(),
+            name: var.name.to_u16_slice().to_vec(),
+            path: Vec::new() }))
+
+}
+
+// Retrieves an entire UEFI variable, together with its attributes and vendor
+pub fn get_whole_variable(system_table: &SystemTable<Boot>,
+    var_name: &CStr16, var_id: Option<usize>) -> Result<UefiVariable, AppError> {
+    get_variable(system_table, var_name, var_id)
+}
+
+// Determines the identifier for a UEFI variable of a given name and
+// vendor GUID, in case there are namesakes, for use in operations
+pub fn get_variable_id(system_table: &SystemTable<Boot>,
+    var_name: &CStr16, vendor: &[u8; 16]) -> Result<Option<usize>, AppError> {
+
+    // Retrieve the variables with the matching name
+    let keys = get_variable_keys(system_table, var_name)?;
+
+    // Find the one with the matching vendor
+    let id = keys.iter().position(|k| k.vendor.0.to_bytes() == *vendor)
+        .ok_or_else(|| AppError::UefiVarGetNone(var_name.to_string()))?;
+
+    // Identifier only needed if there is more than one
+    Ok(if keys.len() > 1 { Some(id) } else { None })
+
+}
+
+// Describes the UEFI variables sharing a given name, in the order of
+// their identifiers, with the vendor GUID, attributes and size of each
+pub fn get_namesake_variables(system_table: &SystemTable<Boot>,
+    var_name: &CStr16) -> Result<Vec<String>, AppError> {
+    get_variable_keys(system_table, var_name)?
+        .iter()
+        .map(|key| describe_variable(system_table, key))
+        .collect()
+}
+
+// Checks whether a UEFI variable of a given name, and vendor GUID
+// if given, is there, as asked by a condition in the input
+pub fn has_variable(system_table: &SystemTable<Boot>,
+    var_name: &CStr16, vendor: Option<&[u8; 16]>) -> Result<bool, AppError> {
+    Ok(get_variable_keys(system_table, var_name)?.iter()
+        .any(|k| vendor.is_none_or(|vendor| k.vendor.0.to_bytes() == *vendor)))
+}
+
+// Queries a UEFI variable at a given offset and size, optionally
+// through HII, returns the value and the operation error status
 pub fn get_value(system_table: &SystemTable<Boot>,
     var_name: &CStr16, var_id: Option<usize>,
-    offset: usize, length: usize) -> Result<UefiValue, AppError> {
+    offset: usize, length: usize, hii: bool) -> Result<UefiValue, AppError> {
 
     // Attempt to retrieve the specified variable
     let var = get_variable(&system_table, var_name, var_id)?;
@@ -210,6 +767,12 @@ pub fn get_value(system_table: &SystemTable<Boot>,
 
     }
 
+    // If routing through HII, ask the driver
+    // owning the variable store for the value
+    if hii {
+        return extract_value(system_table.boot_services(), &var, offset, length);
+    }
+
     // Retrieve the given slice of the variable
     let slice = &var.content[offset .. offset + length];
 
@@ -218,12 +781,12 @@ pub fn get_value(system_table: &SystemTable<Boot>,
 
 }
 
-// Modifies a UEFI variable at a given offset and size,
-// returns a flag whether changes were made, and error status
+// Modifies a UEFI variable at a given offset and size, optionally
+// through HII, returns a flag whether changes were made, and error status
 pub fn set_value(system_table: &SystemTable<Boot>,
     var_name: &CStr16, var_id: Option<usize>,
     offset: usize, length: usize, value: &UefiValue,
-    force: bool, simulate: bool) -> Result<bool, AppError> {
+    force: bool, hii: bool, simulate: bool) -> Result<bool, AppError> {
 
     // Attempt to retrieve the specified variable
     let mut var = get_variable(&system_table, var_name, var_id)?;
@@ -238,6 +801,13 @@ pub fn set_value(system_table: &SystemTable<Boot>,
 
     }
 
+    // Authenticated variables can only be written as a whole with a signed
+    // update, so writing the modified contents back would fail anyway
+    // Note: HII routing leaves it up to the driver owning the variable
+    if !hii && var.attributes.contains(VariableAttributes::TIME_BASED_AUTHENTICATED_WRITE_ACCESS) {
+        return Err(AppError::UefiVarAuth(var.name.to_string()));
+    }
+
     // Retrieve the given slice of the variable
     let slice = &mut var.content[offset .. offset + length];
 
@@ -255,8 +825,16 @@ pub fn set_value(system_table: &SystemTable<Boot>,
         // Copy the new value into the slice
         slice.copy_from_slice(&value.0);
 
-        // Unless simulating
-        if !simulate {
+        // If routing through HII, unless simulating
+        if hii && !simulate {
+
+            // Pass the new value to the driver owning
+            // the variable store, so that it can validate
+            // the change and update any dependent settings
+            route_value(system_table.boot_services(), &var, offset, &value.0)?;
+
+        // Otherwise, unless simulating
+        } else if !simulate {
 
             // Attempt to set the variable, handling a possible error
             system_table.runtime_services()
@@ -275,6 +853,135 @@ pub fn set_value(system_table: &SystemTable<Boot>,
 
 // Private Functions
 
+// Reads a little-endian 64-bit value, padding with zeros if shorter
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut value = [0; 8];
+    let length = bytes.len().min(value.len());
+    value[0 .. length].copy_from_slice(&bytes[0 .. length]);
+    u64::from_le_bytes(value)
+}
+
+// Converts a device path to text using the device-path-to-text protocol
+fn convert_device_path_to_text(boot_services: &BootServices,
+    device_path: &DevicePath) -> Result<CString16, AppError> {
+
+    // Obtain a device-path-to-text protocol handle
+    let device_path_to_text_handle = *boot_services
+        .locate_handle_buffer(SearchType::ByProtocol(&DevicePathToText::GUID))
+        .map_err(|_| AppError::UefiPathFind)?
+        .first().unwrap();
+
+    // Open the device-path-to-text protocol
+    let device_path_to_text = boot_services
+        .open_protocol_exclusive::<DevicePathToText>(device_path_to_text_handle)
+        .map_err(|_| AppError::UefiPathOpen)?;
+
+    // Convert the device path to text
+    let device_path_text =
+        device_path_to_text.convert_device_path_to_text(
+            boot_services, device_path,
+            DisplayOnly(true), AllowShortcuts(false))
+        .map_err(|_| AppError::UefiPathConv)?;
+
+    // Return the converted text as a string
+    Ok(CString16::from(&*device_path_text))
+
+}
+
+// Retrieves a value at a given offset and size from the
+// driver owning the variable store, using HII configuration
+fn extract_value(boot_services: &BootServices, var: &UefiVariable,
+    offset: usize, length: usize) -> Result<UefiValue, AppError> {
+
+    // Open the configuration routing protocol
+    let config_routing = open_config_routing(boot_services)?;
+
+    // Identify the variable store
+    let header = get_config_header(boot_services, &config_routing, var)?;
+
+    // Request the block holding the value
+    let request = ConfigString { header, blocks: vec![
+        ConfigBlock { offset, value: None, width: length }] };
+
+    // Retrieve the response, handling a possible error
+    let response = config_routing.extract_config(boot_services,
+        &CString16::try_from(request.to_string().as_str()).unwrap())
+        .map_err(|e| AppError::UefiHiiExtract(var.name.to_string(), e))?;
+
+    // Find a block containing the requested range
+    // Note: the driver is free to return it in any number of blocks,
+    // or to merge it with the adjacent ones, hence the search
+    hii::parse_config_strings(&response.to_string()).iter()
+        .flat_map(|c| c.blocks.iter())
+        .find_map(|b| match &b.value {
+            Some(value) if b.offset <= offset
+                && offset + length <= b.offset + value.len() =>
+                    Some(UefiValue(value[offset - b.offset .. offset - b.offset + length].to_vec())),
+            _ => None })
+        .ok_or_else(|| AppError::UefiHiiValue(var.name.to_string()))
+
+}
+
+// Finds the HII configuration header matching a variable
+// Note: only variables backing setup forms are exposed
+fn get_config_header(boot_services: &BootServices,
+    config_routing: &HiiConfigRouting, var: &UefiVariable)
+    -> Result<ConfigHeader, AppError> {
+
+    // Export the configuration of all the drivers
+    let export = config_routing.export_config(boot_services)
+        .map_err(AppError::UefiHiiExport)?;
+
+    // Variable identification to compare against
+    let guid = var.vendor.0.to_bytes();
+    let name = var.name.to_u16_slice();
+
+    // Find the configuration for the variable store
+    hii::parse_config_strings(&export.to_string()).into_iter()
+        .map(|c| c.header)
+        .find(|h| h.guid == guid && h.name == name)
+        .ok_or_else(|| AppError::UefiHiiNone(var.name.to_string()))
+
+}
+
+// Opens the HII configuration routing protocol
+fn open_config_routing(boot_services: &BootServices)
+    -> Result<ScopedProtocol<'_, HiiConfigRouting>, AppError> {
+
+    // Obtain a configuration routing protocol handle
+    let config_routing_handle = boot_services
+        .get_handle_for_protocol::<HiiConfigRouting>()
+        .map_err(|_| AppError::UefiHiiFind)?;
+
+    // Open the configuration routing protocol
+    boot_services
+        .open_protocol_exclusive::<HiiConfigRouting>(config_routing_handle)
+        .map_err(|_| AppError::UefiHiiOpen)
+
+}
+
+// Passes a value at a given offset to the driver owning
+// the variable store, using HII configuration
+fn route_value(boot_services: &BootServices, var: &UefiVariable,
+    offset: usize, value: &[u8]) -> Result<(), AppError> {
+
+    // Open the configuration routing protocol
+    let config_routing = open_config_routing(boot_services)?;
+
+    // Identify the variable store
+    let header = get_config_header(boot_services, &config_routing, var)?;
+
+    // Build the response with the new value
+    let response = ConfigString { header, blocks: vec![
+        ConfigBlock { offset, value: Some(value.to_vec()), width: value.len() }] };
+
+    // Route the response, handling a possible error
+    config_routing.route_config(
+        &CString16::try_from(response.to_string().as_str()).unwrap())
+        .map_err(|e| AppError::UefiHiiRoute(var.name.to_string(), e))
+
+}
+
 // A wrapper for UEFI Runtime Services'
 // variable retrieval function, used internally
 fn get_variable(system_table: &SystemTable<Boot>,
@@ -285,23 +992,8 @@ fn get_variable(system_table: &SystemTable<Boot>,
     // which are called numerous times within this function
     let runtime_services = system_table.runtime_services();
 
-    // Retrieve variable name list from UEFI Runtime Services
-    let keys = runtime_services.variable_keys()
-        .map_err(|e| AppError::UefiVarList(e.status()))?;
-
-    // Filter the retrieved list for matching variable names
-    let mut keys = keys
-        .into_iter()
-        .filter(|k| {
-            if let Ok(name) = k.name() {
-                name == var_name
-            } else {
-                false
-            }
-        }).collect::<Vec<_>>();
-
-    // Sort the filtered list by vendor
-    keys.sort_by_key(|k| k.vendor.0);
+    // Retrieve the variables with the matching name
+    let keys = get_variable_keys(system_table, var_name)?;
 
     // If no matches were found, report an error
     if keys.is_empty() {
@@ -351,8 +1043,47 @@ fn get_variable(system_table: &SystemTable<Boot>,
 
 }
 
+// Determines the vendor of a Secure Boot signature database by its name
+fn get_signature_database_vendor(var_name: &str) -> VariableVendor {
+    match var_name {
+        config::VAR_SIGLIST_DB | config::VAR_SIGLIST_DBX | config::VAR_SIGLIST_DBT =>
+            VariableVendor::IMAGE_SECURITY_DATABASE,
+        config::VAR_SIGLIST_MOK_LIST => VariableVendor::SHIM_LOCK,
+        _ => VariableVendor::GLOBAL_VARIABLE,
+    }
+}
+
+// Lists the keys of UEFI variables with a given name, sorted by vendor,
+// which is the order that variable identifiers are based on
+fn get_variable_keys(system_table: &SystemTable<Boot>, var_name: &CStr16)
+    -> Result<Vec<VariableKey>, AppError> {
+
+    // Retrieve variable name list from UEFI Runtime Services
+    let keys = system_table.runtime_services().variable_keys()
+        .map_err(|e| AppError::UefiVarList(e.status()))?;
+
+    // Filter the retrieved list for matching variable names
+    let mut keys = keys
+        .into_iter()
+        .filter(|k| {
+            if let Ok(name) = k.name() {
+                name == var_name
+            } else {
+                false
+            }
+        }).collect::<Vec<_>>();
+
+    // Sort the filtered list by vendor
+    keys.sort_by_key(|k| k.vendor.0);
+
+    // Return the list
+    Ok(keys)
+
+}
+
 // Handles the case where UEFI variable cannot be identified by its name,
 // asks the user to reattempt the operation providing a unique identifier
+// Note: the vendor GUIDs are shown as well, to tell the variables apart
 fn get_variable_ambiguous(system_table: &SystemTable<Boot>, keys: Vec<VariableKey>)
     -> Result<(), AppError> {
 
@@ -364,12 +1095,10 @@ fn get_variable_ambiguous(system_table: &SystemTable<Boot>, keys: Vec<VariableKe
         // Retrieve metadata for each variable
         let id = i;
         let name = key.name()?;
-        let size = system_table.runtime_services()
-            .get_variable_size(name, &key.vendor)
-            .map_err(|e| AppError::UefiVarSizeGet(name.to_string(), e.status()))?;
+        let info = describe_variable(system_table, &key)?;
 
         // Output the resulting information
-        println!("{}({:#04x}){}{:#06x}", name, id, msg::ERR_UEFI_VAR_GET_MANY_ITEM, size);
+        println!("{}({:#04x}){}{}", name, id, msg::ERR_UEFI_VAR_GET_MANY_ITEM, info);
 
     }
 
@@ -377,3 +1106,40 @@ fn get_variable_ambiguous(system_table: &SystemTable<Boot>, keys: Vec<VariableKe
     Ok(())
 
 }
+
+// Describes a UEFI variable by its vendor GUID, attributes and size,
+// so that it can be told apart from others with the same name
+fn describe_variable(system_table: &SystemTable<Boot>, key: &VariableKey)
+    -> Result<String, AppError> {
+
+    // Store a reference to UEFI Runtime Services
+    let runtime_services = system_table.runtime_services();
+
+    // Retrieve the size, and then the variable for its attributes
+    let name = key.name()?;
+    let size = runtime_services.get_variable_size(name, &key.vendor)
+        .map_err(|e| AppError::UefiVarSizeGet(name.to_string(), e.status()))?;
+    let mut buffer = vec![0; size];
+    let (_, attributes) = runtime_services.get_variable(name, &key.vendor, &mut buffer)
+        .map_err(|e| AppError::UefiVarGet(name.to_string(), e.status()))?;
+
+    // Abbreviate the attributes set
+    let attributes = [
+        (VariableAttributes::NON_VOLATILE, "NV"),
+        (VariableAttributes::BOOTSERVICE_ACCESS, "BS"),
+        (VariableAttributes::RUNTIME_ACCESS, "RT"),
+        (VariableAttributes::HARDWARE_ERROR_RECORD, "HR"),
+        (VariableAttributes::AUTHENTICATED_WRITE_ACCESS, "AW"),
+        (VariableAttributes::TIME_BASED_AUTHENTICATED_WRITE_ACCESS, "AT"),
+        (VariableAttributes::APPEND_WRITE, "AP"),
+    ].iter()
+        .filter(|(flag, _)| attributes.contains(*flag))
+        .map(|(_, abbr)| *abbr)
+        .collect::<Vec<_>>()
+        .join(config::VAR_ATTR_SEP);
+
+    // Format the description
+    Ok(format!("{{{}}} {} {}{size:#06x}",
+        format_guid(&key.vendor.0.to_bytes()), attributes, msg::VAR_SIZE))
+
+}
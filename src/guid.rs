@@ -0,0 +1,54 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: GUID
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Builds and formats GUIDs kept as bytes in memory order
+
+// Note: this module has no dependencies on the UEFI environment,
+// so that it can also be used on a host
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{format, string::String};
+
+// Builds a GUID in memory order from its registry format fields,
+// so that well-known GUIDs can be declared as constants
+pub const fn guid(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> [u8; 16] {
+    let d1 = data1.to_le_bytes();
+    let d2 = data2.to_le_bytes();
+    let d3 = data3.to_le_bytes();
+    [d1[0], d1[1], d1[2], d1[3], d2[0], d2[1], d3[0], d3[1],
+        data4[0], data4[1], data4[2], data4[3], data4[4], data4[5], data4[6], data4[7]]
+}
+
+// Parses a GUID in the registry format, optionally in braces
+pub fn parse_guid(string: &str) -> Option<[u8; 16]> {
+
+    // Remove the braces, if any, and split into the fields
+    let string = string.strip_prefix('{').and_then(|s| s.strip_suffix('}')).unwrap_or(string);
+    let fields = string.split('-').collect::<alloc::vec::Vec<_>>();
+    if fields.len() != 5 || fields.iter().zip([8, 4, 4, 4, 12]).any(|(f, length)|
+        f.len() != length || !f.chars().all(|c| c.is_ascii_hexdigit())) {
+        return None;
+    }
+
+    // The last two fields make up the final eight bytes
+    let mut data4 = [0; 8];
+    let tail = [fields[3], fields[4]].concat();
+    for (i, byte) in data4.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&tail[i * 2 .. i * 2 + 2], 16).ok()?;
+    }
+
+    // Return the result
+    Some(guid(u32::from_str_radix(fields[0], 16).ok()?,
+        u16::from_str_radix(fields[1], 16).ok()?, u16::from_str_radix(fields[2], 16).ok()?, data4))
+
+}
+
+// Formats a GUID in the registry format
+pub fn format_guid(guid: &[u8; 16]) -> String {
+    format!("{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]), u16::from_le_bytes([guid[6], guid[7]]),
+        guid[8], guid[9], guid[10], guid[11], guid[12], guid[13], guid[14], guid[15])
+}
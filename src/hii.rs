@@ -0,0 +1,349 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: HII
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Builds and parses HII configuration strings (ConfigRequest and ConfigResp)
+
+// Note: this module operates on plain strings only and has no dependencies
+// on the UEFI environment, so that it can also be used and tested on a host
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+// Configuration string keywords
+const KEY_ALTCFG: &str = "ALTCFG";  // Alternative (default) configuration
+const KEY_GUID:   &str = "GUID";    // Variable store GUID
+const KEY_NAME:   &str = "NAME";    // Variable store name
+const KEY_OFFSET: &str = "OFFSET";  // Block offset within the store
+const KEY_PATH:   &str = "PATH";    // Device path of the driver
+const KEY_VALUE:  &str = "VALUE";   // Block value
+const KEY_WIDTH:  &str = "WIDTH";   // Block width in bytes
+
+// Configuration string separators
+const CHAR_ASS: char = '=';  // Keyword and value separator
+const CHAR_SEP: char = '&';  // Element separator
+
+// Configuration header
+// Identifies the variable store that a configuration string refers to
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigHeader {
+    pub guid: [u8; 16],  // Vendor GUID of the variable
+    pub name: Vec<u16>,  // Name of the variable, UCS-2
+    pub path: Vec<u8>,   // Device path of the driver owning the store
+}
+
+// Configuration block
+// A range of bytes within the variable store, with or without a value
+
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBlock {
+    pub offset: usize,          // Position within the variable store
+    pub value: Option<Vec<u8>>, // Data (response only), in memory order
+    pub width: usize,           // Number of bytes
+}
+
+// Implementation
+impl ConfigBlock {
+
+    // Splits the block into chunks of at most the size given, each with
+    // its offset and size, and the value as a number, if the block has one,
+    // padded to the full width, as leading zeros are sometimes omitted
+    pub fn split(&self, size_max: usize) -> Vec<(usize, usize, Option<usize>)> {
+
+        // Pad the value, if any, to the full width of the block
+        let value = self.value.as_ref().map(|value| {
+            let mut value = value.clone();
+            value.resize(self.width, 0);
+            value });
+
+        // Take each chunk in turn, the last one might be shorter
+        (0 .. self.width).step_by(size_max.max(1)).map(|start| {
+            let size = size_max.min(self.width - start);
+            let value = value.as_ref().map(|value| value_from_bytes(&value[start .. start + size]));
+            (self.offset + start, size, value)
+        }).collect()
+
+    }
+
+}
+
+// Configuration string
+// Request (blocks without values) or response (blocks with values)
+
+#[derive(Clone, Debug, Default)]
+pub struct ConfigString {
+    pub blocks: Vec<ConfigBlock>,  // Ranges requested or provided
+    pub header: ConfigHeader,      // Variable store identification
+}
+
+// Implementation: formatting as a configuration string
+impl Display for ConfigString {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+
+        // Header: GUID and path in memory order, name as UCS-2 code units
+        write!(f, "{KEY_GUID}{CHAR_ASS}{}{CHAR_SEP}{KEY_NAME}{CHAR_ASS}{}{CHAR_SEP}{KEY_PATH}{CHAR_ASS}{}",
+            encode_bytes(&self.header.guid),
+            self.header.name.iter().map(|c| format!("{c:04x}")).collect::<String>(),
+            encode_bytes(&self.header.path))?;
+
+        // Blocks: offset and width as numbers, value in reverse byte order
+        for block in &self.blocks {
+            write!(f, "{CHAR_SEP}{KEY_OFFSET}{CHAR_ASS}{:04x}{CHAR_SEP}{KEY_WIDTH}{CHAR_ASS}{:04x}",
+                block.offset, block.width)?;
+            if let Some(value) = &block.value {
+                write!(f, "{CHAR_SEP}{KEY_VALUE}{CHAR_ASS}{}",
+                    value.iter().rev().map(|b| format!("{b:02x}")).collect::<String>())?;
+            }
+        }
+
+        Ok(())
+
+    }
+
+}
+
+// Public Functions
+
+// Checks whether a string appears to be a configuration string
+pub fn is_config_string(string: &str) -> bool {
+    string.starts_with(&format!("{KEY_GUID}{CHAR_ASS}"))
+}
+
+// Parses a single configuration string, returns nothing if malformed,
+// or if it only holds an alternative (default) configuration
+pub fn parse_config_string(string: &str) -> Option<ConfigString> {
+
+    // Split into keyword and value pairs
+    let mut pairs = string.split(CHAR_SEP)
+        .filter(|p| !p.is_empty())
+        .map(|p| p.split_once(CHAR_ASS));
+
+    // Header elements are mandatory and always come first in order
+    let mut header = ConfigHeader::default();
+    let mut next = |key| match pairs.next() {
+        Some(Some((k, v))) if k == key => Some(v),
+        _ => None };
+    header.guid = decode_bytes(next(KEY_GUID)?)?.try_into().ok()?;
+    header.name = decode_name(next(KEY_NAME)?)?;
+    header.path = decode_bytes(next(KEY_PATH)?)?;
+
+    // Set up the result
+    let mut config = ConfigString { blocks: Vec::new(), header };
+
+    // Parse the body into blocks
+    for pair in pairs {
+        let (key, value) = pair?;
+        match key {
+
+            // Alternative configurations are not of interest
+            KEY_ALTCFG => return None,
+
+            // Offset opens a new block
+            KEY_OFFSET => config.blocks.push(ConfigBlock {
+                offset: decode_number(value)?, ..Default::default() }),
+
+            // Width and value belong to the current block
+            KEY_WIDTH => config.blocks.last_mut()?.width = decode_number(value)?,
+            KEY_VALUE => config.blocks.last_mut()?.value = Some(decode_value(value)?),
+
+            // Name-value storage elements are not supported
+            _ => {}
+
+        }
+    }
+
+    // Return the result
+    Some(config)
+
+}
+
+// Converts a number to the bytes of the size given, in memory order,
+// taking its lowest bytes, and padding with zeros past the number
+pub fn value_to_bytes(value: usize, size: usize) -> Vec<u8> {
+    let mut bytes = value.to_le_bytes().to_vec();
+    bytes.resize(size, 0);
+    bytes
+}
+
+// Converts bytes in memory order to a number, taking
+// only as many of them as the number can hold
+pub fn value_from_bytes(bytes: &[u8]) -> usize {
+    let mut value = [0; size_of::<usize>()];
+    let length = bytes.len().min(value.len());
+    value[.. length].copy_from_slice(&bytes[.. length]);
+    usize::from_le_bytes(value)
+}
+
+// Parses multiple concatenated configuration strings, such as those
+// returned when exporting, skipping alternative and malformed ones
+pub fn parse_config_strings(string: &str) -> Vec<ConfigString> {
+
+    // Each configuration string begins with a GUID element
+    let start = format!("{KEY_GUID}{CHAR_ASS}");
+    let mut starts = string.match_indices(&start)
+        .map(|(i, _)| i)
+        .filter(|&i| i == 0 || string[.. i].ends_with(CHAR_SEP))
+        .collect::<Vec<_>>();
+    starts.push(string.len());
+
+    // Parse each of them separately
+    starts.windows(2)
+        .filter_map(|w| parse_config_string(&string[w[0] .. w[1]]))
+        .collect()
+
+}
+
+// Private Functions
+
+// Decodes hexadecimal digit pairs into bytes in the order given
+fn decode_bytes(string: &str) -> Option<Vec<u8>> {
+    if !string.len().is_multiple_of(2) {
+        return None;
+    }
+    (0 .. string.len()).step_by(2)
+        .map(|i| u8::from_str_radix(string.get(i .. i + 2)?, 16).ok())
+        .collect()
+}
+
+// Decodes groups of four hexadecimal digits into UCS-2 code units
+fn decode_name(string: &str) -> Option<Vec<u16>> {
+    if !string.len().is_multiple_of(4) {
+        return None;
+    }
+    (0 .. string.len()).step_by(4)
+        .map(|i| u16::from_str_radix(string.get(i .. i + 4)?, 16).ok())
+        .collect()
+}
+
+// Decodes a hexadecimal number
+fn decode_number(string: &str) -> Option<usize> {
+    usize::from_str_radix(string, 16).ok()
+}
+
+// Decodes a value written most significant byte first into memory order
+fn decode_value(string: &str) -> Option<Vec<u8>> {
+
+    // Pad to whole bytes
+    let string = if string.len().is_multiple_of(2) {
+        String::from(string)
+    } else {
+        format!("0{string}")
+    };
+
+    // Reverse the byte order
+    let mut value = decode_bytes(&string)?;
+    value.reverse();
+    Some(value)
+
+}
+
+// Encodes bytes as hexadecimal digit pairs in the order given
+fn encode_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Header for the variable Setup, with a short device path
+    const HEADER: &str = "GUID=43d687eca4ebb54ba1e53f3e36b20da9&NAME=00530065007400750070&PATH=7fff0400";
+
+    // Formats a response and parses it back to the same contents
+    #[test]
+    fn round_trip() {
+
+        let config = ConfigString {
+            blocks: Vec::from([
+                ConfigBlock { offset: 0x10, value: Some(Vec::from([0x01])), width: 1 },
+                ConfigBlock { offset: 0x1A4, value: Some(Vec::from([0x34, 0x12])), width: 2 },
+                ConfigBlock { offset: 0x200, value: None, width: 4 }]),
+            header: ConfigHeader {
+                guid: crate::guid::parse_guid("EC87D643-EBA4-4BB5-A1E5-3F3E36B20DA9").unwrap(),
+                name: "Setup".encode_utf16().collect(),
+                path: Vec::from([0x7F, 0xFF, 0x04, 0x00]) } };
+
+        // Values are written most significant byte first
+        let string = config.to_string();
+        assert_eq!(string, format!("{HEADER}&OFFSET=0010&WIDTH=0001&VALUE=01\
+            &OFFSET=01a4&WIDTH=0002&VALUE=1234&OFFSET=0200&WIDTH=0004"));
+        assert!(is_config_string(&string));
+
+        // Parsed back the same
+        let parsed = parse_config_string(&string).unwrap();
+        assert_eq!(parsed.header, config.header);
+        let blocks = |c: &ConfigString| c.blocks.iter()
+            .map(|b| (b.offset, b.width, b.value.clone())).collect::<Vec<_>>();
+        assert_eq!(blocks(&parsed), blocks(&config));
+
+    }
+
+    // Reverses the value into memory order, including when
+    // the leading zeros are left out, which are not restored
+    #[test]
+    fn value_order() {
+        for (value, bytes) in [("01", &[0x01][..]), ("1234", &[0x34, 0x12]),
+            ("1", &[0x01]), ("102", &[0x02, 0x01]), ("00000102", &[0x02, 0x01, 0x00, 0x00])] {
+            let string = format!("{HEADER}&OFFSET=0000&WIDTH=0004&VALUE={value}");
+            let config = parse_config_string(&string).unwrap();
+            assert_eq!(config.blocks[0].value.as_deref(), Some(bytes), "{value}");
+        }
+        assert!(parse_config_string(&format!("{HEADER}&OFFSET=0000&WIDTH=0001&VALUE=1z")).is_none());
+    }
+
+    // Skips the alternative configurations in an export,
+    // along with the malformed ones, keeping the rest in order
+    #[test]
+    fn alternative_skipped() {
+
+        let export = format!("{HEADER}&OFFSET=0010&WIDTH=0001&VALUE=01\
+            &{HEADER}&ALTCFG=0000&OFFSET=0010&WIDTH=0001&VALUE=00\
+            &GUID=00&NAME=0041&PATH=00&OFFSET=0000&WIDTH=0001\
+            &{HEADER}&OFFSET=0011&WIDTH=0002&VALUE=0203");
+
+        // Alternative configuration on its own is not of interest
+        assert!(parse_config_string(&format!("{HEADER}&ALTCFG=0001")).is_none());
+
+        let configs = parse_config_strings(&export);
+        let blocks = configs.iter().flat_map(|c| &c.blocks)
+            .map(|b| (b.offset, b.value.clone())).collect::<Vec<_>>();
+        assert_eq!(blocks, [(0x10, Some(Vec::from([0x01]))), (0x11, Some(Vec::from([0x03, 0x02])))]);
+
+    }
+
+    // Splits a block into values of at most eight bytes, which
+    // convert back to the same bytes, whatever the width
+    #[test]
+    fn block_split() {
+
+        let bytes = (1 ..= 12).collect::<Vec<u8>>();
+        for width in [1, 4, 8, 12] {
+            let block = ConfigBlock { offset: 0x10, value: Some(bytes[.. width].to_vec()), width };
+            let chunks = block.split(8);
+            assert_eq!(chunks.iter().map(|&(offset, size, _)| (offset, size)).collect::<Vec<_>>(),
+                [(0x10, width.min(8)), (0x18, width.saturating_sub(8))][.. width.div_ceil(8)], "{width}");
+            let values = chunks.iter().flat_map(|&(_, size, value)| value_to_bytes(value.unwrap(), size));
+            assert_eq!(values.collect::<Vec<_>>(), block.value.unwrap(), "{width}");
+        }
+
+        // Value padded to the width, or none at all
+        let block = ConfigBlock { offset: 0, value: Some(Vec::from([0x34, 0x12])), width: 9 };
+        assert_eq!(block.split(8), [(0, 8, Some(0x1234)), (8, 1, Some(0))]);
+        assert_eq!(ConfigBlock { value: None, ..block }.split(8), [(0, 8, None), (8, 1, None)]);
+
+        // Lowest bytes taken, and the rest padded
+        assert_eq!(value_to_bytes(usize::MAX, 8), [0xFF; 8]);
+        assert_eq!(value_to_bytes(0x1234, 1), [0x34]);
+        assert_eq!(value_to_bytes(0x1234, 10), [0x34, 0x12, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(value_from_bytes(&bytes), 0x0807060504030201);
+
+    }
+
+}
@@ -0,0 +1,666 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Host-Side Tool
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Provides the entry point for the companion tool that runs on the host
+// operating system rather than in UEFI, to prepare input files for UVT
+
+// Shared modules use the allocation crate
+// explicitly, as they also work in a no_std context
+extern crate alloc;
+
+// Modules
+#[allow(dead_code)]  // Shared with the UEFI application, built here for the tests
+mod boot;      // Decodes and encodes load options and their order lists
+#[allow(dead_code)]  // Shared with the UEFI application, only partially used here
+mod config;    // Stores configurable parameters together for easy adjustment
+mod crypto;    // Computes SHA-256 hashes, and signs and verifies data with RSA keys
+mod guid;      // Builds and formats GUIDs kept as bytes in memory order
+#[allow(dead_code)]  // Shared with the UEFI application, built here for the tests
+mod hii;       // Builds and parses HII configuration strings (ConfigRequest and ConfigResp)
+mod ifr;       // Locates HII packages in firmware images and extracts IFR setup questions
+#[allow(dead_code)]  // Shared with the UEFI application, built here for the tests
+mod script;    // Parses the parts of the input that do not depend on the firmware
+mod siglist;   // Decodes the Secure Boot signature databases (EFI_SIGNATURE_LIST)
+#[allow(dead_code)]  // Shared with the UEFI application, which also locates the tables
+mod smbios;    // Reads the board identification from the SMBIOS tables
+
+// Declare fully-qualified symbols
+// to be used in the local scope
+use std::{collections::BTreeSet, env, fmt, fs, process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH}};
+
+// Symbols from other modules
+use config::host as cfg;
+use config::locale::host as msg;
+use siglist::{AuthenticatedUpdate, Signature, SignatureList, Timestamp};
+
+// Error list
+#[derive(Debug)]
+enum HostError {
+    ArgMissing(String),
+    ArgOpt(String),
+    Cmd(String),
+    FileRead(String, std::io::Error),
+    FileWrite(String, std::io::Error),
+    IfrFormsNone(String),
+    IfrStringsNone(String, String),
+    SignCert(String),
+    SignHash(String),
+    SignKey(String),
+    SignOwner(String),
+    SignSigner(String),
+    SignTime(String),
+    SignVar(String),
+    SignVerify(String),
+    SmbiosNone(String),
+    Usage,
+}
+
+// Error display implementation
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+
+            // Option requires a value
+            Self::ArgMissing(option) => {
+                write!(f, "{} {option}", msg::ERR_ARG_MISSING)
+            }
+
+            // Unrecognized option
+            Self::ArgOpt(option) => {
+                write!(f, "{} \"{option}\"", msg::ERR_ARG_OPT)
+            }
+
+            // Unrecognized command
+            Self::Cmd(command) => {
+                write!(f, "{} \"{command}\"", msg::ERR_CMD)
+            }
+
+            // Failed to read file
+            Self::FileRead(path, e) => {
+                write!(f, "{}: \"{path}\" ({e})", msg::ERR_FILE_READ)
+            }
+
+            // Failed to write file
+            Self::FileWrite(path, e) => {
+                write!(f, "{}: \"{path}\" ({e})", msg::ERR_FILE_WRITE)
+            }
+
+            // No form packages
+            Self::IfrFormsNone(path) => {
+                write!(f, "{} \"{path}\"", msg::ERR_IFR_FORMS_NONE)
+            }
+
+            // No string packages
+            Self::IfrStringsNone(language, path) => {
+                write!(f, "{} {language} {} \"{path}\"",
+                    msg::ERR_IFR_STRINGS_NONE[0], msg::ERR_IFR_STRINGS_NONE[1])
+            }
+
+            // Certificate to add malformed
+            Self::SignCert(path) => {
+                write!(f, "{}: \"{path}\"", msg::ERR_SIGN_CERT)
+            }
+
+            // Hash to add malformed
+            Self::SignHash(hash) => {
+                write!(f, "{}: \"{hash}\"", msg::ERR_SIGN_HASH)
+            }
+
+            // Private key unsupported
+            Self::SignKey(path) => {
+                write!(f, "{}: \"{path}\"", msg::ERR_SIGN_KEY)
+            }
+
+            // Owner GUID malformed
+            Self::SignOwner(owner) => {
+                write!(f, "{}: \"{owner}\"", msg::ERR_SIGN_OWNER)
+            }
+
+            // Signer certificate unsupported
+            Self::SignSigner(path) => {
+                write!(f, "{}: \"{path}\"", msg::ERR_SIGN_SIGNER)
+            }
+
+            // Timestamp malformed
+            Self::SignTime(time) => {
+                write!(f, "{}: \"{time}\"", msg::ERR_SIGN_TIME)
+            }
+
+            // Not a signature database
+            Self::SignVar(name) => {
+                write!(f, "{}: \"{name}\"", msg::ERR_SIGN_VAR)
+            }
+
+            // Signature does not verify
+            Self::SignVerify(path) => {
+                write!(f, "{}: \"{path}\"", msg::ERR_SIGN_VERIFY)
+            }
+
+            // No board identification
+            Self::SmbiosNone(path) => {
+                write!(f, "{} \"{path}\"", msg::ERR_SMBIOS_NONE)
+            }
+
+            // Usage information requested (not actual error)
+            Self::Usage => {
+                write!(f, "")
+            }
+
+        }
+
+    }
+
+}
+
+// Main entry point to the host-side tool
+fn main() -> ExitCode {
+
+    // Skip the executable name
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    // Dispatch the command
+    let result = match args.first().map(String::as_str) {
+
+        // Extract setup questions
+        Some(cfg::CMD_IFR) => run_ifr(&args[1 ..]),
+
+        // Decode signature databases
+        Some(cfg::CMD_SIGLIST) => run_siglist(&args[1 ..]),
+
+        // Sign a signature database update
+        Some(cfg::CMD_SIGN) => run_sign(&args[1 ..]),
+
+        // Show the board identification
+        Some(cfg::CMD_SMBIOS) => run_smbios(&args[1 ..]),
+
+        // Show usage information
+        None | Some(cfg::OPT_USAGE) | Some(cfg::OPT_USAGE_LONG) => Err(HostError::Usage),
+
+        // Unknown
+        Some(command) => Err(HostError::Cmd(command.to_owned())),
+
+    };
+
+    match result {
+
+        // Success
+        Ok(()) => ExitCode::SUCCESS,
+
+        // Show usage information
+        Err(HostError::Usage) => {
+            println!("{}", msg::USAGE);
+            ExitCode::SUCCESS
+        }
+
+        // Failure
+        Err(e) => {
+            eprintln!("{}: {e}", msg::ERR_PREFIX);
+            ExitCode::FAILURE
+        }
+
+    }
+
+}
+
+// Extracts the setup questions from a firmware image as definitions
+fn run_ifr(args: &[String]) -> Result<(), HostError> {
+
+    // Set up the defaults
+    let mut language = cfg::DEFAULT_LANG.to_owned();
+    let mut paths = Vec::new();
+
+    // Parse the arguments
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+
+            // String language
+            cfg::OPT_LANG | cfg::OPT_LANG_LONG => {
+                language = args.next()
+                    .ok_or_else(|| HostError::ArgMissing(arg.to_owned()))?.to_owned();
+            }
+
+            // Show usage information
+            cfg::OPT_USAGE | cfg::OPT_USAGE_LONG => return Err(HostError::Usage),
+
+            // Unknown option
+            option if option.starts_with(config::CHAR_ARG_OPT) => {
+                return Err(HostError::ArgOpt(option.to_owned()));
+            }
+
+            // Input image and output file
+            path => paths.push(path.to_owned()),
+
+        }
+    }
+
+    // Image path is mandatory, at most two paths
+    if paths.is_empty() || paths.len() > 2 {
+        return Err(HostError::Usage);
+    }
+
+    // Read the image
+    let image = fs::read(&paths[0])
+        .map_err(|e| HostError::FileRead(paths[0].to_owned(), e))?;
+
+    // Extract the definitions
+    let lines = format_ifr(&image, &paths[0], &language)?;
+
+    // Write to the output file if given
+    if let Some(path) = paths.get(1) {
+        write_utf16(path, &lines)
+    } else {
+
+        // Otherwise, standard output
+        for line in lines {
+            println!("{line}");
+        }
+        Ok(())
+
+    }
+
+}
+
+// Extracts the setup questions from a firmware image as the lines of
+// an input file, with the path shown in the header as given
+fn format_ifr(image: &[u8], path: &str, language: &str) -> Result<Vec<String>, HostError> {
+
+    // Locate the HII packages
+    let form_sets = ifr::find_form_packages(image);
+    let string_packages = ifr::find_string_packages(image);
+
+    // Form packages are a must
+    if form_sets.is_empty() {
+        return Err(HostError::IfrFormsNone(path.to_owned()));
+    }
+
+    // Pair each form set with its strings
+    let form_sets = form_sets.iter().filter_map(|f|
+        ifr::find_strings_for(f, &string_packages, language).map(|s| (f, s)))
+        .collect::<Vec<_>>();
+
+    // Strings in the requested language are a must as well
+    if form_sets.is_empty() {
+        return Err(HostError::IfrStringsNone(language.to_owned(), path.to_owned()));
+    }
+
+    // Output header
+    let comment = config::CHAR_INPUT_COMMENT;
+    let mut lines = vec![
+        format!("{comment} {}", msg::IFR_HEADER[0]),
+        format!("{comment} {} {}", msg::IFR_HEADER[1], path),
+        format!("{comment} {}: {language}", msg::IFR_HEADER[2]),
+    ];
+
+    // Definition names must be unique across all form sets
+    let mut names = BTreeSet::new();
+
+    // Output each form set
+    for (form_set, strings) in form_sets {
+
+        // Skip the form sets without questions
+        let definitions = ifr::format_definitions(form_set, strings, &mut names);
+        if definitions.is_empty() {
+            continue;
+        }
+
+        // Section heading with the form set title
+        lines.push(String::new());
+        lines.push(format!("{comment}{comment}{comment} {} {{{}}}",
+            strings.get(form_set.title), guid::format_guid(&form_set.guid)));
+        lines.push(String::new());
+
+        // Variable stores, identified by their GUIDs
+        for var_store in &form_set.var_stores {
+            lines.push(format!("{comment} {}: {} {{{}}} {}: {:#06x}",
+                msg::IFR_VAR_STORE[0], var_store.name, guid::format_guid(&var_store.guid),
+                msg::IFR_VAR_STORE[1], var_store.size));
+        }
+        lines.push(String::new());
+
+        // Definitions
+        lines.extend(definitions);
+
+    }
+
+    // Return the result
+    Ok(lines)
+
+}
+
+// Decodes Secure Boot signature databases captured as files
+fn run_siglist(args: &[String]) -> Result<(), HostError> {
+
+    // Parse the arguments
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+
+            // Show usage information
+            cfg::OPT_USAGE | cfg::OPT_USAGE_LONG => return Err(HostError::Usage),
+
+            // Unknown option
+            option if option.starts_with(config::CHAR_ARG_OPT) => {
+                return Err(HostError::ArgOpt(option.to_owned()));
+            }
+
+            // Input file
+            path => paths.push(path.to_owned()),
+
+        }
+    }
+
+    // At least one file is needed
+    if paths.is_empty() {
+        return Err(HostError::Usage);
+    }
+
+    // Decode each file in turn
+    for path in paths {
+
+        // Read the file
+        let bytes = fs::read(&path)
+            .map_err(|e| HostError::FileRead(path.to_owned(), e))?;
+
+        // Signed updates (.auth) begin with the authentication header,
+        // while files from efivarfs begin with the variable attributes,
+        // skip either if the contents do not decode as they are
+        let contents = if siglist::SignatureList::parse_all(&bytes).is_some() {
+            &bytes[..]
+        } else if let Some(update) = siglist::AuthenticatedUpdate::parse(&bytes) {
+            println!("{path}: {} {}, {} {}", config::locale::SECURE_BOOT_SIGNED[0],
+                update.timestamp, update.signature.len(), config::locale::SECURE_BOOT_SIGNED[1]);
+            update.content
+        } else if bytes.len() > cfg::EFIVARFS_ATTR_SIZE {
+            &bytes[cfg::EFIVARFS_ATTR_SIZE ..]
+        } else {
+            &bytes[..]
+        };
+
+        // Output
+        for line in siglist::describe_signature_database(&path, contents) {
+            println!("{line}");
+        }
+
+    }
+
+    // Return no error
+    Ok(())
+
+}
+
+// Shows the board identification from SMBIOS tables captured as files
+fn run_smbios(args: &[String]) -> Result<(), HostError> {
+
+    // Parse the arguments
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+
+            // Show usage information
+            cfg::OPT_USAGE | cfg::OPT_USAGE_LONG => return Err(HostError::Usage),
+
+            // Unknown option
+            option if option.starts_with(config::CHAR_ARG_OPT) => {
+                return Err(HostError::ArgOpt(option.to_owned()));
+            }
+
+            // Input file
+            path => paths.push(path.to_owned()),
+
+        }
+    }
+
+    // At least one file is needed
+    if paths.is_empty() {
+        return Err(HostError::Usage);
+    }
+
+    // Decode each file in turn
+    for path in paths {
+
+        // Read the file
+        let bytes = fs::read(&path)
+            .map_err(|e| HostError::FileRead(path.to_owned(), e))?;
+        let info = smbios::SmbiosInfo::parse_captured(&bytes);
+        if info.is_empty() {
+            return Err(HostError::SmbiosNone(path));
+        }
+
+        // Output the properties as they would be written in the conditions
+        println!("{} {path}: {info}", config::CHAR_INPUT_COMMENT);
+        for (key, value) in [
+            (config::COND_KEY_MANUFACTURER, &info.system_manufacturer),
+            (config::COND_KEY_PRODUCT, &info.system_product),
+            (config::COND_KEY_BOARD, &info.board_product),
+            (config::COND_KEY_BIOS, &info.bios_version),
+            (config::COND_KEY_BIOS_DATE, &info.bios_date)] {
+            println!("{key}{}{}{value}{}", config::COND_EQ, config::CHAR_COND_QUOTE, config::CHAR_COND_QUOTE);
+        }
+
+    }
+
+    // Return no error
+    Ok(())
+
+}
+
+// Builds signature lists from certificates and hashes, and signs them
+// with a key and its certificate as an update to a signature database
+fn run_sign(args: &[String]) -> Result<(), HostError> {
+
+    // Set up the defaults
+    let mut append = false;
+    let mut certificates = Vec::new();
+    let mut hashes = Vec::new();
+    let mut key_path = None;
+    let mut owner = [0; 16];
+    let mut paths = Vec::new();
+    let mut signer_path = None;
+    let mut timestamp = None;
+
+    // Parse the arguments
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+
+        // Retrieves the value of an option that requires one
+        let mut value = || args.next().ok_or_else(|| HostError::ArgMissing(arg.to_owned()));
+
+        match arg.as_str() {
+
+            // Append rather than replace
+            cfg::OPT_APPEND | cfg::OPT_APPEND_LONG => append = true,
+
+            // Certificate to add
+            cfg::OPT_CERT | cfg::OPT_CERT_LONG => certificates.push(value()?.to_owned()),
+
+            // Hash to add, 64 hexadecimal digits
+            cfg::OPT_HASH | cfg::OPT_HASH_LONG => {
+                let hash = value()?;
+                let error = || HostError::SignHash(hash.to_owned());
+                if hash.len() != crypto::SHA256_SIZE * 2 || !hash.is_ascii() {
+                    return Err(error());
+                }
+                hashes.push((0 .. hash.len()).step_by(2)
+                    .map(|i| u8::from_str_radix(&hash[i .. i + 2], 16).map_err(|_| error()))
+                    .collect::<Result<Vec<_>, _>>()?);
+            }
+
+            // Private key to sign with
+            cfg::OPT_KEY | cfg::OPT_KEY_LONG => key_path = Some(value()?.to_owned()),
+
+            // Owner of the signatures added
+            cfg::OPT_OWNER | cfg::OPT_OWNER_LONG => {
+                let string = value()?;
+                owner = guid::parse_guid(string)
+                    .ok_or_else(|| HostError::SignOwner(string.to_owned()))?;
+            }
+
+            // Certificate of the private key
+            cfg::OPT_SIGNER | cfg::OPT_SIGNER_LONG => signer_path = Some(value()?.to_owned()),
+
+            // Timestamp of the update
+            cfg::OPT_TIME | cfg::OPT_TIME_LONG => {
+                let string = value()?;
+                timestamp = Some(Timestamp::parse(string)
+                    .ok_or_else(|| HostError::SignTime(string.to_owned()))?);
+            }
+
+            // Show usage information
+            cfg::OPT_USAGE | cfg::OPT_USAGE_LONG => return Err(HostError::Usage),
+
+            // Unknown option
+            option if option.starts_with(config::CHAR_ARG_OPT) => {
+                return Err(HostError::ArgOpt(option.to_owned()));
+            }
+
+            // Variable name and output file
+            path => paths.push(path.to_owned()),
+
+        }
+    }
+
+    // Variable name and output file are mandatory,
+    // and so are the key and its certificate
+    let [name, output] = &paths[..] else {
+        return Err(HostError::Usage);
+    };
+    let key_path = key_path.ok_or_else(|| HostError::ArgMissing(cfg::OPT_KEY_LONG.to_owned()))?;
+    let signer_path = signer_path
+        .ok_or_else(|| HostError::ArgMissing(cfg::OPT_SIGNER_LONG.to_owned()))?;
+
+    // Only the signature databases accept authenticated updates
+    if !config::VAR_SIGLISTS_AUTH.contains(&name.as_str()) {
+        return Err(HostError::SignVar(name.to_owned()));
+    }
+
+    // Load the key and its certificate
+    let key = crypto::PrivateKey::from_der(&read_der_or_pem(&key_path)?)
+        .ok_or_else(|| HostError::SignKey(key_path.to_owned()))?;
+    let signer = crypto::Signer::from_der(&read_der_or_pem(&signer_path)?)
+        .ok_or_else(|| HostError::SignSigner(signer_path.to_owned()))?;
+
+    // A list for each certificate, since they differ in size
+    let mut lists = Vec::new();
+    for path in certificates {
+        let data = read_der_or_pem(&path)?;
+        if siglist::Certificate::from_der(&data).is_none() {
+            return Err(HostError::SignCert(path));
+        }
+        lists.push(SignatureList { kind: siglist::TYPE_X509,
+            signatures: vec![Signature { owner, data }] });
+    }
+
+    // A single list for all the hashes
+    if !hashes.is_empty() {
+        lists.push(SignatureList { kind: siglist::TYPE_SHA256,
+            signatures: hashes.into_iter().map(|data| Signature { owner, data }).collect() });
+    }
+
+    // Sign the lists together with the variable name, attributes and timestamp
+    let content = lists.iter().flat_map(SignatureList::to_bytes).collect::<Vec<_>>();
+    let timestamp = timestamp.unwrap_or_else(current_timestamp);
+    let attributes = siglist::AUTH_ATTRIBUTES
+        | if append { siglist::AUTH_ATTRIBUTE_APPEND } else { 0 };
+    let data = siglist::signed_data(name, attributes, &timestamp, &content);
+    let signature = crypto::sign_pkcs7(&data, &key, &signer)
+        .ok_or_else(|| HostError::SignKey(key_path.to_owned()))?;
+
+    // Verify the signature, which fails if the key does not match the certificate
+    if !crypto::verify_pkcs7(&data, &signature, &signer.key) {
+        return Err(HostError::SignVerify(signer_path));
+    }
+
+    // Write the update
+    let update = AuthenticatedUpdate { timestamp, signature: &signature, content: &content };
+    fs::write(output, update.to_bytes())
+        .map_err(|e| HostError::FileWrite(output.to_owned(), e))?;
+
+    // Describe the update, decoding it the same way as in UEFI
+    println!("{output}: {} {timestamp}, {} {}", config::locale::SECURE_BOOT_SIGNED[0],
+        signature.len(), config::locale::SECURE_BOOT_SIGNED[1]);
+    println!("{output}: {} \"{signer_path}\"", msg::SIGN_VERIFIED);
+    for line in siglist::describe_signature_database(name, &content) {
+        println!("{line}");
+    }
+
+    // Return no error
+    Ok(())
+
+}
+
+// Determines the current date and time in UTC
+fn current_timestamp() -> Timestamp {
+
+    // Days and seconds since the Unix epoch
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, time) = ((seconds / 86400) as i64, seconds % 86400);
+
+    // Convert the days to a date in the Gregorian calendar, counting
+    // the years from March, so that the leap day comes last
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    // Return the result
+    Timestamp { year: year as u16, month: month as u8, day: day as u8,
+        hour: (time / 3600) as u8, minute: (time / 60 % 60) as u8, second: (time % 60) as u8 }
+
+}
+
+// Reads a key or a certificate, either PEM-armored or DER-encoded
+fn read_der_or_pem(path: &str) -> Result<Vec<u8>, HostError> {
+    let bytes = fs::read(path).map_err(|e| HostError::FileRead(path.to_owned(), e))?;
+    Ok(std::str::from_utf8(&bytes).ok().and_then(crypto::decode_pem).unwrap_or(bytes))
+}
+
+// Writes lines to a file as UTF-16 LE text, the encoding expected by UVT
+fn write_utf16(path: &str, lines: &[String]) -> Result<(), HostError> {
+
+    // Byte Order Mark first, then each line followed by a line break
+    let text = lines.iter().fold(String::from(config::CHAR_CTL_BOM),
+        |text, line| text + line + cfg::LINE_BREAK);
+
+    // Encode as little-endian
+    let bytes = text.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>();
+
+    // Write
+    fs::write(path, bytes).map_err(|e| HostError::FileWrite(path.to_owned(), e))
+
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Extracts the definitions from a constructed image, see fixtures/README.md
+    #[test]
+    fn ifr_definitions() {
+        let image = include_bytes!("../fixtures/ifr/setup.bin");
+        let lines = format_ifr(image, "setup.bin", "en-US").unwrap();
+        assert_eq!(lines, include_str!("../fixtures/ifr/setup.txt").lines().collect::<Vec<_>>());
+    }
+
+    // Requires both the form packages, and the strings in the given language
+    #[test]
+    fn ifr_missing() {
+        let image = include_bytes!("../fixtures/ifr/setup.bin");
+        assert!(matches!(format_ifr(image, "setup.bin", "fr-FR"),
+            Err(HostError::IfrStringsNone(language, _)) if language == "fr-FR"));
+        assert!(matches!(format_ifr(&image[0x100 ..], "strings.bin", "en-US"),
+            Err(HostError::IfrFormsNone(path)) if path == "strings.bin"));
+    }
+
+}
@@ -0,0 +1,785 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: IFR
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Locates HII packages in firmware images and extracts IFR setup questions
+
+// Note: this module operates on raw byte slices only and has no dependencies
+// on the UEFI environment, so that it can be used by the host-side tool
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{borrow::ToOwned, collections::BTreeSet, format, string::String, vec::Vec};
+
+// Symbols from other modules
+use crate::config;
+use crate::guid::format_guid;
+
+// HII package types
+const HII_PACKAGE_FORMS:   u8 = 0x02;  // Form package (IFR)
+const HII_PACKAGE_STRINGS: u8 = 0x04;  // String package
+
+// HII package header sizes and offsets
+const HII_PACKAGE_HEADER:    usize = 0x04;  // Length (24 bits) and type (8 bits)
+const HII_STRINGS_LANGUAGE:  usize = 0x2E;  // Language name within string package header
+
+// HII string information block types
+const SIBT_END:                u8 = 0x00;  // End of string information
+const SIBT_STRING_SCSU:        u8 = 0x10;  // Single SCSU-encoded string
+const SIBT_STRING_SCSU_FONT:   u8 = 0x11;  // Single SCSU-encoded string with font
+const SIBT_STRINGS_SCSU:       u8 = 0x12;  // Multiple SCSU-encoded strings
+const SIBT_STRINGS_SCSU_FONT:  u8 = 0x13;  // Multiple SCSU-encoded strings with font
+const SIBT_STRING_UCS2:        u8 = 0x14;  // Single UCS-2 string
+const SIBT_STRING_UCS2_FONT:   u8 = 0x15;  // Single UCS-2 string with font
+const SIBT_STRINGS_UCS2:       u8 = 0x16;  // Multiple UCS-2 strings
+const SIBT_STRINGS_UCS2_FONT:  u8 = 0x17;  // Multiple UCS-2 strings with font
+const SIBT_DUPLICATE:          u8 = 0x20;  // Duplicate of an earlier string
+const SIBT_SKIP2:              u8 = 0x21;  // Skip a number of identifiers (16-bit count)
+const SIBT_SKIP1:              u8 = 0x22;  // Skip a number of identifiers (8-bit count)
+const SIBT_EXT1:               u8 = 0x30;  // Extended block (8-bit length)
+const SIBT_EXT2:               u8 = 0x31;  // Extended block (16-bit length)
+const SIBT_EXT4:               u8 = 0x32;  // Extended block (32-bit length)
+
+// IFR operation codes
+const IFR_FORM:          u8 = 0x01;  // Form
+const IFR_ONE_OF:        u8 = 0x05;  // Selection from a list of options
+const IFR_CHECKBOX:      u8 = 0x06;  // Boolean switch
+const IFR_NUMERIC:       u8 = 0x07;  // Number within a range
+const IFR_ONE_OF_OPTION: u8 = 0x09;  // Option for a selection
+const IFR_FORM_SET:      u8 = 0x0E;  // Form set, always first in the package
+const IFR_VARSTORE:      u8 = 0x24;  // Buffer variable store
+const IFR_VARSTORE_EFI:  u8 = 0x26;  // UEFI variable store
+const IFR_END:           u8 = 0x29;  // End of scope
+const IFR_DEFAULT:       u8 = 0x5B;  // Default value
+
+// IFR flags
+const IFR_CHECKBOX_DEFAULT:  u8 = 0x01;  // Checkbox set by default
+const IFR_NUMERIC_SIZE:      u8 = 0x03;  // Mask for numeric value size
+const IFR_OPTION_DEFAULT:    u8 = 0x10;  // Option selected by default
+const IFR_SCOPE:             u8 = 0x80;  // Operation opens a new scope
+
+// String package
+// All strings of a package for a single language
+
+pub struct StringPackage {
+    pub language: String,      // Language code, such as en-US
+    pub length: usize,         // Length of the package in bytes
+    pub offset: usize,         // Position of the package in the image
+    pub strings: Vec<String>,  // Strings indexed by identifier
+}
+
+// Implementation
+impl StringPackage {
+
+    // Retrieves a string by its identifier
+    pub fn get(&self, id: u16) -> &str {
+        self.strings.get(id as usize).map_or("", |s| s.as_str())
+    }
+
+}
+
+// Variable store
+// Storage backing the setup questions
+
+pub struct VarStore {
+    pub guid: [u8; 16],  // Vendor GUID of the UEFI variable
+    pub id: u16,         // Identifier used by questions
+    pub name: String,    // Name of the UEFI variable
+    pub size: u16,       // Size of the variable
+}
+
+// Question type
+// Kind of setup option presented to the user
+
+pub enum QuestionKind {
+    CheckBox,                                  // Boolean switch
+    Numeric { max: u64, min: u64, step: u64 },  // Number within a range
+    OneOf,                                     // Selection from a list
+}
+
+// Question option
+// Single choice for a selection question
+
+pub struct QuestionOption {
+    pub default: bool,  // Selected by default
+    pub text: u16,      // Option string identifier
+    pub value: u64,     // Value stored if selected
+}
+
+// Question
+// Setup option stored at an offset within a variable store
+
+pub struct Question {
+    pub default: Option<u64>,          // Default value, if known
+    pub form: u16,                     // Form title string identifier
+    pub kind: QuestionKind,            // Type of the question
+    pub offset: u16,                   // Offset within the variable store
+    pub options: Vec<QuestionOption>,  // Options for a selection
+    pub prompt: u16,                   // Prompt string identifier
+    pub size: usize,                   // Value size in bytes
+    pub var_store: u16,                // Variable store identifier
+}
+
+// Form set
+// All questions and variable stores of a form package
+
+pub struct FormSet {
+    pub guid: [u8; 16],              // Form set GUID
+    pub length: usize,               // Length of the package in bytes
+    pub offset: usize,               // Position of the package in the image
+    pub questions: Vec<Question>,    // Questions in order of appearance
+    pub title: u16,                  // Title string identifier
+    pub var_stores: Vec<VarStore>,   // Variable stores declared
+}
+
+// Implementation
+impl FormSet {
+
+    // Retrieves a variable store by its identifier
+    pub fn var_store(&self, id: u16) -> Option<&VarStore> {
+        self.var_stores.iter().find(|v| v.id == id)
+    }
+
+}
+
+// Public Functions
+
+// Locates all form packages within a firmware image
+pub fn find_form_packages(data: &[u8]) -> Vec<FormSet> {
+
+    // Set up output and helper variables
+    let mut form_sets = Vec::new();
+    let mut at = 0;
+
+    // Try every position, skipping past packages found
+    while at + HII_PACKAGE_HEADER < data.len() {
+        if let Some(form_set) = parse_form_package(data, at) {
+            at += form_set.length;
+            form_sets.push(form_set);
+        } else {
+            at += 1;
+        }
+    }
+
+    // Return the result
+    form_sets
+
+}
+
+// Locates all string packages within a firmware image
+pub fn find_string_packages(data: &[u8]) -> Vec<StringPackage> {
+
+    // Set up output and helper variables
+    let mut packages = Vec::new();
+    let mut at = 0;
+
+    // Try every position, skipping past packages found
+    while at + HII_PACKAGE_HEADER < data.len() {
+        if let Some(package) = parse_string_package(data, at) {
+            at += package.length;
+            packages.push(package);
+        } else {
+            at += 1;
+        }
+    }
+
+    // Return the result
+    packages
+
+}
+
+// Picks the string package belonging with a form package: the
+// package for the given language that is the closest in the image
+pub fn find_strings_for<'a>(form_set: &FormSet,
+    packages: &'a [StringPackage], language: &str) -> Option<&'a StringPackage> {
+
+    packages.iter()
+        .filter(|p| p.language.split(';').any(|l| l.eq_ignore_ascii_case(language)))
+        .min_by_key(|p| p.offset.abs_diff(form_set.offset))
+
+}
+
+// Formats the questions of a form set as definition lines:
+// <Def>,<VarName>{<GUID>}:<Offset>[(<Size>)]  # <Form>: <Prompt> [<Options>]
+pub fn format_definitions(form_set: &FormSet,
+    strings: &StringPackage, names: &mut BTreeSet<String>) -> Vec<String> {
+
+    // Build the definition and the comment for each question
+    let entries = form_set.questions.iter().filter_map(|q| {
+
+        // Only questions stored in a known variable can be accessed
+        let var_store = form_set.var_store(q.var_store)?;
+
+        // Derive a unique definition name from the prompt
+        let name = definition_name(strings.get(q.prompt), names);
+
+        // Size is only shown if not a single byte
+        let size = if q.size == 1 {
+            "".to_owned()
+        } else {
+            format!("{}{}{}", config::CHAR_ARG_BKT_L, q.size, config::CHAR_ARG_BKT_R)
+        };
+
+        // Definition, in the same format accepted in the input, with
+        // the vendor GUID, since variable stores may share their names
+        let definition = format!("{name}{} {}{}{}{}{}{:#06x}{size}",
+            config::CHAR_INPUT_DEF, var_store.name, config::CHAR_ARG_GUID_L,
+            format_guid(&var_store.guid), config::CHAR_ARG_GUID_R, config::CHAR_ARG_POS, q.offset);
+
+        // Comment, with the form title, prompt and the options
+        let comment = format!("{} {}: {} [{}]", config::CHAR_INPUT_COMMENT,
+            strings.get(q.form), strings.get(q.prompt), format_options(q, strings));
+
+        Some((definition, comment))
+
+    }).collect::<Vec<_>>();
+
+    // Align the comments in a single column
+    let width = entries.iter().map(|(d, _)| d.chars().count()).max().unwrap_or(0);
+
+    // Return the formatted lines
+    entries.into_iter()
+        .map(|(d, c)| format!("{d:<width$}  {c}"))
+        .collect()
+
+}
+
+// Private Functions
+
+// Derives a unique definition name from a question prompt
+// by capitalizing and joining all of its alphanumeric words
+fn definition_name(prompt: &str, names: &mut BTreeSet<String>) -> String {
+
+    // Join the words, capitalizing the first letter of each
+    let mut name = prompt.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            format!("{first}{}", chars.as_str())
+        }).collect::<String>();
+
+    // Prompt might have no usable characters
+    if name.is_empty() {
+        name = "Question".to_owned();
+    }
+
+    // Number the namesakes to keep every name unique
+    let mut unique = name.clone();
+    let mut count = 1;
+    while names.contains(&unique) {
+        count += 1;
+        unique = format!("{name}_{count}");
+    }
+
+    // Remember and return
+    names.insert(unique.clone());
+    unique
+
+}
+
+// Formats the possible values of a question,
+// marking the default one with an asterisk
+fn format_options(question: &Question, strings: &StringPackage) -> String {
+
+    // Hexadecimal value padded to the question size
+    let width = 2 + question.size * 2;
+    let hex = |value: u64| format!("{value:#0width$x}");
+
+    // Default marker
+    let mark = |default: bool| if default { "*" } else { "" };
+
+    match question.kind {
+
+        // Both states of a switch
+        QuestionKind::CheckBox => {
+            format!("{}{} / {}{}",
+                hex(0), mark(question.default == Some(0)),
+                hex(1), mark(question.default == Some(1)))
+        }
+
+        // Range and the default within
+        QuestionKind::Numeric { max, min, step } => {
+            let mut options = format!("{}-{}", hex(min), hex(max));
+            if step > 1 {
+                options.push_str(&format!(" / {step}"));
+            }
+            if let Some(default) = question.default {
+                options.push_str(&format!(" / {}*", hex(default)));
+            }
+            options
+        }
+
+        // All options with their descriptions
+        QuestionKind::OneOf => {
+            question.options.iter().map(|o| format!("{}: {}{}",
+                hex(o.value), strings.get(o.text),
+                mark(o.default || question.default == Some(o.value))))
+                .collect::<Vec<_>>().join(" / ")
+        }
+
+    }
+
+}
+
+// Attempts to parse a form package at the given position
+fn parse_form_package(data: &[u8], at: usize) -> Option<FormSet> {
+
+    // Check the package header
+    let length = read_u24(data, at)?;
+    if data[at + 3] != HII_PACKAGE_FORMS
+        || length <= HII_PACKAGE_HEADER
+        || at + length > data.len() {
+        return None;
+    }
+
+    // The package has to open with a form set
+    let body = &data[at + HII_PACKAGE_HEADER .. at + length];
+    if *body.first()? != IFR_FORM_SET || (*body.get(1)? & !IFR_SCOPE) < 0x17 {
+        return None;
+    }
+
+    // Set up the form set
+    let mut form_set = FormSet {
+        guid: read_guid(body, 2)?, length, offset: at,
+        questions: Vec::new(), title: read_u16(body, 18)?, var_stores: Vec::new() };
+
+    // Set up helper variables
+    let mut depth = 0usize;            // Current scope nesting level
+    let mut form = 0;                  // Current form title
+    let mut question = None;           // Current question and its scope level
+    let mut position = 0;
+
+    // Iterate through the operations
+    while position + 2 <= body.len() {
+
+        // Operation code, length and scope flag
+        let opcode = body[position];
+        let op_length = (body[position + 1] & !IFR_SCOPE) as usize;
+        let scope = body[position + 1] & IFR_SCOPE != 0;
+
+        // Stop on malformed operations
+        if op_length < 2 || position + op_length > body.len() {
+            return None;
+        }
+
+        // Operation data
+        let op = &body[position .. position + op_length];
+
+        match opcode {
+
+            // Form
+            IFR_FORM => form = read_u16(op, 4)?,
+
+            // Variable stores
+            IFR_VARSTORE => if let Some(v) = parse_var_store(op, 18, 20, 22) {
+                form_set.var_stores.push(v)
+            },
+            IFR_VARSTORE_EFI => if let Some(v) = parse_var_store(op, 18, 24, 26) {
+                form_set.var_stores.push(v)
+            },
+
+            // Questions
+            IFR_CHECKBOX | IFR_NUMERIC | IFR_ONE_OF => {
+
+                // Close any question left open
+                if let Some((q, _)) = question.take() {
+                    form_set.questions.push(q);
+                }
+
+                // Parse the new question, a question
+                // without scope is complete at once
+                if let Some(q) = parse_question(op, opcode, form) {
+                    if scope {
+                        question = Some((q, depth));
+                    } else {
+                        form_set.questions.push(q);
+                    }
+                }
+
+            }
+
+            // Option for the current question
+            IFR_ONE_OF_OPTION => if let Some((q, _)) = question.as_mut() {
+                if let Some(o) = parse_option(op) {
+                    q.options.push(o);
+                }
+            },
+
+            // Standard default for the current question
+            IFR_DEFAULT => if let Some((q, _)) = question.as_mut() {
+                if read_u16(op, 2) == Some(0) {
+                    if let Some(value) = read_value(op, 5, *op.get(4)?) {
+                        q.default = Some(value);
+                    }
+                }
+            },
+
+            // End of scope
+            IFR_END => {
+                depth = depth.saturating_sub(1);
+
+                // Close the question if this was its scope
+                if matches!(question, Some((_, d)) if d == depth) {
+                    form_set.questions.push(question.take().unwrap().0);
+                }
+
+            },
+
+            // Everything else is irrelevant
+            _ => {}
+
+        }
+
+        // Open a new scope if flagged
+        if scope {
+            depth += 1;
+        }
+
+        // Move on to the next operation
+        position += op_length;
+
+    }
+
+    // Close any question left open
+    if let Some((q, _)) = question.take() {
+        form_set.questions.push(q);
+    }
+
+    // Return the result
+    Some(form_set)
+
+}
+
+// Attempts to parse a selection option
+fn parse_option(op: &[u8]) -> Option<QuestionOption> {
+
+    // Option string, flags and value
+    let text = read_u16(op, 2)?;
+    let flags = *op.get(4)?;
+    let value = read_value(op, 6, *op.get(5)?)?;
+
+    Some(QuestionOption { default: flags & IFR_OPTION_DEFAULT != 0, text, value })
+
+}
+
+// Attempts to parse a question
+fn parse_question(op: &[u8], opcode: u8, form: u16) -> Option<Question> {
+
+    // Common question header
+    let prompt = read_u16(op, 2)?;
+    let var_store = read_u16(op, 8)?;
+    let offset = read_u16(op, 10)?;
+    let flags = *op.get(13)?;
+
+    // Type-specific data
+    let (kind, size, default) = if opcode == IFR_CHECKBOX {
+
+        // A switch is always a single byte
+        (QuestionKind::CheckBox, 1,
+            Some(u64::from(flags & IFR_CHECKBOX_DEFAULT != 0)))
+
+    } else {
+
+        // Value size is encoded in the flags
+        let size = 1 << (flags & IFR_NUMERIC_SIZE);
+
+        if opcode == IFR_NUMERIC {
+
+            // Range and step follow in the given size
+            (QuestionKind::Numeric {
+                min: read_uint(op, 14, size)?,
+                max: read_uint(op, 14 + size, size)?,
+                step: read_uint(op, 14 + 2 * size, size)? }, size, None)
+
+        } else {
+
+            // Options follow as separate operations
+            (QuestionKind::OneOf, size, None)
+
+        }
+
+    };
+
+    Some(Question { default, form, kind, offset, options: Vec::new(),
+        prompt, size, var_store })
+
+}
+
+// Attempts to parse a string package at the given position
+fn parse_string_package(data: &[u8], at: usize) -> Option<StringPackage> {
+
+    // Check the package header
+    let length = read_u24(data, at)?;
+    if data[at + 3] != HII_PACKAGE_STRINGS
+        || length <= HII_STRINGS_LANGUAGE
+        || at + length > data.len() {
+        return None;
+    }
+
+    // Header size and string information offset must agree
+    let header_size = read_u32(data, at + 4)? as usize;
+    let info_offset = read_u32(data, at + 8)? as usize;
+    if header_size != info_offset
+        || header_size <= HII_STRINGS_LANGUAGE + 1
+        || header_size >= length {
+        return None;
+    }
+
+    // Language must be a terminated ASCII code
+    let language = &data[at + HII_STRINGS_LANGUAGE .. at + header_size];
+    let language = &language[.. language.iter().position(|&b| b == 0)?];
+    if language.is_empty() || !language.iter()
+        .all(|&b| b.is_ascii_alphanumeric() || b == b'-' || b == b';') {
+        return None;
+    }
+
+    // Parse the string information blocks
+    let strings = parse_string_blocks(&data[at + info_offset .. at + length])?;
+
+    Some(StringPackage { language: language.iter().map(|&b| b as char).collect(),
+        length, offset: at, strings })
+
+}
+
+// Attempts to parse string information blocks
+fn parse_string_blocks(blocks: &[u8]) -> Option<Vec<String>> {
+
+    // Identifiers start at 1
+    let mut strings = Vec::from([String::new()]);
+    let mut at = 0;
+
+    loop {
+
+        // Block type
+        let block = *blocks.get(at)?;
+
+        match block {
+
+            // Done
+            SIBT_END => return Some(strings),
+
+            // Single strings
+            SIBT_STRING_SCSU | SIBT_STRING_SCSU_FONT => {
+                at += 1 + usize::from(block == SIBT_STRING_SCSU_FONT);
+                let (string, length) = read_scsu(blocks, at)?;
+                strings.push(string);
+                at += length;
+            }
+            SIBT_STRING_UCS2 | SIBT_STRING_UCS2_FONT => {
+                at += 1 + usize::from(block == SIBT_STRING_UCS2_FONT);
+                let (string, length) = read_ucs2(blocks, at)?;
+                strings.push(string);
+                at += length;
+            }
+
+            // Multiple strings
+            SIBT_STRINGS_SCSU | SIBT_STRINGS_SCSU_FONT
+                | SIBT_STRINGS_UCS2 | SIBT_STRINGS_UCS2_FONT => {
+                at += 1 + usize::from(block == SIBT_STRINGS_SCSU_FONT
+                    || block == SIBT_STRINGS_UCS2_FONT);
+                let count = read_u16(blocks, at)?;
+                at += 2;
+                for _ in 0 .. count {
+                    let (string, length) = if block <= SIBT_STRINGS_SCSU_FONT {
+                        read_scsu(blocks, at)?
+                    } else {
+                        read_ucs2(blocks, at)?
+                    };
+                    strings.push(string);
+                    at += length;
+                }
+            }
+
+            // Duplicate of an earlier string
+            SIBT_DUPLICATE => {
+                let id = read_u16(blocks, at + 1)? as usize;
+                strings.push(strings.get(id).cloned().unwrap_or_default());
+                at += 3;
+            }
+
+            // Skipped identifiers
+            SIBT_SKIP1 | SIBT_SKIP2 => {
+                let count = if block == SIBT_SKIP1 {
+                    *blocks.get(at + 1)? as usize
+                } else {
+                    read_u16(blocks, at + 1)? as usize
+                };
+                strings.resize(strings.len() + count, String::new());
+                at += if block == SIBT_SKIP1 { 2 } else { 3 };
+            }
+
+            // Extended blocks carry no strings
+            SIBT_EXT1 => at += *blocks.get(at + 2)? as usize,
+            SIBT_EXT2 => at += read_u16(blocks, at + 2)? as usize,
+            SIBT_EXT4 => at += read_u32(blocks, at + 2)? as usize,
+
+            // Unknown block type
+            _ => return None,
+
+        }
+
+    }
+
+}
+
+// Attempts to parse a variable store, given
+// the positions of its identifier, size and name
+fn parse_var_store(op: &[u8], id: usize, size: usize, name: usize) -> Option<VarStore> {
+
+    // Name is a terminated ASCII string
+    let name = op.get(name ..)?;
+    let name = &name[.. name.iter().position(|&b| b == 0)?];
+
+    Some(VarStore { guid: read_guid(op, 2)?, id: read_u16(op, id)?,
+        name: name.iter().map(|&b| b as char).collect(), size: read_u16(op, size)? })
+
+}
+
+// Byte Readers
+
+// Reads a GUID
+fn read_guid(data: &[u8], at: usize) -> Option<[u8; 16]> {
+    data.get(at .. at + 16)?.try_into().ok()
+}
+
+// Reads a terminated SCSU string, returns it with its length in bytes
+fn read_scsu(data: &[u8], at: usize) -> Option<(String, usize)> {
+    let string = data.get(at ..)?;
+    let length = string.iter().position(|&b| b == 0)?;
+    Some((string[.. length].iter().map(|&b| b as char).collect(), length + 1))
+}
+
+// Reads a terminated UCS-2 string, returns it with its length in bytes
+fn read_ucs2(data: &[u8], at: usize) -> Option<(String, usize)> {
+    let units = data.get(at ..)?.chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&c| c != 0).collect::<Vec<_>>();
+    if at + units.len() * 2 + 2 > data.len() {
+        return None;
+    }
+    Some((char::decode_utf16(units.iter().copied())
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect(),
+        units.len() * 2 + 2))
+}
+
+// Reads a little-endian 16-bit integer
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at .. at + 2)?.try_into().ok()?))
+}
+
+// Reads a little-endian 24-bit integer
+fn read_u24(data: &[u8], at: usize) -> Option<usize> {
+    read_uint(data, at, 3).map(|n| n as usize)
+}
+
+// Reads a little-endian 32-bit integer
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at .. at + 4)?.try_into().ok()?))
+}
+
+// Reads a little-endian integer of the given size
+fn read_uint(data: &[u8], at: usize, size: usize) -> Option<u64> {
+    Some(data.get(at .. at + size)?.iter().rev()
+        .fold(0u64, |acc, &b| acc << 8 | b as u64))
+}
+
+// Reads an IFR value of the given type
+fn read_value(data: &[u8], at: usize, value_type: u8) -> Option<u64> {
+    match value_type {
+        0 ..= 3 => read_uint(data, at, 1 << value_type),  // 8, 16, 32 and 64-bit
+        4 => read_uint(data, at, 1),                      // Boolean
+        _ => None,                                        // Not a number
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::guid::parse_guid;
+
+    // Constructed image with a form package, preceded and followed by filler,
+    // and string packages in two languages, see fixtures/README.md
+    const IMAGE: &[u8] = include_bytes!("../fixtures/ifr/setup.bin");
+
+    // Locates the form set, its variable stores and all questions
+    #[test]
+    fn form_packages() {
+
+        let form_sets = find_form_packages(IMAGE);
+        assert_eq!(form_sets.len(), 1);
+
+        // Form set, with the title
+        let form_set = &form_sets[0];
+        assert_eq!(form_set.offset, 0x10);
+        assert_eq!(Some(form_set.guid), parse_guid("7B59104A-C00D-4158-87FF-F04D6396A915"));
+        assert_eq!(form_set.title, 1);
+
+        // Variable stores of both kinds, sharing the name
+        let var_stores = form_set.var_stores.iter()
+            .map(|v| (v.name.as_str(), v.id, v.size, v.guid)).collect::<Vec<_>>();
+        assert_eq!(var_stores, [
+            ("Setup", 1, 0x400, parse_guid("EC87D643-EBA4-4BB5-A1E5-3F3E36B20DA9").unwrap()),
+            ("Setup", 2, 0x20, parse_guid("A04A27F4-DF00-4D42-B552-39511302113D").unwrap())]);
+
+        // Questions, in order, with the form they are on
+        let questions = form_set.questions.iter()
+            .map(|q| (q.prompt, q.form, q.var_store, q.offset, q.size, q.default)).collect::<Vec<_>>();
+        assert_eq!(questions, [
+            (4, 2, 1, 0x40, 1, Some(1)), (5, 2, 1, 0x41, 1, None),
+            (8, 3, 1, 0x1A4, 2, Some(0x28)), (8, 3, 2, 0x10, 1, None), (9, 3, 3, 0x00, 1, Some(0))]);
+
+        // Question types, the options and the range
+        assert!(matches!(form_set.questions[0].kind, QuestionKind::CheckBox));
+        assert!(matches!(form_set.questions[1].kind, QuestionKind::OneOf));
+        let options = form_set.questions[1].options.iter()
+            .map(|o| (o.text, o.value, o.default)).collect::<Vec<_>>();
+        assert_eq!(options, [(6, 0, false), (7, 1, true)]);
+        assert!(matches!(form_set.questions[3].kind, QuestionKind::Numeric { min: 0, max: 0x64, step: 5 }));
+
+    }
+
+    // Locates the string packages, and picks the one in the requested language
+    #[test]
+    fn string_packages() {
+
+        let packages = find_string_packages(IMAGE);
+        let languages = packages.iter().map(|p| p.language.as_str()).collect::<Vec<_>>();
+        assert_eq!(languages, ["en-US", "de-DE"]);
+
+        // Strings are indexed from 1
+        assert_eq!(packages[0].get(0), "");
+        assert_eq!(packages[0].get(4), "Quiet Boot");
+        assert_eq!(packages[1].get(8), "Lüfterdrehzahl");
+        assert_eq!(packages[0].get(100), "");
+
+        // Language is matched regardless of the case
+        let form_set = &find_form_packages(IMAGE)[0];
+        assert_eq!(find_strings_for(form_set, &packages, "DE-de").map(|p| p.offset), Some(packages[1].offset));
+        assert!(find_strings_for(form_set, &packages, "fr-FR").is_none());
+
+    }
+
+    // Formats the definitions with the vendor GUIDs, names kept unique
+    // across calls, and questions stored in unknown variables left out
+    #[test]
+    fn definitions() {
+
+        let form_set = &find_form_packages(IMAGE)[0];
+        let strings = &find_string_packages(IMAGE)[0];
+        let mut names = BTreeSet::from(["QuietBoot".to_owned()]);
+
+        let lines = format_definitions(form_set, strings, &mut names);
+        assert_eq!(lines, [
+            "QuietBoot_2, Setup{EC87D643-EBA4-4BB5-A1E5-3F3E36B20DA9}:0x0040  # Main: Quiet Boot [0x00 / 0x01*]",
+            "BootMode, Setup{EC87D643-EBA4-4BB5-A1E5-3F3E36B20DA9}:0x0041     # Main: Boot Mode [0x00: Legacy / 0x01: UEFI*]",
+            "FanSpeed, Setup{EC87D643-EBA4-4BB5-A1E5-3F3E36B20DA9}:0x01a4(2)  # Advanced: Fan Speed [0x0010-0x0050 / 0x0028*]",
+            "FanSpeed_2, Setup{A04A27F4-DF00-4D42-B552-39511302113D}:0x0010   # Advanced: Fan Speed [0x00-0x64 / 5]"]);
+        assert!(names.contains("FanSpeed_2"));
+
+    }
+
+}
@@ -14,24 +14,47 @@
 extern crate alloc;
 
 // Modules
+mod boot;      // Decodes and encodes load options (Boot####, Driver####, SysPrep####)
 mod config;    // Stores configurable parameters together for easy adjustment
 mod data;      // Defines data types and structures used throughout the application
+mod editor;    // Provides a full-screen hex editor for UEFI variables
 mod error;     // Allows for error handling in a single centralized manner
 mod firmware;  // Performs UEFI operations such as querying and setting UEFI variables
+mod guid;      // Builds and formats GUIDs kept as bytes in memory order
+mod hii;       // Builds and parses HII configuration strings (ConfigRequest and ConfigResp)
+#[allow(dead_code)]
+mod ifr;       // Extracts IFR setup questions from HII packages
 mod parse;     // Processes command-line and stream (standard) input into data structures
+mod protocol;  // Declares UEFI protocols not provided by the uefi crate
+mod script;    // Parses the parts of the input that do not depend on the firmware, shared with the host
+mod siglist;   // Decodes the Secure Boot signature databases (EFI_SIGNATURE_LIST)
+mod smbios;    // Reads the board identification from the SMBIOS tables
 mod string;    // Provides string manipulation routines, including an extension to CStr16
 
 // Declare fully-qualified symbols
 // to be used in the local scope
-use uefi::prelude::*;
-use uefi_services::println;
+use alloc::{borrow::ToOwned, format, string::{String, ToString}, vec, vec::Vec};
+use uefi::{CStr16, CString16, prelude::*};
+use uefi_services::{print, println};
 
 // Symbols from other modules
+use boot::{LoadOption, LoadOptionId, LoadOptionType};
 use config::locale as msg;
-use data::Args;
+use data::{Args, ArgOption, BootCommand, Condition, ConfigResponse, InputEntry, InputScope,
+    InteractiveCommand, JsonOutput, Output, OutputFormat, ScriptStep};
 use error::AppError;
-use firmware::{exit, get_image_name, get_value, load_options, read_stream, restart_system, set_value};
-use parse::{parse_args, parse_input};
+use firmware::{delete_global_variable, device_path_to_text, exit,
+    get_config_header_for, get_global_variable, get_image_name, get_load_options,
+    get_namesake_variables, get_signature_database, get_smbios_info, get_value, get_variable_id,
+    get_whole_variable, has_variable, load_options, read_line, read_script_file, read_stream,
+    restart_system, set_global_variable, set_signature_database, set_value,
+    text_to_device_path, write_file};
+use hii::ConfigString;
+use siglist::AuthenticatedUpdate;
+use parse::{is_config_input, parse_args, parse_config_input, parse_input, parse_input_choice,
+    parse_input_line};
+use script::SystemInfo;
+use string::{CStr16Ext, decode_script, encode_text, format_hex_dump};
 
 #[entry] // Main entry point to the application
 fn main(_handle: Handle, mut system_table: SystemTable<Boot>) -> Status {
@@ -40,42 +63,86 @@ fn main(_handle: Handle, mut system_table: SystemTable<Boot>) -> Status {
     uefi_services::init(&mut system_table)
         .expect(msg::ERR_UEFI_INIT);
 
-    // Print name and version header, including
-    // firmware vendor and version, and UEFI revision
-    println!("# {} ({}) {} {}-{} @ {} {}.{:02} UEFI {}", 
+    // Firmware vendor and version, UEFI revision, and the board
+    // identification, also checked by the conditions in the input
+    let system = SystemInfo {
+        firmware_revision: format!("{}.{:02}",
+            system_table.firmware_revision() >> 16, system_table.firmware_revision() & 0xFFFFu32),
+        firmware_vendor: system_table.firmware_vendor().to_string(),
+        smbios: get_smbios_info(&system_table),
+        uefi_revision: system_table.uefi_revision().to_string() };
+
+    // Name and version header, including the above
+    let header = format!("# {} ({}) {} {}-{} @ {system}",
         config::APP_TITLE.unwrap_or_else(|| msg::APP_TITLE), config::APP_NAME.unwrap_or_else(|| msg::APP_NAME),
         msg::VERSION, config::APP_VERSION.unwrap_or_else(|| msg::VERSION_UNKNOWN), 
-        config::BUILD_TYPE.unwrap_or_else(|| msg::BUILD_TYPE), system_table.firmware_vendor(),
-        system_table.firmware_revision() >> 16, system_table.firmware_revision() & 0xFFFFu32,
-        system_table.uefi_revision());
+        config::BUILD_TYPE.unwrap_or_else(|| msg::BUILD_TYPE));
+
+    // Attempt to load and parse command-line arguments
+    let args = parse_args(load_options(&system_table).unwrap());
+
+    // Print the header, unless the output will be machine-readable,
+    // in which case the information is a part of the document instead
+    if !matches!(&args, Ok(args) if args.format == OutputFormat::Json) {
+        println!("{header}");
+    }
 
     // Set the default exit status
     let mut status = Status::SUCCESS;
 
-    // Attempt to load and parse command-line arguments,
-    // determine subsequent actions based on the outcome
-    let args = match parse_args(load_options(&system_table).unwrap()) {
+    // Determine subsequent actions based on the outcome
+    let args = match args {
 
         // Parse success
-        // Continue with arguments
-        Ok(args) => args,
+        // Continue with arguments,
+        // and the input files, if any
+        Ok(mut args) => match read_input_files(&system_table, &mut args, &system) {
+
+            // Success
+            Ok(()) => args,
+
+            // Failure
+            Err(e) => {
+
+                // Show input file error
+                println!("{}: {e}", msg::ERR_PREFIX_INPUT);
+
+                // Exit after showing usage information
+                status = Status::INVALID_PARAMETER;
+                Args { usage: true, ..Default::default() }
+
+            }
+
+        },
 
         // Empty argument list
         Err(AppError::ArgNone) => {
 
-            // Parse the data from standard input
-            match parse_input(read_stream(system_table.stdin())) {
+            // Read the data from standard input
+            let input = read_stream(system_table.stdin());
+
+            // Parse it either as HII configuration strings,
+            // resolving the variables, or as the usual script
+            let args = if is_config_input(&input) {
+                parse_config_input(&input,
+                    |name, vendor| get_variable_id(&system_table, name, vendor))
+            } else {
+                parse_input(input, None, &mut [], &system,
+                    &mut |path| read_script(&system_table, path))
+            };
+
+            // Determine subsequent actions based on the outcome
+            match args {
 
                 // If success, use
                 // data as arguments
                 Ok(args) => args,
 
-                // Nothing to do
+                // Nothing to do, so ask what to do
                 Err(AppError::InputNone) => {
 
-                    // Show message
-                    println!("{}", AppError::InputNone);
-                    return Status::SUCCESS;
+                    // Enter lines one by one at the prompt
+                    return run_interactive(&mut system_table);
 
                 }
 
@@ -117,28 +184,67 @@ fn main(_handle: Handle, mut system_table: SystemTable<Boot>) -> Status {
 
     }
 
-    // Iterate through operations
-    for op in args.op {
+    // Values to output as HII configuration, if requested
+    let mut responses = Vec::new();
+
+    // Results to output, also kept for the file if requested, in which case
+    // the header is repeated at its start, unless the output is machine-readable
+    let json = args.format == OutputFormat::Json;
+    let mut output = Output {
+        file: args.output.as_ref().map(|_| if json { Vec::new() } else { vec![header] }),
+        json: json.then(|| JsonOutput { simulate: args.simulate, system: system.clone(), ..Default::default() }),
+        quiet: args.quiet };
+
+    // Run the operations, including those in the blocks
+    // whose conditions hold when reached
+    let status = run_steps(&system_table, &args.op, &args, &system, &mut responses, &mut output);
+
+    // If an operation failed
+    if status != Status::SUCCESS {
+
+        // Interrupt, keeping the results so far
+        return write_output(&system_table, &args, &mut output, status);
+
+    }
+
+    // Output the collected HII configuration
+    for response in responses {
+        output.line(response.config.to_string());
+    }
+
+    // Iterate through boot entry commands
+    for command in &args.boot {
 
-        // Process each operation and retain its status
-        let status = process_op(&system_table,
-            &op, args.force, args.simulate);
+        // Process each command and retain its status
+        let status = process_boot(&system_table, command, args.simulate, &mut output);
 
-        // If an operation failed
+        // If a command failed
         if status != Status::SUCCESS {
 
-            // Interrupt
-            return status;
+            // Interrupt, keeping the results so far
+            return write_output(&system_table, &args, &mut output, status);
 
         }
 
     }
 
+    // Write the results to the file before a possible restart
+    let status = write_output(&system_table, &args, &mut output, Status::SUCCESS);
+    if status != Status::SUCCESS {
+        return status;
+    }
+
     // If restart requested
-    if args.restart {
+    if let Some(restart_type) = args.restart {
 
         // Restart the system upon succesful completion
-        restart_system(&system_table);
+        if let Err(e) = restart_system(&system_table, restart_type, args.simulate) {
+
+            // Show error message if could not
+            println!("{}: {e}", msg::ERR_PREFIX_RESTART);
+            return Status::ABORTED;
+
+        }
 
     }
 
@@ -147,77 +253,512 @@ fn main(_handle: Handle, mut system_table: SystemTable<Boot>) -> Status {
 
 }
 
-// Process an argument operation, returning its status
-fn process_op(system_table: &SystemTable<Boot>,
-    op: &data::ArgOperation, force: bool, simulate: bool) -> Status {
+// Adds a value to the HII configuration to be output,
+// grouping together the values for the same variable
+fn add_config_response(system_table: &SystemTable<Boot>,
+    responses: &mut Vec<ConfigResponse>, op: &data::ArgOperation,
+    value: &data::UefiValue) -> Result<(), AppError> {
 
-    // Variable name, offset and size
+    // Variable name and identifier
     let name = &op.target.name;
-    let size = op.target.size;
-    let offset = op.target.offset;
+    let id = op.target.id;
+
+    // Find the response for the variable, or add a new one
+    let response = match responses.iter().position(|r| r.name == *name && r.id == id) {
+        Some(i) => &mut responses[i],
+        None => {
+            let header = get_config_header_for(system_table, name, id)?;
+            responses.push(ConfigResponse { id, name: name.clone(),
+                config: ConfigString { blocks: Vec::new(), header } });
+            responses.last_mut().unwrap()
+        }
+    };
 
-    // Operation type
-    match op.action {
+    // Add the value
+    response.config.blocks.push(op.to_config_block(value));
 
-        // Get current value
-        data::OperationType::Get => {
+    // Return no error
+    Ok(())
 
-            // Perform retrieval
-            match get_value(&system_table,
-                name, op.target.id, offset, size) {
+}
 
-                // Success
-                Ok(value) =>
+// Reads and parses the input files given as arguments, in order,
+// putting their operations and boot entry commands ahead of those
+// given directly, with options applying to all of them, and the
+// parameters given overriding those declared in the files, and
+// the conditions in them checked against the system information
+fn read_input_files(system_table: &SystemTable<Boot>, args: &mut Args,
+    system: &SystemInfo) -> Result<(), AppError> {
+
+    // Set up the operations and commands from the files
+    let mut op = Vec::new();
+    let mut boot = Vec::new();
+
+    // Go through the files
+    for path in &args.input {
+
+        // Read and decode the file
+        let input = read_script(system_table, path)?;
+
+        // Parse it the same way as the standard input, including other files
+        // relative to it, an empty file simply having nothing to add
+        let mut input = match parse_input(input, Some(path), &mut args.params, system,
+            &mut |path| read_script(system_table, path)) {
+            Ok(input) => input,
+            Err(AppError::InputNone) => continue,
+            Err(e) => Err(e)?,
+        };
+
+        // Add the contents
+        op.append(&mut input.op);
+        boot.append(&mut input.boot);
+        args.config |= input.config;
+        args.force |= input.force;
+        args.hii |= input.hii;
+        args.restart = args.restart.or(input.restart);
+        args.simulate |= input.simulate;
 
-                    // Output the formatted value
-                    println!("{}", op.to_string_with_val(&value)),
+    }
 
-                // Failure
-                Err(e) => {
+    // Stop if a parameter is not declared in any of the files,
+    // which is most likely a typo, or the wrong script
+    if let Some(param) = args.params.iter().find(|param| !param.used) {
+        return Err(AppError::ArgParam(param.name.clone()));
+    }
+
+    // Put the contents ahead of the arguments
+    op.append(&mut args.op);
+    boot.append(&mut args.boot);
+    args.op = op;
+    args.boot = boot;
+
+    // Return no error
+    Ok(())
+
+}
+
+// Reads and decodes a script file, either given as input, or included by another
+fn read_script(system_table: &SystemTable<Boot>, path: &CStr16) -> Result<CString16, AppError> {
+    let bytes = read_script_file(system_table, path)?;
+    decode_script(&bytes).ok_or_else(|| AppError::FileText(path.to_string()))
+}
+
+// Process a boot entry command, returning its status
+fn process_boot(system_table: &SystemTable<Boot>,
+    command: &BootCommand, simulate: bool, output: &mut Output) -> Status {
+
+    // Perform the command
+    match run_boot_command(system_table, command, simulate, output) {
+
+        // Success
+        Ok(()) => Status::SUCCESS,
+
+        // Failure
+        Err(e) => {
+
+            // Show error message and interrupt processing
+            output.error(msg::ERR_PREFIX_BOOT, &e);
+            Status::ABORTED
+
+        }
+
+    }
+
+}
+
+// Performs a boot entry command, outputting the outcome
+// as comments, so that the output can still be used as input
+fn run_boot_command(system_table: &SystemTable<Boot>,
+    command: &BootCommand, simulate: bool, output: &mut Output) -> Result<(), AppError> {
+
+    // Retrieves an existing load option, with error handling
+    let get_load_option = |id: &LoadOptionId| {
+        let bytes = get_global_variable(system_table, &id.to_string())?
+            .ok_or_else(|| AppError::UefiVarGetNone(id.to_string()))?;
+        LoadOption::from_bytes(&bytes)
+            .ok_or_else(|| AppError::BootOption(id.to_string()))
+    };
 
-                    // Show error message and interrupt processing
-                    println!("{}: {e}", msg::ERR_PREFIX_OP_GET);
-                    return Status::ABORTED;
+    // Retrieves an order variable, empty if absent
+    let get_order = |kind: LoadOptionType| {
+        boot::parse_order(&get_global_variable(system_table, &kind.order_name())?.unwrap_or_default())
+            .ok_or_else(|| AppError::BootOrder(kind.order_name()))
+    };
+
+    // Command type
+    match command {
+
+        // List load options and the order
+        BootCommand::List => {
+
+            // Boot manager state
+            let current = get_global_variable(system_table, config::VAR_BOOT_CURRENT)?;
+            let next = get_global_variable(system_table, config::VAR_BOOT_NEXT)?;
+            for (name, value) in [(config::VAR_BOOT_CURRENT, current), (config::VAR_BOOT_NEXT, next)] {
+                output.line(format!("# {name}: {}", match value {
+                    Some(value) => boot::format_order(&boot::parse_order(&value)
+                        .ok_or_else(|| AppError::BootOrder(name.to_string()))?),
+                    None => msg::BOOT_NONE.to_string() }));
+            }
 
+            // Order of each type, if present
+            for kind in LoadOptionType::ALL {
+                let order = get_order(kind)?;
+                if kind == LoadOptionType::Boot || !order.is_empty() {
+                    output.line(format!("# {}: {}", kind.order_name(), boot::format_order(&order)));
                 }
+            }
 
+            // Load options: active mark, description, device path and optional data
+            for id in get_load_options(system_table)? {
+                let option = get_load_option(&id)?;
+                let path = device_path_to_text(system_table, &option.file_path)
+                    .map(|path| path.to_string())
+                    .unwrap_or_else(|_| msg::BOOT_PATH_UNKNOWN.to_string());
+                output.line(format!("# {id}{} \"{}\" {path}{}",
+                    if option.is_active() { "*" } else { " " }, option.description,
+                    if option.optional_data.is_empty() {
+                        "".to_string()
+                    } else {
+                        format!(" +{} {}", option.optional_data.len(), msg::BOOT_DATA)
+                    }));
             }
 
         }
 
-        // Set new value
-        data::OperationType::Set(value) => {
+        // Add a load option, and append it to the order
+        BootCommand::Create { id, description, path } => {
 
-            // Initialize the new value
-            let value = data::UefiValue::from_usize(value, size);
+            // Do not overwrite an existing one
+            if get_global_variable(system_table, &id.to_string())?.is_some() {
+                return Err(AppError::BootExists(id.to_string()));
+            }
 
-            // Perform the assignment
-            match set_value(&system_table,
-                name, op.target.id, offset, size, &value, force, simulate) {
+            // Set up the load option, active from the start
+            let option = LoadOption {
+                attributes: boot::LOAD_OPTION_ACTIVE,
+                description: description.clone(),
+                file_path: text_to_device_path(system_table, path)?,
+                optional_data: Vec::new() };
+            set_global_variable(system_table, &id.to_string(), &option.to_bytes(), simulate)?;
+
+            // Append it to the order, unless already there
+            let mut order = get_order(id.kind)?;
+            if !order.contains(&id.number) {
+                order.push(id.number);
+                set_global_variable(system_table, &id.kind.order_name(),
+                    &boot::order_to_bytes(&order), simulate)?;
+            }
+            output.line(format!("# {id}: {}", msg::BOOT_CREATED));
+
+        }
+
+        // Remove a load option, also from the order
+        BootCommand::Delete(id) => {
 
-                // Success
-                Ok(written) => {
+            // Make sure it exists
+            get_load_option(id)?;
+            delete_global_variable(system_table, &id.to_string(), simulate)?;
 
-                    // Output the formatted value,
-                    // adding a comment if no writing occurred
-                    println!("{}{}", op.to_string_with_val(&value),
-                        if let false = written {
-                            msg::OP_SKIPPED
-                        } else {
-                            ""
-                        });
+            // Remove it from the order, if there
+            let mut order = get_order(id.kind)?;
+            if order.contains(&id.number) {
+                order.retain(|&n| n != id.number);
+                set_global_variable(system_table, &id.kind.order_name(),
+                    &boot::order_to_bytes(&order), simulate)?;
+            }
+            output.line(format!("# {id}: {}", msg::BOOT_DELETED));
+
+        }
+
+        // Clear or set the active attribute
+        BootCommand::Disable(id) | BootCommand::Enable(id) => {
+            let enable = matches!(command, BootCommand::Enable(_));
+            let mut option = get_load_option(id)?;
+            if enable {
+                option.attributes |= boot::LOAD_OPTION_ACTIVE;
+            } else {
+                option.attributes &= !boot::LOAD_OPTION_ACTIVE;
+            }
+            set_global_variable(system_table, &id.to_string(), &option.to_bytes(), simulate)?;
+            output.line(format!("# {id}: {}", if enable { msg::BOOT_ENABLED } else { msg::BOOT_DISABLED }));
+        }
+
+        // Boot once from a given entry
+        BootCommand::Next(number) => {
+
+            // Make sure it exists
+            get_load_option(&LoadOptionId { kind: LoadOptionType::Boot, number: *number })?;
+            set_global_variable(system_table, config::VAR_BOOT_NEXT,
+                &boot::order_to_bytes(&[*number]), simulate)?;
+            output.line(format!("# {}: {}", config::VAR_BOOT_NEXT, boot::format_order(&[*number])));
 
+        }
+
+        // Move the given entries to the front of the order,
+        // the remaining ones follow in their existing order
+        BootCommand::Order(numbers) => {
+
+            // Make sure they all exist
+            for &number in numbers {
+                get_load_option(&LoadOptionId { kind: LoadOptionType::Boot, number })?;
+            }
+
+            // Reorder, skipping any repeated entries
+            let mut order = Vec::new();
+            for number in numbers.iter().copied()
+                .chain(get_order(LoadOptionType::Boot)?) {
+                if !order.contains(&number) {
+                    order.push(number);
                 }
+            }
+            set_global_variable(system_table, &LoadOptionType::Boot.order_name(),
+                &boot::order_to_bytes(&order), simulate)?;
+            output.line(format!("# {}: {}", LoadOptionType::Boot.order_name(), boot::format_order(&order)));
 
-                // Failure
-                Err(e) => {
+        }
+
+        // Open a variable in the editor
+        BootCommand::Edit { name, id } => editor::run_editor(system_table, name, *id, simulate)?,
 
-                    // Show error message and interrupt processing
-                    println!("{}: {e}", msg::ERR_PREFIX_OP_SET);
-                    return Status::ABORTED;
+        // Show Secure Boot state and signature databases
+        BootCommand::SecureBoot => {
+
+            // State, each variable holding a single boolean byte
+            for name in config::VAR_SECURE_BOOT_STATE {
+                output.line(format!("# {name}: {}", match get_global_variable(system_table, name)?
+                    .and_then(|value| value.first().copied()) {
+                    Some(0) => msg::SECURE_BOOT_NO,
+                    Some(_) => msg::SECURE_BOOT_YES,
+                    None => msg::BOOT_NONE }));
+            }
 
+            // Signature databases, decoded the same way as on a host
+            for name in config::VAR_SIGLISTS {
+                match get_signature_database(system_table, name)? {
+                    Some(content) => for line in siglist::describe_signature_database(name, &content) {
+                        output.line(format!("# {line}"));
+                    },
+                    None => output.line(format!("# {name}: {}", msg::BOOT_NONE)),
                 }
+            }
+
+        }
+
+        // Write or append a signed update to a signature database
+        BootCommand::SecureBootWrite { name, path, append } => {
 
+            // Read the update, from where the scripts are read,
+            // and make sure it is signed
+            let update = read_script_file(system_table, path)?;
+            let authenticated = AuthenticatedUpdate::parse(&update)
+                .ok_or_else(|| AppError::BootAuth(path.to_string()))?;
+
+            // Show what is about to be written
+            output.line(format!("# {name}: {} {}, {} {}", msg::SECURE_BOOT_SIGNED[0],
+                authenticated.timestamp, authenticated.signature.len(), msg::SECURE_BOOT_SIGNED[1]));
+            for line in siglist::describe_signature_database(name, authenticated.content) {
+                output.line(format!("# {line}"));
+            }
+
+            // Pass the update as it is, the firmware verifies the signature
+            set_signature_database(system_table, name, &update, *append, simulate)?;
+            output.line(format!("# {name}: {}", if *append { msg::SECURE_BOOT_APPENDED } else { msg::SECURE_BOOT_WRITTEN }));
+
+        }
+
+    }
+
+    // Return no error
+    Ok(())
+
+}
+
+// Runs an interactive session, reading and running one line at a time,
+// keeping the definitions and the options until the session ends
+fn run_interactive(system_table: &mut SystemTable<Boot>) -> Status {
+
+    // Disable the watchdog timer, which would otherwise restart
+    // the system after five minutes if launched from the boot manager
+    // Note: the shell already does that, so failure does not matter
+    let _ = system_table.boot_services()
+        .set_watchdog_timer(0, config::INTERACTIVE_WATCHDOG_CODE, None);
+
+    // Set up the session state
+    let mut choices: Vec<(CString16, usize)> = Vec::new();
+    let mut history: Vec<CString16> = Vec::new();
+    let mut options = Args::default();
+    let mut output = Output::default();
+    let mut scope = InputScope::default();
+
+    // Show how to get started
+    println!("# {}", msg::INTERACTIVE_HEADER);
+
+    // Read and run lines until asked to stop
+    loop {
+
+        // Show the prompt and read the line
+        print!("{}", config::INTERACTIVE_PROMPT);
+        let line = read_line(system_table, &history);
+
+        // Keep the line in the history, unless blank or the same as the last one
+        if !line.trim().is_empty() && history.last() != Some(&line) {
+            if history.len() == config::INTERACTIVE_HISTORY_MAX {
+                history.remove(0);
+            }
+            history.push(line.clone());
+        }
+
+        // Parse the line, skipping it if blank
+        let command = match parse_input_line(&line, &scope) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+
+            // Show input parser error, and carry on
+            Err(e) => {
+                println!("{}: {e}", msg::ERR_PREFIX_INPUT);
+                continue;
+            }
+
+        };
+
+        // Command type
+        match command {
+
+            // Show all contents of a variable, as comments
+            InteractiveCommand::Dump { name, id } => {
+                match choose_variable(system_table, &mut choices, &name, id)
+                    .and_then(|id| get_whole_variable(system_table, &name, id)) {
+                    Ok(var) => {
+                        println!("# {name}: {} {}", var.content.len(), msg::INTERACTIVE_BYTES);
+                        for line in format_hex_dump(&var.content) {
+                            println!("# {line}");
+                        }
+                    }
+                    Err(e) => println!("{}: {e}", msg::ERR_PREFIX_OP_GET),
+                }
+            }
+
+            // Run an input entry right away
+            // Note: errors are shown, but do not end the session
+            InteractiveCommand::Entry(entry) => match entry {
+
+                // Boot entry command, asking which variable
+                // to open in the editor if there are namesakes
+                InputEntry::Boot(mut command) => {
+                    if let BootCommand::Edit { name, id } = &mut command {
+                        match choose_variable(system_table, &mut choices, name, *id) {
+                            Ok(chosen) => *id = chosen,
+                            Err(e) => {
+                                println!("{}: {e}", msg::ERR_PREFIX_BOOT);
+                                continue;
+                            }
+                        }
+                    }
+                    process_boot(system_table, &command, options.simulate, &mut output);
+                }
+
+                // Operation, including a resolved reference,
+                // asking which variable is meant if there are namesakes
+                InputEntry::Operation(mut op) => {
+                    if op.target.vendor.is_none() {
+                        match choose_variable(system_table, &mut choices, &op.target.name, op.target.id) {
+                            Ok(chosen) => op.target.id = chosen,
+                            Err(e) => {
+                                println!("{}: {e}", msg::ERR_PREFIX_OP_GET);
+                                continue;
+                            }
+                        }
+                    }
+                    let mut responses = Vec::new();
+                    process_op(system_table, &op, options.force, options.hii,
+                        options.simulate, options.config.then_some(&mut responses), &mut output);
+                    for response in responses {
+                        println!("{}", response.config);
+                    }
+                }
+
+                // Option, in effect until the session ends
+                InputEntry::Option(option) => match option {
+                    ArgOption::Config => options.config = true,
+                    ArgOption::Force => options.force = true,
+                    ArgOption::Hii => options.hii = true,
+                    ArgOption::Simulate => options.simulate = true,
+
+                    // Restart right away, rather than when done
+                    ArgOption::Restart(restart_type) => {
+                        if let Err(e) = restart_system(system_table, restart_type, options.simulate) {
+                            println!("{}: {e}", msg::ERR_PREFIX_RESTART);
+                        }
+                    }
+
+                    // Not available in the input
+                    ArgOption::Encoding(_) | ArgOption::Format(_)
+                        | ArgOption::Quiet | ArgOption::Usage => unreachable!(),
+
+                },
+
+                // Constant, replacing any previous one with the same name,
+                // a parameter being no different, with nothing to override it
+                InputEntry::Constant { name, value, .. } => {
+                    scope.consts.retain(|(const_name, _)| *const_name != name);
+                    scope.consts.push((name, value));
+                }
+
+                // Structure, replacing any previous one with the same name,
+                // the definitions made before keeping the one they are bound to
+                InputEntry::Layout(layout) => {
+                    scope.layouts.retain(|other| other.name != layout.name);
+                    scope.layouts.push(layout);
+                }
+
+                // Definition, replacing any previous one with the same name
+                InputEntry::TargetDefinition { name, target } => {
+                    scope.defs.retain(|(def_name, _)| *def_name != name);
+                    scope.defs.push((name, target));
+                }
+
+                // References are already resolved into operations
+                InputEntry::TargetReference { .. } => unreachable!(),
+
+                // Blocks are only in the input files
+                InputEntry::If(_) | InputEntry::Else | InputEntry::EndIf => unreachable!(),
+
+            },
+
+            // End the session
+            InteractiveCommand::Exit => break,
+
+            // Show the available commands
+            InteractiveCommand::Help => println!("{}", msg::INTERACTIVE_HELP),
+
+            // Show the constants, the structures and the definitions, in the input format
+            InteractiveCommand::List => {
+                if scope.consts.is_empty() && scope.layouts.is_empty() && scope.defs.is_empty() {
+                    println!("# {}", msg::INTERACTIVE_DEFS_NONE);
+                }
+                for (name, value) in &scope.consts {
+                    println!("{}{} {name}{}{value:#x}", config::CHAR_INPUT_OPT,
+                        config::OPT_INPUT_SET, config::CHAR_ARG_ASS);
+                }
+                for layout in &scope.layouts {
+                    println!("{}{} {layout}", config::CHAR_INPUT_OPT, config::OPT_INPUT_STRUCT);
+                }
+                for (name, target) in &scope.defs {
+
+                    // Names of the values, if any, in braces
+                    let values = if target.values.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" {}{}{}", config::CHAR_INPUT_ENUM_L, target.values.iter()
+                            .map(|(name, value)| format!("{name}{}{value:#x}", config::CHAR_ARG_ASS))
+                            .collect::<Vec<_>>().join(&config::CHAR_INPUT_ENUM.to_string()),
+                            config::CHAR_INPUT_ENUM_R)
+                    };
+
+                    println!("{name}{}{target}{values}", config::CHAR_INPUT_DEF);
+
+                }
             }
 
         }
@@ -229,6 +770,305 @@ fn process_op(system_table: &SystemTable<Boot>,
 
 }
 
+// Determines which of the variables sharing a name is meant when no
+// identifier is given, asking at the prompt the first time, and then
+// remembering the choice for the rest of the interactive session
+fn choose_variable(system_table: &mut SystemTable<Boot>, choices: &mut Vec<(CString16, usize)>,
+    name: &CStr16, id: Option<usize>) -> Result<Option<usize>, AppError> {
+
+    // Identifier given, or chosen before
+    if id.is_some() {
+        return Ok(id);
+    }
+    if let Some((_, id)) = choices.iter().find(|(chosen, _)| **chosen == *name) {
+        return Ok(Some(*id));
+    }
+
+    // Nothing to choose from unless there are namesakes
+    let namesakes = get_namesake_variables(system_table, name)?;
+    if namesakes.len() < 2 {
+        return Ok(None);
+    }
+
+    // Show the variables to choose from
+    println!("# {}", msg::ERR_UEFI_VAR_GET_MANY_HEAD);
+    for (id, info) in namesakes.iter().enumerate() {
+        println!("# {name}({id:#04x}) {info}");
+    }
+
+    // Keep asking until a valid identifier is entered,
+    // cancelling with the usual error if left blank
+    loop {
+        print!("# {}", msg::INTERACTIVE_CHOICE);
+        match parse_input_choice(&read_line(system_table, &[])) {
+            Ok(Some(id)) if id < namesakes.len() => {
+                println!("# {name}({id:#04x}) {} {name}", msg::INTERACTIVE_CHOSEN);
+                choices.push((name.to_owned(), id));
+                return Ok(Some(id));
+            }
+            Ok(None) => return Err(AppError::UefiVarGetMany),
+            _ => {}
+        }
+    }
+
+}
+
+// Runs the operations in order, and the blocks of them depending on whether
+// their conditions hold, checked only when reached, so that the variables
+// are as left by the operations before, returning the status of the first
+// operation to fail, or of the condition, if it cannot be checked
+fn run_steps(system_table: &SystemTable<Boot>, steps: &[ScriptStep], args: &Args,
+    system: &SystemInfo, responses: &mut Vec<ConfigResponse>, output: &mut Output) -> Status {
+
+    // Iterate through the steps
+    for step in steps {
+
+        // Process each of them and retain the status
+        let status = match step {
+
+            // Operation
+            ScriptStep::Operation(op) => process_op(system_table,
+                op, args.force, args.hii, args.simulate,
+                args.config.then_some(&mut *responses), output),
+
+            // Block, running either way
+            ScriptStep::Block { condition, then, otherwise } =>
+                match check_condition(system_table, condition, system, args.hii) {
+                    Ok(true) => run_steps(system_table, then, args, system, responses, output),
+                    Ok(false) => run_steps(system_table, otherwise, args, system, responses, output),
+                    Err(e) => {
+                        output.error(msg::ERR_PREFIX_COND, &e);
+                        Status::ABORTED
+                    }
+                },
+
+        };
+
+        // Interrupt if failed
+        if status != Status::SUCCESS {
+            return status;
+        }
+
+    }
+
+    // Return no error
+    Status::SUCCESS
+
+}
+
+// Checks whether a condition holds, looking at the variables as they are
+// now, with the values read the same way as by the operations
+fn check_condition(system_table: &SystemTable<Boot>, condition: &Condition,
+    system: &SystemInfo, hii: bool) -> Result<bool, AppError> {
+
+    condition.evaluate(system, &mut |term| match term {
+
+        // Variable is there
+        Condition::Exists { name, vendor } => CString16::try_from(name.as_str())
+            .map_err(|_| AppError::InputCond(name.clone()))
+            .and_then(|name| has_variable(system_table, &name, vendor.as_ref())),
+
+        // Value compared with a number
+        Condition::Value { target, operator, value } => resolve_target(system_table, target)
+            .and_then(|target| get_value(system_table,
+                &target.name, target.id, target.offset, target.size, hii))
+            .map(|current| operator.matches(current.to_usize().cmp(value))),
+
+        // Everything else is known without the variables
+        _ => unreachable!(),
+
+    })
+
+}
+
+// Resolves the vendor GUID of the target variable, if given, to
+// the identifier telling it apart from its namesakes, if there are any
+fn resolve_target(system_table: &SystemTable<Boot>,
+    target: &data::OperationTarget) -> Result<data::OperationTarget, AppError> {
+    match &target.vendor {
+        Some(vendor) => Ok(data::OperationTarget {
+            id: get_variable_id(system_table, &target.name, vendor)?, ..target.clone() }),
+        None => Ok(target.clone()),
+    }
+}
+
+// Process an argument operation, returning its status
+fn process_op(system_table: &SystemTable<Boot>,
+    op: &data::ArgOperation, force: bool, hii: bool, simulate: bool,
+    responses: Option<&mut Vec<ConfigResponse>>, output: &mut Output) -> Status {
+
+    // Route through HII if requested either
+    // globally or for this operation only
+    let hii = hii || op.hii;
+
+    // Resolve the vendor GUID, if given, to the variable identifier
+    let op = &match resolve_target(system_table, &op.target) {
+        Ok(target) => data::ArgOperation { target, ..op.clone() },
+        Err(e) => {
+            output.error(match op.action {
+                data::OperationType::Get => msg::ERR_PREFIX_OP_GET,
+                data::OperationType::Set(_) => msg::ERR_PREFIX_OP_SET,
+            }, &e);
+            return Status::ABORTED;
+        }
+    };
+
+    // Variable name, offset and size
+    let name = &op.target.name;
+    let size = op.target.size;
+    let offset = op.target.offset;
+
+    // If the output is machine-readable, read the whole variable first,
+    // for its vendor GUID and the value before the operation
+    let before = output.json.as_ref()
+        .and_then(|_| get_whole_variable(system_table, name, op.target.id).ok());
+
+    // Perform the operation, keeping the value read or written, and whether
+    // written, along with the prefix of the error message if it failed
+    let (prefix, mut result) = match op.action {
+
+        // Get current value
+        data::OperationType::Get => (msg::ERR_PREFIX_OP_GET,
+            get_value(system_table, name, op.target.id, offset, size, hii)
+                .map(|value| (value, None))),
+
+        // Set new value
+        data::OperationType::Set(value) => {
+
+            // Initialize the new value
+            let value = data::UefiValue::from_usize(value, size);
+
+            // Perform the assignment
+            (msg::ERR_PREFIX_OP_SET, set_value(system_table,
+                name, op.target.id, offset, size, &value, force, hii, simulate)
+                .map(|written| (value, Some(written))))
+
+        }
+
+    };
+
+    // Collect the value as HII configuration, if requested
+    let config = responses.is_some();
+    if let (Some(responses), Ok((value, _))) = (responses, &result) {
+        if let Err(e) = add_config_response(system_table, responses, op, value) {
+            result = Err(e);
+        }
+    }
+
+    // If the output is machine-readable
+    if let Some(json) = &mut output.json {
+
+        // Value in the variable before the operation, if found
+        let old = before.as_ref().and_then(|var| var.content.get(offset .. offset + size))
+            .map(|bytes| data::UefiValue(bytes.to_vec()));
+
+        // Record the outcome of the operation
+        json.operations.push(match &result {
+
+            // Success
+            Ok((value, written)) => data::OperationRecord {
+                error: None,
+                new: written.map(|_| value.clone()),
+                old: if written.is_none() { Some(value.clone()) } else { old },
+                status: match written {
+                    None => data::OperationStatus::Read,
+                    Some(false) => data::OperationStatus::Skipped,
+                    Some(true) => data::OperationStatus::Written,
+                },
+                target: op.target.clone(),
+                vendor: before.as_ref().map(|var| var.vendor.0.to_bytes()),
+            },
+
+            // Failure
+            Err(e) => data::OperationRecord {
+                error: Some(e.to_string()),
+                new: match op.action {
+                    data::OperationType::Get => None,
+                    data::OperationType::Set(value) => Some(data::UefiValue::from_usize(value, size)),
+                },
+                old,
+                status: data::OperationStatus::Failed,
+                target: op.target.clone(),
+                vendor: before.as_ref().map(|var| var.vendor.0.to_bytes()),
+            },
+
+        });
+
+    }
+
+    // Determine the outcome
+    match result {
+
+        // Success, which is already recorded if the output
+        // is machine-readable, or collected as HII configuration
+        Ok(_) if config || output.json.is_some() => Status::SUCCESS,
+
+        // Success
+        Ok((value, written)) => {
+
+            // Output the formatted value,
+            // adding a comment if no writing occurred
+            output.line(format!("{}{}", op.to_string_with_val(&value),
+                if let Some(false) = written {
+                    msg::OP_SKIPPED
+                } else {
+                    ""
+                }));
+
+            // Return no error
+            Status::SUCCESS
+
+        }
+
+        // Failure
+        Err(e) => {
+
+            // Show error message and interrupt processing
+            output.error(prefix, &e);
+            Status::ABORTED
+
+        }
+
+    }
+
+}
+
+// Completes the results, outputting the machine-readable document if any,
+// and writes them to the output file, if requested, returning the status
+// given, or an error status if the file could not be written
+fn write_output(system_table: &SystemTable<Boot>,
+    args: &Args, output: &mut Output, status: Status) -> Status {
+
+    // Output the document, if any
+    output.finish();
+
+    // Nothing to do without a file
+    let (Some(path), Some(lines)) = (&args.output, &output.file) else {
+        return status;
+    };
+
+    // One result per line, each of them terminated
+    let text = lines.iter().map(|line| format!("{line}\n")).collect::<String>();
+
+    // Write the file in the requested encoding
+    match write_file(system_table, path, &encode_text(&text, args.encoding)) {
+
+        // Success
+        Ok(()) => status,
+
+        // Failure
+        Err(e) => {
+
+            // Show error message
+            println!("{}: {e}", msg::ERR_PREFIX_OUTPUT);
+            Status::ABORTED
+
+        }
+
+    }
+
+}
+
 // Shows the usage information and exits the application
 fn show_usage(system_table: &SystemTable<Boot>, status: Status) {
 
@@ -236,8 +1076,8 @@ fn show_usage(system_table: &SystemTable<Boot>, status: Status) {
     let image_name = get_image_name(&system_table);
 
     // Output the usage information, substituting the image name
-    println!("{}{image_name}{}{image_name}{}{image_name}{}",
-        msg::USAGE[0], msg::USAGE[1], msg::USAGE[2], msg::USAGE[3]);
+    println!("{}{image_name}{}{image_name}{}{image_name}{}{image_name}{}",
+        msg::USAGE[0], msg::USAGE[1], msg::USAGE[2], msg::USAGE[3], msg::USAGE[4]);
 
     // Make a call to UEFI boot services to exit
     exit(&system_table, status);
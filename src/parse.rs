@@ -7,17 +7,26 @@
 // Processes command-line and stream input into data structures
 
 // Declare fully-qualified symbols to be used in the local scope
-use alloc::{borrow::{Cow, ToOwned}, format, string::ToString, vec::Vec};
+use alloc::{borrow::{Cow, ToOwned}, format, string::{String, ToString}, vec, vec::Vec};
 use uefi::{CStr16, CString16, data_types::EqStrUntilNul};
 
 // Symbols from other modules
+use crate::boot::{LoadOptionId, LoadOptionType};
 use crate::config;
 use crate::data::{
-    Arg, Args, ArgOperation, ArgOption,
-    InputEntry, OperationTarget, OperationType};
+    Arg, Args, ArgOperation, ArgOption, BootCommand, InputEntry, InputLine, InputScope,
+    InteractiveCommand, OperationTarget, OperationType, OutputFormat, RestartType, ScriptParam,
+    ScriptStep, TextEncoding};
 use crate::error::AppError;
+use crate::guid::parse_guid;
+use crate::hii;
 use crate::parse_multiple;
-use crate::string::{CStr16Ext, try_next_char};
+use crate::script::{
+    self, BlockDirective, ConditionScope, ConditionTarget, ErrorOffset, InputBlock, SystemInfo,
+    TargetPath, is_constant_name, offset_in, parse_block_directive, parse_constant, parse_layout,
+    parse_offset, parse_require_directive, parse_target_def_layout, parse_target_def_values,
+    parse_value, strip_directive};
+use crate::string::{CStr16Ext, resolve_relative_path};
 
 // Command-Line Arguments
 
@@ -29,10 +38,30 @@ pub fn parse_args(args: Vec<CString16>) -> Result<Args, AppError> {
         return Err(AppError::ArgNone);
     }
 
-    // Iterate through arguments, trying to parse each
-    // with either the operation or the option parser
-    let args = args.into_iter().map(|s| {
-        parse_multiple!(&s, parse_arg_option, parse_arg_operation)
+    // Take out the input file paths, each of them following a separate
+    // input file option, and the output file path in the same way
+    let mut input = Vec::new();
+    let mut output = None;
+    let mut other = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg.eq_str_until_nul(config::OPT_ARG_INPUT)
+            || arg.eq_str_until_nul(config::OPT_ARG_INPUT_LONG) {
+            input.push(args.next().ok_or(AppError::ArgInput)?);
+        } else if arg.eq_str_until_nul(config::OPT_ARG_OUTPUT)
+            || arg.eq_str_until_nul(config::OPT_ARG_OUTPUT_LONG) {
+            output = Some(args.next().ok_or(AppError::ArgOutput)?);
+        } else {
+            other.push(arg);
+        }
+    }
+
+    // Iterate through arguments, trying to parse each as a parameter,
+    // told apart by its form, or with the option, the boot entry
+    // or the operation parser
+    let args = other.into_iter().map(|s| {
+        parse_arg_param(&s).unwrap_or_else(||
+            parse_multiple!(&s, parse_arg_option, parse_arg_boot, parse_arg_operation))
 
             // Interrupt on error
             .map_err(|e| AppError::Arg(format!("\"{s}\" - {e}")))
@@ -43,7 +72,13 @@ pub fn parse_args(args: Vec<CString16>) -> Result<Args, AppError> {
     // Collect operations
     let operations = args.iter().filter(|arg| matches!(arg, Arg::Operation(_)))
         .map(|arg| {
-            if let Arg::Operation(arg) = arg { arg.clone() } else { unreachable!() }
+            if let Arg::Operation(arg) = arg { ScriptStep::Operation(arg.clone()) } else { unreachable!() }
+        }).collect::<Vec<_>>();
+
+    // Collect boot entry commands
+    let boot = args.iter().filter(|arg| matches!(arg, Arg::Boot(_)))
+        .map(|arg| {
+            if let Arg::Boot(arg) = arg { arg.clone() } else { unreachable!() }
         }).collect::<Vec<_>>();
 
     // Collect options
@@ -52,18 +87,38 @@ pub fn parse_args(args: Vec<CString16>) -> Result<Args, AppError> {
             if let Arg::Option(arg) = arg { arg } else { unreachable!() }
         }).collect::<Vec<_>>();
 
+    // Collect parameters
+    let params = args.iter().filter_map(|arg| match arg {
+        Arg::Param(param) => Some(param.clone()),
+        _ => None }).collect::<Vec<_>>();
+
     // Initialize the argument structure
-    let mut args = Args::default();
+    let mut args = Args { boot, input, output, params, ..Default::default() };
 
     // Assign the options
     for option in options {
         match option {
 
+            // Output as HII configuration
+            ArgOption::Config => args.config = true,
+
+            // Output file encoding
+            ArgOption::Encoding(encoding) => args.encoding = *encoding,
+
             // Force-write even if already set
             ArgOption::Force => args.force = true,
 
+            // Results output format
+            ArgOption::Format(format) => args.format = *format,
+
+            // Route through HII configuration
+            ArgOption::Hii => args.hii = true,
+
+            // Do not show results on the console
+            ArgOption::Quiet => args.quiet = true,
+
             // Restart system on completion
-            ArgOption::Restart => args.restart = true,
+            ArgOption::Restart(restart_type) => args.restart = Some(*restart_type),
 
             // Simulate, do not write
             ArgOption::Simulate => args.simulate = true,
@@ -92,24 +147,90 @@ fn parse_arg_operation(arg: &CStr16) -> Result<Arg, AppError> {
 
     // This function is just a wrapper that returns
     // a general instead of an operation argument
-    Ok(Arg::Operation(parse_operation(arg)?))
+    Ok(Arg::Operation(parse_operation(arg, &[])?))
+
+}
+
+// Attempts to parse a parameter override, such as "CL=0x24", returns
+// nothing if the argument is not one, i.e. the part before the assignment
+// is not a constant name, as with the operations, which have an offset
+fn parse_arg_param(arg: &CStr16) -> Option<Result<Arg, AppError>> {
+
+    // Split into the name and the value
+    let arg = arg.to_string();
+    let (name, value) = arg.split_once(config::CHAR_ARG_ASS)?;
+    if !is_constant_name(name) {
+        return None;
+    }
+
+    // Evaluate the value, without any constants to refer to
+    Some(parse_value(value, &[])
+        .map(|value| Arg::Param(ScriptParam { name: name.to_owned(), value, used: false }))
+        .map_err(AppError::from))
+
+}
+
+// Attempts to parse a boot entry command as a general argument
+fn parse_arg_boot(arg: &CStr16) -> Result<Arg, AppError> {
+
+    // The short form only lists the entries
+    if arg.eq_str_until_nul(config::OPT_ARG_BOOT) {
+        return Ok(Arg::Boot(BootCommand::List));
+    }
+
+    // The long form has the same syntax as in the input stream
+    let arg = arg.to_string();
+    let command = arg.strip_prefix(config::OPT_ARG_LONG)
+        .ok_or(AppError::ArgOpt)?;
+
+    // Parse the command
+    Ok(Arg::Boot(parse_boot_command(command)?))
 
 }
 
 // Attempts to parse a command-line option as a general argument
 fn parse_arg_option(key: &CStr16) -> Result<Arg, AppError> {
 
+    // Output as HII configuration
+    if key.eq_str_until_nul(config::OPT_ARG_CONFIG)
+        || key.eq_str_until_nul(config::OPT_ARG_CONFIG_LONG) {
+
+        Ok(Arg::Option(ArgOption::Config))
+
+    // Output file encoding
+    } else if let Some(encoding) = parse_encoding_option(key,
+        &[config::OPT_ARG_ENCODING, config::OPT_ARG_ENCODING_LONG]) {
+
+        Ok(Arg::Option(ArgOption::Encoding(encoding?)))
+
     // Force-write even if already set
-    if key.eq_str_until_nul(config::OPT_ARG_FORCE)
+    } else if key.eq_str_until_nul(config::OPT_ARG_FORCE)
         || key.eq_str_until_nul(config::OPT_ARG_FORCE_LONG) {
 
         Ok(Arg::Option(ArgOption::Force))
 
+    // Results output format
+    } else if let Some(format) = parse_format_option(key, &[config::OPT_ARG_FORMAT_LONG]) {
+
+        Ok(Arg::Option(ArgOption::Format(format?)))
+
+    // Route through HII configuration
+    } else if key.eq_str_until_nul(config::OPT_ARG_HII)
+        || key.eq_str_until_nul(config::OPT_ARG_HII_LONG) {
+
+        Ok(Arg::Option(ArgOption::Hii))
+
+    // Do not show results on the console
+    } else if key.eq_str_until_nul(config::OPT_ARG_QUIET)
+        || key.eq_str_until_nul(config::OPT_ARG_QUIET_LONG) {
+
+        Ok(Arg::Option(ArgOption::Quiet))
+
     // Restart system on completion
-    } else if key.eq_str_until_nul(config::OPT_ARG_RESTART)
-        || key.eq_str_until_nul(config::OPT_ARG_RESTART_LONG) {
+    } else if let Some(restart_type) = parse_restart_option(key,
+        &[config::OPT_ARG_RESTART, config::OPT_ARG_RESTART_LONG]) {
 
-        Ok(Arg::Option(ArgOption::Restart))
+        Ok(Arg::Option(ArgOption::Restart(restart_type?)))
 
     // Simulate, do not write
     } else if key.eq_str_until_nul(config::OPT_ARG_SIMULATE)
@@ -138,114 +259,450 @@ fn parse_arg_option(key: &CStr16) -> Result<Arg, AppError> {
 
 // Input Stream (Standard Input)
 
-// Parses an input script read from a stream such as standard input
-pub fn parse_input(input: CString16) -> Result<Args, AppError> {
+// Parses an input script read from a stream such as standard input,
+// with the parameters given to override those it declares, which
+// are marked as used if it does, and the system information
+// to check the conditions against
+pub fn parse_input(input: CString16, path: Option<&CStr16>, params: &mut [ScriptParam],
+    system: &SystemInfo, read: &mut impl FnMut(&CStr16) -> Result<CString16, AppError>) -> Result<Args, AppError> {
 
     // Error if nothing to parse
     if input.is_empty() {
         return Err(AppError::InputNone);
     }
 
-    // Split input into lines
-    let lines = input.split(config::CHAR_CTL_LF);
+    // Parse the input into the entries, including other files as requested,
+    // starting with the path of this one, if any, to detect the cycles
+    // Note: the errors are collected rather than returned
+    // one by one, so that all of them are reported at once
+    let mut stack = path.map(|path| vec![CString16::from(path)]).unwrap_or_default();
+    let mut errors = Vec::new();
+    let lines = parse_input_entries(&input, &mut stack, &mut InputScope::default(),
+        params, system, read, &mut errors);
 
-    // Remove comments and whitespace from each line
-    let lines = lines.into_iter().filter_map(|s| {
+    // The entries alone, in the order given
+    let entries = || lines.iter().map(|line| &line.entry);
 
-        // Set up a helper variable
-        let mut line_filtered = s.clone();
+    // Collect parsed target definitions
+    let target_defs = entries().filter(
+        |e| matches!(e, InputEntry::TargetDefinition { .. }))
+        .map(|e| e.as_def()).collect::<Vec<_>>();
 
-        // If the line has a comment
-        if s.has(config::CHAR_INPUT_COMMENT) {
+    // Collect parsed input operations in the order given, including
+    // those with references, resolved in place, as an operation
+    // may depend on the outcome of any of the preceding ones,
+    // and so may a block checking the variables
+    let (operations, _) = collect_script_steps(&mut lines.iter(), &target_defs, &mut errors);
+
+    // Report all the errors found, if any
+    if !errors.is_empty() {
+        return Err(AppError::many(errors));
+    }
+
+    // Collect parsed boot entry commands
+    let boot = entries().filter_map(|e| match e {
+        InputEntry::Boot(command) => Some(command.clone()),
+        _ => None }).collect::<Vec<_>>();
+
+    // Set option arguments from input
+    let config = entries().any(    // Output as HII configuration
+        |e| matches!(e, InputEntry::Option(ArgOption::Config)));
 
-            // Find out where the comment begins
-            let comment_start = s.find_first(config::CHAR_INPUT_COMMENT).unwrap();
-	
-            // If at first character,
-            // ignore the entire line
-            if comment_start == 0 {
-                return None;
+    let force = entries().any(     // Force write
+        |e| matches!(e, InputEntry::Option(ArgOption::Force)));
+
+    let hii = entries().any(       // Route through HII
+        |e| matches!(e, InputEntry::Option(ArgOption::Hii)));
+
+    let restart = entries().rev().find_map(|e| match e {  // Restart when done
+        InputEntry::Option(ArgOption::Restart(restart_type)) => Some(*restart_type),
+        _ => None });
+
+    let simulate = entries().any(  // Simulate, do not write
+        |e| matches!(e, InputEntry::Option(ArgOption::Simulate)));
+
+    // Return the complete argument structure
+    Ok(Args { op: operations, boot, config, force, hii, restart, simulate, ..Default::default() })
+
+}
+
+// Collects the operations from the input lines into the steps, with
+// the references resolved using the definitions given, and the blocks
+// checking the variables nested, stopping at the else or the endif
+// directive that ends the block being collected, if any, which is
+// returned along with the steps, adding the errors to those given
+fn collect_script_steps<'a>(lines: &mut impl Iterator<Item = &'a InputLine>,
+    defs: &[(&CString16, &OperationTarget)], errors: &mut Vec<AppError>)
+    -> (Vec<ScriptStep>, Option<&'a InputEntry>) {
+
+    // Set up the steps
+    let mut steps = Vec::new();
+
+    // Go through the lines
+    while let Some(line) = lines.next() {
+        match &line.entry {
+
+            // Operation, or a reference resolved into one
+            InputEntry::Operation(op) => steps.push(ScriptStep::Operation(op.clone())),
+            InputEntry::TargetReference { name, path, action, hii } =>
+                match resolve_target_ref(name, path, *action, *hii, defs.iter().copied()) {
+                    Ok(op) => steps.push(ScriptStep::Operation(op)),
+                    Err(e) => errors.push(e.at(&line.source, line.line, &line.text)),
+                },
+
+            // Block, with the steps up to the else directive,
+            // if there is one, and the rest up to the endif
+            InputEntry::If(condition) => {
+                let (then, end) = collect_script_steps(lines, defs, errors);
+                let otherwise = match end {
+                    Some(InputEntry::Else) => collect_script_steps(lines, defs, errors).0,
+                    _ => Vec::new(),
+                };
+                steps.push(ScriptStep::Block { condition: condition.clone(), then, otherwise });
             }
-	
-            // Filter out the comment portion
-            line_filtered = s.substring(0, comment_start - 1);
-	
-        }
 
-        // Trim leading and trailing whitespace
-        line_filtered = line_filtered.trim();
+            // End of the block being collected
+            end @ (InputEntry::Else | InputEntry::EndIf) => return (steps, Some(end)),
+
+            // Nothing else is run as a step
+            _ => {}
 
-        // Skip line if completely empty
-        if line_filtered.is_empty() {
-            return None;
         }
+    }
 
-        // Return the result
-        Some(line_filtered)
+    // Return the steps, up to the end of the input
+    (steps, None)
 
-    });
+}
 
-    // Parse the input into the entries
-    let entries = lines.map(|s| {
-        parse_multiple!(&s,
-            parse_input_option, parse_target_def,
-            parse_input_operation, parse_target_ref) })
-        .collect::<Result<Vec<_>, _>>()?;
+// Parses the lines of the input into the entries, each include directive
+// replaced with the entries from the file it names, read with the function
+// given, the stack holding the paths of the files being parsed, the current
+// one last, to detect any cycles, the constants and the definitions made
+// so far, including in the other files, the parameters to override, and
+// the system to check the conditions against, skipping the lines in the blocks
+// whose conditions do not hold, and keeping those checking the variables
+// for when running; every line is parsed even if some of them are malformed,
+// with the errors added to those given, so that all of them can be
+// reported at once
+fn parse_input_entries(input: &CStr16, stack: &mut Vec<CString16>,
+    scope: &mut InputScope, params: &mut [ScriptParam], system: &SystemInfo,
+    read: &mut impl FnMut(&CStr16) -> Result<CString16, AppError>,
+    errors: &mut Vec<AppError>) -> Vec<InputLine> {
+
+    // Name of the input to tell where the entries are
+    let source = stack.last().map(|path| path.to_string())
+        .unwrap_or_else(|| config::INPUT_SOURCE_STDIN.to_owned());
+
+    // Set up the entries, and the conditional blocks open at this point
+    let mut entries = Vec::new();
+    let mut blocks = Vec::new();
+
+    // Go through the lines
+    for (number, text) in input.split(config::CHAR_CTL_LF).iter().enumerate() {
+
+        // Remove comments and whitespace, skipping the line if nothing is left
+        let Some((start, line)) = filter_input_line(text) else {
+            continue;
+        };
+
+        // Line number, from one, the line as written, and the location
+        // of the errors in it, which refer to the entry past any whitespace
+        let number = number + 1;
+        let text = text.to_string();
+        let locate = |e: AppError| e.offset(start).at(&source, number, &text);
+
+        // Conditional block directives, followed even in the blocks
+        // being skipped, to tell where each of them ends, with those
+        // checking the variables kept, for when the operations are run
+        match parse_block_directive(&line.to_string(), number, start, &text, &mut blocks, scope, system) {
+            Some(Ok(Some(directive))) => {
+                let entry = match directive {
+                    BlockDirective::If(condition) => InputEntry::If(condition),
+                    BlockDirective::Else => InputEntry::Else,
+                    BlockDirective::EndIf => InputEntry::EndIf,
+                };
+                entries.push(InputLine { entry, line: number, source: source.clone(), text });
+                continue;
+            }
+            Some(Ok(None)) => continue,
+            Some(Err(e)) => {
+                errors.push(locate(e));
+                continue;
+            }
+            None => {}
+        }
 
-    // Collect parsed target definitions
-    let target_defs = entries.iter().filter(
-        |e| matches!(e, InputEntry::TargetDefinition { .. }))
-        .map(|e| e.as_def()).collect::<Vec<_>>();
+        // Skip the lines in the blocks whose conditions do not hold
+        if !blocks.iter().all(InputBlock::is_active) {
+            continue;
+        }
+
+        // Only the operations can be in the blocks checking the variables,
+        // as everything else takes effect before any of them is run
+        let deferred = blocks.iter().any(|block| block.deferred);
 
-    // Collect parsed input operations
-    let mut operations = entries.iter().filter(
-        |e| matches!(e, InputEntry::Operation(_))).map(
-            |e| e.as_op()).cloned().collect::<Vec<_>>();
-
-    // Collect parsed target references
-    let target_refs = entries.iter().filter(
-        |e| matches!(e, InputEntry::TargetReference { .. }));
-
-    // Collect parsed input operations with references
-    let mut operations_ref = target_refs.map(|e| {
-        match e {
-            InputEntry::TargetReference { name, action } => {
-
-                // Find the target definition for the reference
-                let (_, target) = target_defs.iter().find(
-                    |(def_name, _)| name == *def_name).ok_or_else(
-                        || AppError::InputRefNone(name.to_string()))?;
-                Ok(ArgOperation {
-                    target: (*target).clone(), action: *action })
+        // Requirement, which has nothing to add if it is met
+        if let Some(result) = parse_require_directive(&line.to_string(), scope, system) {
+            if let Err(e) = result.and(if deferred { Err(AppError::InputIfEntry) } else { Ok(()) }) {
+                errors.push(locate(e));
             }
-            _ => unreachable!() }})
-        .collect::<Result<Vec<_>, AppError>>()?;
+            continue;
+        }
 
-    // Include the operations with references
-    // together with those defined directly
-    operations.append(&mut operations_ref);
+        // Include directive, or otherwise a single entry,
+        // keeping where it came from in either case
+        let result = match parse_include_directive(&line) {
+            Some(_) if deferred => Err(AppError::InputIfEntry),
+            Some(path) => path.and_then(|(offset, path)|
+                include_input_file(&path, stack, scope, params, system, read, errors)
+                    .map_err(|e| e.offset(offset))),
+            None => parse_input_entry(&line, scope).and_then(|mut entry| {
+
+                // Check what can be in a block checking the variables
+                if deferred && !matches!(entry, InputEntry::Operation(_) | InputEntry::TargetReference { .. }) {
+                    return Err(AppError::InputIfEntry);
+                }
+
+                // Keep a constant for the lines that follow, a parameter
+                // taking the value given to override it, if any
+                if let InputEntry::Constant { name, value, param } = &mut entry {
+                    if *param {
+                        for given in params.iter_mut().filter(|given| given.name == *name) {
+                            given.used = true;
+                            *value = given.value;
+                        }
+                    }
+                    scope.consts.push((name.clone(), *value));
+                }
+
+                // Keep a structure for the definitions that follow
+                if let InputEntry::Layout(layout) = &entry {
+                    scope.layouts.push(layout.clone());
+                }
+
+                // Keep a definition for the conditions that follow
+                if let InputEntry::TargetDefinition { name, target } = &entry {
+                    scope.defs.push((name.clone(), target.clone()));
+                }
+
+                Ok(vec![InputLine { entry, line: number, source: source.clone(), text: text.clone() }])
+
+            }),
+        };
+
+        // Keep the entries, or the error, and go on either way
+        match result {
+            Ok(lines) => entries.extend(lines),
+            Err(e) => errors.push(locate(e)),
+        }
 
-    // Set option arguments from input
-    let force = entries.iter().any(     // Force write
-        |e| matches!(e, InputEntry::Option(ArgOption::Force)));
+    }
 
-    let restart = entries.iter().any(   // Restart when done
-        |e| matches!(e, InputEntry::Option(ArgOption::Restart)));
+    // Every block must end in the same file
+    for block in blocks {
+        errors.push(AppError::InputIf.offset(block.start).at(&source, block.line, &block.text));
+    }
 
-    let simulate = entries.iter().any(  // Simulate, do not write
-        |e| matches!(e, InputEntry::Option(ArgOption::Simulate)));
+    // Return the entries
+    entries
 
-    // Return the complete argument structure
-    Ok(Args { op: operations, force, restart, simulate, usage: false })
+}
+
+// Reads and parses an included file, with the path relative to the including
+// one, last on the stack, returning its entries, with the errors in it added
+// to those given, or an error if the file cannot be included at all
+fn include_input_file(path: &CStr16, stack: &mut Vec<CString16>,
+    scope: &mut InputScope, params: &mut [ScriptParam], system: &SystemInfo,
+    read: &mut impl FnMut(&CStr16) -> Result<CString16, AppError>,
+    errors: &mut Vec<AppError>) -> Result<Vec<InputLine>, AppError> {
+
+    // Resolve the path relative to the including file
+    let path = resolve_relative_path(stack.last().map(|path| path.as_ref()), path);
+
+    // Stop if already being parsed, with the names compared
+    // regardless of the case, same as the file system does
+    if stack.iter().any(|other| other.to_string().eq_ignore_ascii_case(&path.to_string())) {
+        return Err(AppError::InputIncludeCycle(path.to_string()));
+    }
+
+    // Also stop if nested too deep, which catches
+    // a cycle with the same file named differently
+    if stack.len() > config::INPUT_INCLUDE_DEPTH_MAX {
+        return Err(AppError::InputIncludeDepth);
+    }
+
+    // Read the file, and parse it the same way
+    let input = read(&path)?;
+    stack.push(path);
+    let entries = parse_input_entries(&input, stack, scope, params, system, read, errors);
+    stack.pop();
+
+    // Return the entries
+    Ok(entries)
+
+}
+
+// Parses a line of the input into an entry, with the parser chosen by
+// the form of the line, so that if it is malformed, the error comes
+// from the parser that applies: an option or a boot entry command,
+// a reference, a definition, or otherwise an operation, with the constants,
+// the structures and the definitions made so far in the scope given
+fn parse_input_entry(line: &CStr16, scope: &InputScope) -> Result<InputEntry, AppError> {
+
+    // Options and boot entry commands share the prefix,
+    // as do the constants and the structures
+    if line.has_first(config::CHAR_INPUT_OPT) {
+
+        // Constants
+        if let Some(entry) = parse_input_constant(line, &scope.consts) {
+            return entry;
+        }
+
+        // Structures
+        if let Some(entry) = parse_input_struct(line, &scope.consts) {
+            return entry;
+        }
+
+        // Boot entry commands all start with one of these
+        let name = line.to_string();
+        let name = name.trim_start_matches(config::CHAR_INPUT_OPT);
+        let boot = [config::OPT_BOOT_EDIT, config::OPT_BOOT_LIST, config::OPT_BOOT_SECURE]
+            .iter().any(|command| name.starts_with(command));
+
+        // Try the option first, and if it is not one, the command,
+        // reporting the error for the command if the name is one
+        return match parse_input_option(line) {
+            Err(e @ AppError::InputOpt(_)) => parse_input_boot(line)
+                .map_err(|boot_error| if boot { boot_error } else { e }),
+            result => result,
+        };
+
+    }
+
+    // References, with an optional prefix to route through HII
+    if line.strip_first(config::CHAR_ARG_HII).unwrap_or(line).has_first(config::CHAR_INPUT_REF) {
+        return parse_target_ref(line, scope);
+    }
+
+    // Definitions
+    if line.has(config::CHAR_INPUT_DEF) {
+        return parse_target_def(line, scope);
+    }
+
+    // Operations
+    parse_input_operation(line, &scope.consts)
+
+}
+
+// Attempts to split an input directive from what follows it after whitespace,
+// such as the path in "!include defs.txt", returns nothing if the line
+// is not the directive given, or otherwise the offset where the rest
+// of it starts, and the rest itself, possibly empty
+fn strip_input_directive(line: &CStr16, name: &str) -> Option<(usize, String)> {
+    let line = line.to_string();
+    strip_directive(&line, name).map(|(offset, rest)| (offset, rest.to_owned()))
+}
+
+// Attempts to parse an include directive, with the path following
+// the option name after whitespace, such as "!include defs.txt",
+// returns nothing if the line is not an include directive, or otherwise
+// the offset where the path starts, and the path itself
+fn parse_include_directive(line: &CStr16) -> Option<Result<(usize, CString16), AppError>> {
+
+    // Return the path, which must be present
+    let (offset, path) = strip_input_directive(line, config::OPT_INPUT_INCLUDE)?;
+    Some(if path.is_empty() {
+        Err(AppError::InputInclude(line.to_string()))
+    } else {
+        Ok((offset, CString16::try_from(path.as_str()).unwrap()))
+    })
+
+}
+
+// Attempts to parse a constant, with the name and the value following
+// the option name after whitespace, such as "!set BASE=0x10", or
+// a parameter declared the same way, such as "!param CL=0x22",
+// returns nothing if the line does not set a constant
+fn parse_input_constant(line: &CStr16, consts: &[(String, usize)])
+    -> Option<Result<InputEntry, AppError>> {
+
+    // Wrap the constant into an entry
+    Some(parse_constant(&line.to_string(), consts)?
+        .map(|(name, value, param)| InputEntry::Constant { name, value, param })
+        .map_err(AppError::from))
+
+}
+
+// Attempts to parse a structure, with the name and the fields in braces
+// following the option name after whitespace, such as
+// "!struct Timing { tCL:0(1) tRAS:3(2) }", returns nothing
+// if the line does not declare a structure
+fn parse_input_struct(line: &CStr16, consts: &[(String, usize)])
+    -> Option<Result<InputEntry, AppError>> {
+
+    // Parse the rest of the line
+    let (offset, rest) = strip_input_directive(line, config::OPT_INPUT_STRUCT)?;
+    Some(parse_layout(&rest, consts).map(InputEntry::Layout).map_err(|e| e.offset(offset).into()))
+
+}
+
+// Removes the comment and whitespace from an input line, returns nothing
+// if there is nothing else left, or otherwise the offset where the rest
+// of it starts, and the rest itself
+fn filter_input_line(s: &CStr16) -> Option<(usize, CString16)> {
+
+    // Set up a helper variable
+    let mut line_filtered = CString16::from(s);
+
+    // If the line has a comment
+    if s.has(config::CHAR_INPUT_COMMENT) {
+
+        // Find out where the comment begins
+        let comment_start = s.find_first(config::CHAR_INPUT_COMMENT).unwrap();
+
+        // If at first character,
+        // ignore the entire line
+        if comment_start == 0 {
+            return None;
+        }
+
+        // Filter out the comment portion
+        line_filtered = s.substring(0, comment_start - 1);
+
+    }
+
+    // Trim leading and trailing whitespace
+    let (start, line_filtered) = line_filtered.trim_offset();
+
+    // Skip line if completely empty
+    if line_filtered.is_empty() {
+        return None;
+    }
+
+    // Return the result
+    Some((start, line_filtered))
+
+}
+
+// Attempts to parse a boot entry command as an input entry
+fn parse_input_boot(arg: &CStr16) -> Result<InputEntry, AppError> {
+
+    // Remove the option prefix
+    let named_arg = arg.strip_first(config::CHAR_INPUT_OPT)
+        .ok_or_else(|| AppError::InputOpt(arg.to_string()))?;
+
+    // Parse the command, which starts past the prefix
+    Ok(InputEntry::Boot(parse_boot_command(&named_arg.to_string()).map_err(|e| e.offset(1))?))
 
 }
 
 // Attempts to parse a command-line operation as an input entry
-fn parse_input_operation(arg: &CStr16) -> Result<InputEntry, AppError> {
+fn parse_input_operation(arg: &CStr16, consts: &[(String, usize)]) -> Result<InputEntry, AppError> {
 
     // This function is just a wrapper that returns
     // an input entry instead of an operation argument
-    Ok(InputEntry::Operation(crate::parse::parse_operation(arg)?))
+    Ok(InputEntry::Operation(crate::parse::parse_operation(arg, consts)?))
 
 }
 
@@ -257,15 +714,26 @@ fn parse_input_option(arg: &CStr16) -> Result<InputEntry, AppError> {
         .ok_or_else(|| AppError::InputOpt(arg.to_string()))?;
 
     // Parse the option
-    if named_arg.eq_str_until_nul(config::OPT_INPUT_FORCE) {
+    if named_arg.eq_str_until_nul(config::OPT_INPUT_CONFIG) {
+
+        // Output as HII configuration
+        Ok(InputEntry::Option(ArgOption::Config))
+
+    } else if named_arg.eq_str_until_nul(config::OPT_INPUT_FORCE) {
 
         // Force-write even if already set
         Ok(InputEntry::Option(ArgOption::Force))
 
-    } else if named_arg.eq_str_until_nul(config::OPT_INPUT_RESTART) {
+    } else if named_arg.eq_str_until_nul(config::OPT_INPUT_HII) {
+
+        // Route through HII configuration
+        Ok(InputEntry::Option(ArgOption::Hii))
+
+    } else if let Some(restart_type) = parse_restart_option(named_arg,
+        &[config::OPT_INPUT_RESTART]) {
 
         // Restart system on completion
-        Ok(InputEntry::Option(ArgOption::Restart))
+        Ok(InputEntry::Option(ArgOption::Restart(restart_type.map_err(|e| e.offset(1))?)))
 
     } else if named_arg.eq_str_until_nul(config::OPT_INPUT_SIMULATE) {
 
@@ -281,23 +749,435 @@ fn parse_input_option(arg: &CStr16) -> Result<InputEntry, AppError> {
 
 }
 
+// Attempts to parse an encoding option, with the encoding following
+// an assignment operator, such as "--encoding=utf16", returns nothing
+// if the option name is none of those given
+fn parse_encoding_option(arg: &CStr16, names: &[&str])
+    -> Option<Result<TextEncoding, AppError>> {
+
+    // Split into the option name and the encoding
+    let arg = arg.to_string();
+    let (name, value) = arg.split_once(config::CHAR_ARG_ASS)?;
+
+    // Check the option name
+    if !names.contains(&name) {
+        return None;
+    }
+
+    // Determine the encoding
+    Some(match value {
+        config::OPT_ENCODING_UTF8 => Ok(TextEncoding::Utf8),
+        config::OPT_ENCODING_UTF16 => Ok(TextEncoding::Utf16Le),
+        config::OPT_ENCODING_UTF16BE => Ok(TextEncoding::Utf16Be),
+        value => Err(AppError::ArgEncoding(value.to_owned())),
+    })
+
+}
+
+// Attempts to parse an output format option, with the format following
+// an assignment operator, such as "--format=json", returns nothing
+// if the option name is none of those given
+fn parse_format_option(arg: &CStr16, names: &[&str])
+    -> Option<Result<OutputFormat, AppError>> {
+
+    // Split into the option name and the format
+    let arg = arg.to_string();
+    let (name, value) = arg.split_once(config::CHAR_ARG_ASS)?;
+
+    // Check the option name
+    if !names.contains(&name) {
+        return None;
+    }
+
+    // Determine the format
+    Some(match value {
+        config::OPT_FORMAT_JSON => Ok(OutputFormat::Json),
+        config::OPT_FORMAT_TEXT => Ok(OutputFormat::Text),
+        value => Err(AppError::ArgFormat(value.to_owned())),
+    })
+
+}
+
+// Attempts to parse a restart option, with an optional restart type
+// following an assignment operator, such as "--restart=cold" or "!restart=setup",
+// returns nothing if the option name is none of those given
+fn parse_restart_option(arg: &CStr16, names: &[&str])
+    -> Option<Result<RestartType, AppError>> {
+
+    // Split into the option name and the restart type, if any
+    let arg = arg.to_string();
+    let (name, value) = match arg.split_once(config::CHAR_ARG_ASS) {
+        Some((name, value)) => (name, Some(value)),
+        None => (arg.as_str(), None),
+    };
+
+    // Check the option name
+    if !names.contains(&name) {
+        return None;
+    }
+
+    // Determine the restart type
+    Some(match value {
+        None | Some(config::OPT_RESTART_WARM) => Ok(RestartType::Warm),
+        Some(config::OPT_RESTART_COLD) => Ok(RestartType::Cold),
+        Some(config::OPT_RESTART_PLATFORM) => Ok(RestartType::Platform),
+        Some(config::OPT_RESTART_SETUP) => Ok(RestartType::Setup),
+        Some(config::OPT_RESTART_SHUTDOWN) => Ok(RestartType::Shutdown),
+        Some(value) => Err(AppError::ArgRestart(value.to_owned()).offset(offset_in(&arg, value))),
+    })
+
+}
+
+// Interactive Mode
+// (Prompt Only)
+
+// Parses a single line entered at the prompt, either as an interactive
+// command, or as an input entry with any reference resolved right away
+// using the definitions made so far, returns nothing if the line is blank
+pub fn parse_input_line(line: &CStr16, scope: &InputScope)
+    -> Result<Option<InteractiveCommand>, AppError> {
+
+    // Skip blank lines and comments
+    let Some((_, line)) = filter_input_line(line) else {
+        return Ok(None);
+    };
+
+    // Split into the command and its argument, if any
+    let string = line.to_string();
+    let (command, arg) = match string.split_once(config::CHAR_ARG_SEP) {
+        Some((command, arg)) => (command, Some(arg.trim())),
+        None => (string.as_str(), None),
+    };
+
+    // Look up the definitions by name
+    let find_def = |name: &CStr16| scope.defs.iter().find(|(def_name, _)| **def_name == *name);
+
+    // Interactive commands first
+    match (command, arg) {
+
+        // Show all contents of a variable, or open it in the editor,
+        // given either by name and identifier, or by a reference
+        (config::CMD_INTERACTIVE_DUMP | config::CMD_INTERACTIVE_EDIT, Some(arg)) => {
+            let error = || AppError::InputCommand(arg.to_owned());
+            let arg = CString16::try_from(arg).map_err(|_| error())?;
+            let (name, id) = match arg.strip_first(config::CHAR_INPUT_REF) {
+                Some(name) => {
+                    let (name, _) = parse_target_path(&name.to_string(), &scope.consts)?;
+                    find_def(&name).map(|(_, target)| (target.name.clone(), target.id))
+                        .ok_or_else(|| AppError::InputRefNone(name.to_string()))?
+                }
+                None if arg.has(config::CHAR_ARG_POS) => Err(error())?,
+                None => parse_variable(arg).map_err(|_| error())?,
+            };
+            return Ok(Some(if command == config::CMD_INTERACTIVE_DUMP {
+                InteractiveCommand::Dump { name, id }
+            } else {
+                InteractiveCommand::Entry(InputEntry::Boot(BootCommand::Edit { name, id }))
+            }));
+        }
+        (config::CMD_INTERACTIVE_DUMP | config::CMD_INTERACTIVE_EDIT, None) =>
+            Err(AppError::InputCommand(string.clone()))?,
+
+        // End the session
+        (config::CMD_INTERACTIVE_EXIT | config::CMD_INTERACTIVE_QUIT, None) =>
+            return Ok(Some(InteractiveCommand::Exit)),
+
+        // Show the available commands
+        (config::CMD_INTERACTIVE_HELP, None) => return Ok(Some(InteractiveCommand::Help)),
+
+        // Show the definitions
+        (config::CMD_INTERACTIVE_LIST, None) => return Ok(Some(InteractiveCommand::List)),
+
+        // Anything else is an input entry
+        _ => {}
+
+    }
+
+    // Parse the line the same way as the input
+    let entry = parse_input_entry(&line, scope)?;
+
+    // Resolve a reference into an operation
+    let entry = match entry {
+        InputEntry::TargetReference { name, path, action, hii } => InputEntry::Operation(
+            resolve_target_ref(&name, &path, action, hii, scope.defs.iter().map(|(name, target)| (name, target)))?),
+        entry => entry,
+    };
+
+    // Validate an operation, as with the arguments
+    if let InputEntry::Operation(op) = &entry {
+        op.validate()?;
+    }
+
+    // Return the entry
+    Ok(Some(InteractiveCommand::Entry(entry)))
+
+}
+
+// Parses the identifier entered at the prompt to choose among
+// the variables sharing a name, returns nothing if the line is blank
+pub fn parse_input_choice(line: &CStr16) -> Result<Option<usize>, AppError> {
+
+    // Blank line means no choice
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    // Parse the identifier as either a decimal or a hexadecimal number
+    Ok(Some(parse_multiple!(&line, parse_value_hex, parse_value_dec)?))
+
+}
+
+// HII Configuration Strings
+// (Input Stream Only)
+
+// Checks whether an input stream holds HII configuration strings
+// rather than operations, judging by the first line of content
+pub fn is_config_input(input: &CStr16) -> bool {
+
+    // Skip blank lines, comments and options
+    input.split(config::CHAR_CTL_LF).into_iter()
+        .map(|s| s.trim())
+        .find(|s| !s.is_empty()
+            && !s.has_first(config::CHAR_INPUT_COMMENT)
+            && !s.has_first(config::CHAR_INPUT_OPT))
+
+        // Check the first line remaining
+        .is_some_and(|s| hii::is_config_string(&s.to_string()))
+
+}
+
+// Parses HII configuration strings (ConfigResp, or ConfigRequest to read only)
+// read from a stream, using the given function to resolve the identifier
+// of the variable each of them refers to, which is based on the vendor GUID
+pub fn parse_config_input(input: &CStr16,
+    resolve_id: impl Fn(&CStr16, &[u8; 16]) -> Result<Option<usize>, AppError>)
+    -> Result<Args, AppError> {
+
+    // Initialize the argument structure
+    let mut args = Args::default();
+
+    // Configuration strings, possibly split across lines
+    let mut strings = String::new();
+
+    // Go through the input line by line
+    for line in input.split(config::CHAR_CTL_LF) {
+
+        // Trim leading and trailing whitespace
+        let line = line.trim();
+
+        // Skip blank lines and comments
+        if line.is_empty() || line.has_first(config::CHAR_INPUT_COMMENT) {
+            continue;
+        }
+
+        // Options can be set the same way as in the usual input
+        if line.has_first(config::CHAR_INPUT_OPT) {
+            match parse_input_option(&line)? {
+                InputEntry::Option(ArgOption::Config) => args.config = true,
+                InputEntry::Option(ArgOption::Force) => args.force = true,
+                InputEntry::Option(ArgOption::Hii) => args.hii = true,
+                InputEntry::Option(ArgOption::Restart(restart_type)) =>
+                    args.restart = Some(restart_type),
+                InputEntry::Option(ArgOption::Simulate) => args.simulate = true,
+                _ => unreachable!() }
+            continue;
+        }
+
+        // Everything else is a part of the configuration strings
+        // Note: an element separator is added in between the lines
+        strings.push_str(&line.to_string());
+        strings.push('&');
+
+    }
+
+    // Parse the configuration strings
+    let configs = hii::parse_config_strings(&strings);
+    if configs.is_empty() {
+        return Err(AppError::InputConfigNone);
+    }
+
+    // Convert each of them into operations
+    for config in configs {
+
+        // Retrieve the variable name and identifier
+        let name = CString16::try_from(config.header.name.clone())
+            .map_err(|_| AppError::InputConfig(config.to_string()))?;
+        let id = resolve_id(&name, &config.header.guid)?;
+
+        // Each block results in one or more operations
+        for block in &config.blocks {
+            args.op.extend(ArgOperation::from_config_block(&name, id, block)
+                .into_iter().map(ScriptStep::Operation));
+        }
+
+    }
+
+    // Validate arguments
+    args.validate()?;
+
+    // Ready
+    Ok(args)
+
+}
+
 // Common (Command-Line & Input Stream)
 
-// Attempts to parse a command-line argument as an operation argument
-fn parse_operation(arg: &CStr16) -> Result<ArgOperation, AppError> {
+// Attempts to parse a boot entry command, with its prefix already removed
+fn parse_boot_command(arg: &str) -> Result<BootCommand, AppError> {
+
+    // Set up the error to return in case of failure
+    let error = || AppError::ArgBoot(arg.to_owned());
+
+    // Split into the command and its value, if any
+    let (command, value) = match arg.split_once(config::CHAR_ARG_ASS) {
+        Some((command, value)) => (command, Some(value.trim())),
+        None => (arg, None),
+    };
+
+    // Load option identifier, boot by default
+    let parse_id = |s: &str| LoadOptionId::parse(s.trim()).ok_or_else(error);
+
+    // Number of a boot load option only
+    let parse_boot_number = |s: &str| match parse_id(s)? {
+        LoadOptionId { kind: LoadOptionType::Boot, number } => Ok(number),
+        _ => Err(error()),
+    };
+
+    // Parse the command
+    match (command, value) {
+
+        // List load options and the order
+        (config::OPT_BOOT_LIST, None) => Ok(BootCommand::List),
+
+        // Show Secure Boot state and signature databases
+        (config::OPT_BOOT_SECURE, None) => Ok(BootCommand::SecureBoot),
+
+        // Write or append a signed update to a signature database
+        (config::OPT_BOOT_SECURE_APPEND | config::OPT_BOOT_SECURE_WRITE, Some(value)) => {
+
+            // Database name and file path, the latter non-empty
+            let (name, path) = value.split_once(config::CHAR_BOOT_SEP).ok_or_else(error)?;
+            let (name, path) = (name.trim(), path.trim());
+            if !config::VAR_SIGLISTS_AUTH.contains(&name) || path.is_empty() {
+                Err(error())?
+            }
+
+            // Return the command
+            Ok(BootCommand::SecureBootWrite { name: name.to_owned(),
+                path: CString16::try_from(path).map_err(|_| error())?,
+                append: command == config::OPT_BOOT_SECURE_APPEND })
+
+        }
+
+        // Add a load option
+        (config::OPT_BOOT_CREATE, Some(value)) => {
+
+            // Identifier, description and device path, in this order
+            // Note: the device path might contain separators itself
+            let mut parts = value.splitn(3, config::CHAR_BOOT_SEP);
+            let id = parse_id(parts.next().ok_or_else(error)?)?;
+            let description = parts.next().ok_or_else(error)?.trim();
+            let path = parts.next().ok_or_else(error)?.trim();
+
+            // Neither can be empty
+            if description.is_empty() || path.is_empty() {
+                Err(error())?
+            }
+
+            // Return the command
+            Ok(BootCommand::Create { id, description: description.to_owned(),
+                path: CString16::try_from(path).map_err(|_| error())? })
+
+        }
+
+        // Open a variable in the editor
+        (config::OPT_BOOT_EDIT, Some(value)) => {
+            let value = CString16::try_from(value).map_err(|_| error())?;
+            if value.is_empty() || value.has(config::CHAR_ARG_POS) {
+                Err(error())?
+            }
+            let (name, id) = parse_variable(value).map_err(|_| error())?;
+            Ok(BootCommand::Edit { name, id })
+        }
+
+        // Remove, disable or enable a load option
+        (config::OPT_BOOT_DELETE, Some(value)) => Ok(BootCommand::Delete(parse_id(value)?)),
+        (config::OPT_BOOT_DISABLE, Some(value)) => Ok(BootCommand::Disable(parse_id(value)?)),
+        (config::OPT_BOOT_ENABLE, Some(value)) => Ok(BootCommand::Enable(parse_id(value)?)),
+
+        // Boot once from a given entry
+        (config::OPT_BOOT_NEXT, Some(value)) => Ok(BootCommand::Next(parse_boot_number(value)?)),
+
+        // Move the given entries to the front of the order
+        (config::OPT_BOOT_ORDER, Some(value)) => Ok(BootCommand::Order(
+            value.split(config::CHAR_BOOT_SEP).map(parse_boot_number)
+                .collect::<Result<Vec<_>, _>>()?)),
+
+        // Unknown or malformed
+        _ => Err(error()),
+
+    }
+
+}
+
+// Attempts to parse a command-line argument as an operation argument,
+// with the offset, size and value possibly being expressions that refer
+// to the constants given
+fn parse_operation(arg: &CStr16, consts: &[(String, usize)]) -> Result<ArgOperation, AppError> {
+
+    // Determine whether to route through HII configuration
+    // Indicated by an optional prefix, removed if present
+    let (arg, hii) = match arg.strip_first(config::CHAR_ARG_HII) {
+        Some(arg) => (arg, true),
+        None => (arg, false),
+    };
+
+    // Errors refer to the argument past the prefix, if any
+    let prefix = usize::from(hii);
 
     // Split the argument at the offset specification
-    let mut arg_split = arg.split(config::CHAR_ARG_POS);
+    let mut arg_split = arg.split_offsets(config::CHAR_ARG_POS);
 
     // Every argument must have
     // exactly one offset indicator
     if arg_split.len() != 2 {
-        Err(AppError::ArgPos)?
+        Err(AppError::ArgPos.offset(prefix))?
     }
 
-    // Determine the variable name
+    // Determine the variable name, and either the vendor GUID or
+    // the identifier, not both, to tell the namesakes apart
     // Note: swap_remove() is O(1), remove is O(n)
-    let mut name = arg_split.swap_remove(0);
+    let (_, variable) = arg_split.swap_remove(0);
+    let (name, vendor) = parse_vendor(variable.clone()).map_err(|e| e.offset(prefix))?;
+    let (name, id) = parse_variable(name).map_err(|e| e.offset(prefix))?;
+    if vendor.is_some() && id.is_some() {
+        Err(AppError::ArgVarGuid(variable.to_string()).offset(prefix))?
+    }
+
+    // Determine the offset (position within variable),
+    // and where it starts in the argument
+    let (start, offset) = arg_split.swap_remove(0);
+    let start = prefix + start;
+
+    // Determine the operation type and the new data
+    // to be assigned if operation type is to set the value
+    let (offset, op_type) = parse_operation_type(&offset, consts).map_err(|e| e.offset(start))?;
+
+    // Determine the value size and the offset value,
+    // the size defaulting to a byte (1), or defined in brackets
+    let (offset, size) = parse_offset(&offset.to_string(), consts).map_err(|e| e.offset(start))?;
+
+    // Return the populated data structure
+    Ok(ArgOperation { action: op_type, hii, reference: None,
+        target: OperationTarget { id, name, offset, size,
+            values: Vec::new(), layout: None, count: None, vendor }})
+
+}
+
+// Attempts to parse a variable name, together with
+// the optional identifier in brackets to tell namesakes
+fn parse_variable(mut name: CString16) -> Result<(CString16, Option<usize>), AppError> {
 
     // Determine the variable identifier
     // Empty by default, can be defined in brackets
@@ -305,7 +1185,7 @@ fn parse_operation(arg: &CStr16) -> Result<ArgOperation, AppError> {
     if name.has(config::CHAR_ARG_BKT_L) {
 
         // Split the variable name at the opening bracket
-        let mut arg_split = name.split(config::CHAR_ARG_BKT_L);
+        let mut arg_split = name.split_offsets(config::CHAR_ARG_BKT_L);
 
         // Part left of offset may have
         // at most a single bracket
@@ -315,74 +1195,55 @@ fn parse_operation(arg: &CStr16) -> Result<ArgOperation, AppError> {
 
         // Remove the matching closing bracket
         // Error out if no closing bracket present
-        let id_string = arg_split[1]
+        let (start, id_string) = &arg_split[1];
+        let id_string = id_string
             .strip_last(config::CHAR_ARG_BKT_R)
             .ok_or(AppError::ArgVarBktR)?;
 
         // Parse the variable identifier as
         // either a decimal or a hexadecimal number
-        id = Some(parse_multiple!(&id_string, parse_value_dec, parse_value_hex)?);
+        id = Some(parse_multiple!(@offset *start; &id_string, parse_value_dec, parse_value_hex)?);
 
         // Update the variable name
         // to remove the part in brackets
-        name = arg_split.swap_remove(0);
+        name = arg_split.swap_remove(0).1;
 
     }
 
-    // Determine the offset (position within variable)
-    let offset = arg_split.swap_remove(0);
-
-    // Determine the operation type and the new data
-    // to be assigned if operation type is to set the value
-    let (mut offset, op_type) = parse_operation_type(&offset)?;
+    // Return the name and identifier
+    Ok((name, id))
 
-    // Determine the value size
-    // Defaults to a byte (1), can be defined in brackets
-    let mut size = 1;
-    if offset.has(config::CHAR_ARG_BKT_L) {
-
-        // Split the offset at the opening bracket
-        let mut arg_split = offset.split(config::CHAR_ARG_BKT_L);
-
-        // Part right of offset may have
-        // at most a single bracket
-        if arg_split.len() != 2 {
-            Err(AppError::ArgPosBktL)?
-        }
-
-        // Remove the matching closing bracket
-        // Error out if no closing bracket present
-        let size_string = arg_split[1]
-            .strip_last(config::CHAR_ARG_BKT_R)
-            .ok_or(AppError::ArgPosBktR)?;
-
-        // Parse the size as either a decimal or a hexadecimal number
-        size = parse_multiple!(&size_string, parse_value_dec, parse_value_hex)?;
+}
 
-        // Update the offset to remove the part in brackets
-        offset = Cow::Owned(arg_split.swap_remove(0));
+// Attempts to parse a variable name, together with the optional
+// vendor GUID in braces, which also tells namesakes apart
+fn parse_vendor(name: CString16) -> Result<(CString16, Option<[u8; 16]>), AppError> {
 
-    }
+    // Split the variable name at the opening brace, if any
+    let string = name.to_string();
+    let Some((var_name, guid)) = string.split_once(config::CHAR_ARG_GUID_L) else {
+        return Ok((name, None));
+    };
 
-    // Parse the offset value as either a decimal or a hexadecimal number
-    let offset = parse_multiple!(&offset, parse_value_hex, parse_value_dec)?;
+    // The GUID must be well-formed and end the variable name
+    let vendor = guid.strip_suffix(config::CHAR_ARG_GUID_R).and_then(parse_guid)
+        .ok_or_else(|| AppError::ArgVarGuid(string.to_owned()))?;
 
-    // Return the populated data structure
-    Ok(ArgOperation { action: op_type,
-        target: OperationTarget { id, name, offset, size }})
+    // Return the name without the GUID, and the GUID
+    Ok((CString16::try_from(var_name).unwrap(), Some(vendor)))
 
 }
 
 // Attempts to parse operation type,
 // and optionally the new data to be set
-fn parse_operation_type(arg: &CStr16)
-    -> Result<(Cow<CStr16>, OperationType), AppError> {
+fn parse_operation_type<'a>(arg: &'a CStr16, consts: &[(String, usize)])
+    -> Result<(Cow<'a, CStr16>, OperationType), AppError> {
 
     // If operation is an assignment
     if arg.has(config::CHAR_ARG_ASS) {
 
         // Split the string at the opening bracket
-        let mut arg_split = arg.split(config::CHAR_ARG_ASS);
+        let mut arg_split = arg.split_offsets(config::CHAR_ARG_ASS);
 
         // There can only be
         // a single assignment operator
@@ -390,13 +1251,14 @@ fn parse_operation_type(arg: &CStr16)
             Err(AppError::ArgAss)?
         }
 
-        // Parse the value to be assigned as either a decimal or a hexadecimal number
-        let value = parse_multiple!(&arg_split[1], parse_value_hex, parse_value_dec)?;
+        // Parse the value to be assigned
+        let (start, value) = &arg_split[1];
+        let value = parse_value(&value.to_string(), consts).map_err(|e| e.offset(*start))?;
 
         // Set the operation type to assignment (set) and the new
         // value to be assigned, update the offset to only the part
         // on the left-hand size of the assignment operator
-        Ok((Cow::Owned(arg_split.swap_remove(0)), OperationType::Set(value)))
+        Ok((Cow::Owned(arg_split.swap_remove(0).1), OperationType::Set(value)))
 
     } else {
 
@@ -408,89 +1270,128 @@ fn parse_operation_type(arg: &CStr16)
 
 }
 
-// Attempts to parse a decimal value
-fn parse_value_dec(value: &CStr16) -> Result<usize, AppError> {
-
-    // Split the input string into a character vector
-    let chars = value.iter().map(|&c| char::from(c)).collect::<Vec<_>>();
-
-    // Only ASCII digits are allowed in a decimal value
-    if chars.iter().any(|c| !c.is_ascii_digit()) {
-        Err(AppError::ArgNumDec(format!("\"{}\"", value.to_string())))?
-    }
-
-    // Iterate through the characters:
-    // Take the ASCII value of each (48-57), and deduct the value of 0 (48)
-    // Multiply the accumulator by 10 at each step and add to the total
-    let value = chars.into_iter()
-        .fold(0usize, |acc, n| acc * 10 + (n as u8 - b'0') as usize);
-
-    // Return
-    Ok(value)
-
-}
-
-// Attempts to parse a hexadecimal value
-fn parse_value_hex(value: &CStr16) -> Result<usize, AppError> {
-
-    // Define an iterator over the input string
-    let mut str_iter = value.iter().map(|&c| char::from(c));
+// Conditions
 
-    // Retrieve the initial two characters of the string
-    let c0 = try_next_char(&mut str_iter, value)?;
-    let c1 = try_next_char(&mut str_iter, value)?;
+// Implementation of what the conditions can refer to, with the variables
+// compared given the same way as the operations
+impl ConditionScope for InputScope {
 
-    // Check the prefix
-    match (c0, c1) {
+    type Target = OperationTarget;
+    type Error = AppError;
 
-        // The hexadecimal
-        // prefix is present
-        ('0', 'x') => {}
-        ('0', 'X') => {}
+    fn is_defined(&self, name: &str) -> bool {
+        self.defs.iter().any(|(def_name, _)| def_name.to_string() == name)
+    }
 
-        // Report an error in hexadecimal string formatting
-        _ => return Err(AppError::ArgNumHexPrefix(format!("\"{}\"", value.to_string()))),
+    fn target(&self, target: &str) -> Result<ConditionTarget<OperationTarget>, AppError> {
+
+        // Resolve the reference using the definitions made so far, selecting
+        // the element and the field, if any, the names of its values usable
+        // in the number
+        if let Some(reference) = target.strip_prefix(config::CHAR_INPUT_REF) {
+            let offset = offset_in(target, reference);
+            let (name, path) = parse_target_path(reference, &self.consts).map_err(|e| e.offset(offset))?;
+            let (_, def) = self.defs.iter().find(|(def_name, _)| *def_name == name)
+                .ok_or_else(|| AppError::InputRefNone(name.to_string()).offset(offset))?;
+            return Ok((def.select(&name, &path)?, self.consts_for(&name)));
+        }
 
-    }
+        // Otherwise parse the variable, the offset and the size as in an operation
+        let op = parse_operation(&CString16::try_from(target)
+            .map_err(|_| AppError::InputCond(target.to_owned()))?, &self.consts)?;
+        if op.hii {
+            return Err(AppError::InputCond(target.to_owned()));
+        }
+        Ok((op.target, self.consts.clone()))
 
-    // Check if within the size constraint
-    if value.num_bytes() > 2 * (18 + 1) {
-        return Err(AppError::ArgSizeLimit(value.to_string()));
     }
 
-    // Convert each byte (char) to its hexadecimal value
-    let value = str_iter
-        .map(|c| c.to_digit(16).map(|n| n as u8))
-        .collect::<Option<Vec<u8>>>()
-        .ok_or_else(|| AppError::ArgNumHex(format!("\"{}\"", value.to_string())))?;
+}
 
-    // Sum all the values, multiplied according their respective positions
-    let length = value.len();
-    let value = value.iter().enumerate().fold(0usize, |acc, (i, &n)| {
-        acc + ((n as usize) << (4 * (length - i - 1)))
-    });
+// Numbers
 
-    // Return
-    Ok(value)
+// Attempts to parse a decimal value
+fn parse_value_dec(value: &CStr16) -> Result<usize, AppError> {
+    Ok(script::parse_value_dec(&value.to_string())?)
+}
 
+// Attempts to parse a hexadecimal value
+fn parse_value_hex(value: &CStr16) -> Result<usize, AppError> {
+    Ok(script::parse_value_hex(&value.to_string())?)
 }
 
 // Definitions & References
 // (Input Stream Only)
 
-// Attempts to parse a target definition
-pub fn parse_target_def(arg: &CStr16) -> Result<InputEntry, AppError> {
+// Attempts to parse a target definition, optionally bound to a structure
+// declared above, or made an array, such as "as Timing[4]", and followed
+// by the names of its values in braces, such as "{Disabled=0,Enabled=1}"
+pub fn parse_target_def(arg: &CStr16, scope: &InputScope) -> Result<InputEntry, AppError> {
+
+    // Constants to use in the target
+    let consts = scope.consts.as_slice();
 
     // Split into two at input definition sepator
-    let (name, target) = arg.split_once(config::CHAR_INPUT_DEF)
+    let (name, _) = arg.split_once(config::CHAR_INPUT_DEF)
         .ok_or_else(|| AppError::InputDef(arg.to_string()))?;
 
+    // Keep the target as a slice of the whole definition,
+    // to tell where each of its parts starts
+    let string = arg.to_string();
+    let (_, target) = string.split_once(config::CHAR_INPUT_DEF).unwrap();
+    let target = target.trim();
+
+    // Split off the names of the values, if any
+    // Note: only looked for past the offset, since the vendor GUID is in braces too
+    let (target, values) = match target.find(config::CHAR_ARG_POS)
+        .and_then(|pos| target[pos ..].find(config::CHAR_INPUT_ENUM_L).map(|index| pos + index)) {
+        Some(index) => (target[.. index].trim_end(), Some(&target[index ..])),
+        None => (target, None),
+    };
+
+    // Split off the structure and the number of elements, if any,
+    // following the keyword after whitespace
+    let keyword = target.match_indices(config::INPUT_DEF_AS).map(|(index, _)| index)
+        .find(|&index| target[.. index].ends_with(char::is_whitespace)
+            && target[index + config::INPUT_DEF_AS.len() ..]
+                .starts_with(|c: char| c.is_whitespace() || c == config::CHAR_INPUT_INDEX_L));
+    let (target, layout) = match keyword {
+        Some(index) => (target[.. index].trim_end(), Some(&target[index + config::INPUT_DEF_AS.len() ..])),
+        None => (target, None),
+    };
+    let offset = offset_in(&string, target);
+    let target = CString16::try_from(target).unwrap();
+
     // Attempt to parse the target as an operation
-    let operation = parse_operation(&target)?;
+    let mut operation = parse_operation(&target, consts).map_err(|e| e.offset(offset))?;
+
+    // Bind the structure, and make the target an array, as requested,
+    // the size coming from the structure, if there is one, and the values
+    // being those of the fields, rather than of the target itself
+    if let Some(layout) = layout {
+        (operation.target.layout, operation.target.count) =
+            parse_target_def_layout(layout, &scope.layouts, &scope.consts)
+                .map_err(|e| e.offset(offset_in(&string, layout)))?;
+        if operation.target.layout.is_some() && (operation.target.size != 1 || values.is_some()) {
+            Err(AppError::InputDef(arg.to_string()))?
+        }
+    }
+
+    // Routing is up to the references, not the definition
+    if operation.hii {
+        Err(AppError::InputDef(arg.to_string()))?
+    }
 
     // Check operation type
     if let OperationType::Get = operation.action {
 
+        // Add the names of the values, each of which
+        // has to fit within the size of the target
+        if let Some(values) = values {
+            operation.target.values = parse_target_def_values(values, consts, operation.target.size)
+                .map_err(|e| e.offset(offset_in(&string, values)))?;
+        }
+
         // Add the target definition entry
         Ok(InputEntry::TargetDefinition {
             name, target: operation.target })
@@ -498,16 +1399,28 @@ pub fn parse_target_def(arg: &CStr16) -> Result<InputEntry, AppError> {
     } else {
 
         // Value setting not allowed in a definition
-        Err(AppError::InputDefSet(target.to_string()))
+        Err(AppError::InputDefSet(target.to_string()).offset(offset))
 
     }
 
 }
 
-// Attempts to parse a target reference
-fn parse_target_ref(arg: &CStr16)
+// Attempts to parse a target reference, possibly to an element or a field,
+// with the value assigned, if any, possibly being the name of one
+// of the values of the definition
+fn parse_target_ref(arg: &CStr16, scope: &InputScope)
     -> Result<InputEntry, AppError> {
 
+    // Determine whether to route through HII configuration
+    // Indicated by an optional prefix, removed if present
+    let (arg, hii) = match arg.strip_first(config::CHAR_ARG_HII) {
+        Some(arg) => (arg, true),
+        None => (arg, false),
+    };
+
+    // Errors refer to the reference past the prefixes
+    let prefix = usize::from(hii) + 1;
+
     // Check if prefix is the first character in reference
     let arg = if arg.has_first(config::CHAR_INPUT_REF) {
 
@@ -522,37 +1435,66 @@ fn parse_target_ref(arg: &CStr16)
 
     };
 
-    // Retrieval is the default,
-    // name is the whole argument
-    let mut action = OperationType::Get;
-    let mut name = CString16::from(arg);
+    // Split into two at argument assignment operator, if it appears
+    // in the argument, keeping the operator in the value as it is required,
+    // otherwise the name is the whole argument
+    let (name, value) = match arg.find_first(config::CHAR_ARG_ASS) {
+        Some(ass) => (arg.substring(0, ass - 1), Some(arg.substring(ass, arg.num_chars() - 1))),
+        None => (CString16::from(arg), None),
+    };
 
-    // Override the settings for an assignment operation
-    // if assignment operator appears in the argument
-    if arg.has(config::CHAR_ARG_ASS) {
+    // Split the name of the definition from the element and the field
+    let (name, path) = parse_target_path(&name.to_string(), &scope.consts)
+        .map_err(|e| e.offset(prefix))?;
 
-        // Split into two at argument assignment operator
-        // keep the operator in the value as it is required
-        let ass = arg.find_first(config::CHAR_ARG_ASS).unwrap();
-        name = arg.substring(0, ass - 1);
-        let value = arg.substring(ass, arg.num_chars() - 1);
+    // Retrieve the operation type (and the new value if set),
+    // retrieval being the default
+    let action = match value {
+        Some(value) => parse_target_ref_operation_type(&value, &scope.consts_for(&name))
+            .map_err(|e| e.offset(prefix + arg.num_chars() - value.num_chars()))?,
+        None => OperationType::Get,
+    };
 
-        // Retrieve the operation type (and the new value if set)
-        action = parse_target_ref_operation_type(&value)?;
+    // Return the reference
+    Ok(InputEntry::TargetReference { action, hii, name, path })
 
-    }
+}
 
-    // Return the reference
-    Ok(InputEntry::TargetReference { action, name: name.to_owned() })
+// Attempts to split a reference into the name of the definition,
+// and the element and the field, either of them optional
+fn parse_target_path(arg: &str, consts: &[(String, usize)])
+    -> Result<(CString16, TargetPath), AppError> {
+
+    // Convert the name, reporting the reference as written if it cannot be
+    let (name, path) = script::parse_target_path(arg, consts)?;
+    let error = || AppError::InputRef(arg.trim().to_owned()).offset(offset_in(arg, arg.trim()));
+    Ok((CString16::try_from(name).map_err(|_| error())?, path))
+
+}
+
+// Resolves a target reference into an operation, by finding the definition
+// with the matching name, and selecting the element and the field, if any
+fn resolve_target_ref<'a>(name: &CString16, path: &TargetPath, action: OperationType, hii: bool,
+    mut defs: impl Iterator<Item = (&'a CString16, &'a OperationTarget)>)
+    -> Result<ArgOperation, AppError> {
+
+    // Find the target definition for the reference
+    let (_, target) = defs.find(|(def_name, _)| name == *def_name)
+        .ok_or_else(|| AppError::InputRefNone(name.to_string()))?;
+
+    // Return the operation, keeping the reference as written to show the results by
+    let reference = CString16::try_from(format!("{name}{path}").as_str()).unwrap();
+    Ok(ArgOperation { target: target.select(name, path)?, action, hii, reference: Some(reference) })
 
 }
 
 // Attempts to parse operation type,
 // and optionally the new data to be set
-fn parse_target_ref_operation_type(arg: &CStr16) -> Result<OperationType, AppError> {
+fn parse_target_ref_operation_type(arg: &CStr16, consts: &[(String, usize)])
+    -> Result<OperationType, AppError> {
 
     // This function is just a wrapper that returns
     // only the operation type and discards the offset
-    Ok(parse_operation_type(arg)?.1)
+    Ok(parse_operation_type(arg, consts)?.1)
 
 }
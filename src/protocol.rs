@@ -0,0 +1,315 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: Protocol
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Declares UEFI protocols not provided by the uefi crate
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{vec, vec::Vec};
+use core::{ffi::c_void, ptr::{null, null_mut}};
+use uefi::{Char16, CStr16, CString16, Status,
+    proto::unsafe_protocol,
+    table::boot::BootServices};
+
+// HII Configuration Routing Protocol
+// Routes configuration strings to and from the drivers owning the
+// variable stores, so that their validation and callbacks take place
+
+#[repr(C)]
+#[unsafe_protocol("587e72d7-cc50-4f79-8209-ca291fc1a10f")]
+pub struct HiiConfigRouting {
+
+    extract_config: unsafe extern "efiapi" fn(this: *const Self,
+        request: *const Char16, progress: *mut *const Char16,
+        results: *mut *mut Char16) -> Status,
+
+    export_config: unsafe extern "efiapi" fn(this: *const Self,
+        results: *mut *mut Char16) -> Status,
+
+    route_config: unsafe extern "efiapi" fn(this: *const Self,
+        configuration: *const Char16, progress: *mut *const Char16) -> Status,
+
+    // Note: the remaining members (BlockToConfig, ConfigToBlock
+    // and GetAltConfig) are not used and thus not declared
+
+}
+
+// Implementation
+impl HiiConfigRouting {
+
+    // Retrieves the current configuration for a request,
+    // returns the response or the status in case of an error
+    pub fn extract_config(&self, boot_services: &BootServices,
+        request: &CStr16) -> Result<CString16, Status> {
+
+        // Set up the output variables
+        let mut progress = null();
+        let mut results = null_mut();
+
+        // Call the protocol function
+        let status = unsafe {
+            (self.extract_config)(self, request.as_ptr(), &mut progress, &mut results)
+        };
+
+        // Copy and release the results
+        take_pool_string(boot_services, status, results)
+
+    }
+
+    // Retrieves the current configuration of all the drivers,
+    // returns the responses or the status in case of an error
+    pub fn export_config(&self, boot_services: &BootServices)
+        -> Result<CString16, Status> {
+
+        // Set up the output variable
+        let mut results = null_mut();
+
+        // Call the protocol function
+        let status = unsafe {
+            (self.export_config)(self, &mut results)
+        };
+
+        // Copy and release the results
+        take_pool_string(boot_services, status, results)
+
+    }
+
+    // Applies a configuration response,
+    // returns the status in case of an error
+    pub fn route_config(&self, configuration: &CStr16) -> Result<(), Status> {
+
+        // Set up the output variable
+        let mut progress = null();
+
+        // Call the protocol function
+        let status = unsafe {
+            (self.route_config)(self, configuration.as_ptr(), &mut progress)
+        };
+
+        // Convert the status
+        if status.is_error() {
+            Err(status)
+        } else {
+            Ok(())
+        }
+
+    }
+
+}
+
+// HII Database Protocol
+// Holds the HII package lists, such as the forms and strings
+// installed by the drivers owning the setup variable stores
+
+#[repr(C)]
+#[unsafe_protocol("ef9fc172-a1b2-4693-b327-6d32fc416042")]
+pub struct HiiDatabase {
+
+    // Note: the members preceding ExportPackageLists are not used,
+    // and are only declared to keep the layout of the structure
+    _new_package_list: *const c_void,
+    _remove_package_list: *const c_void,
+    _update_package_list: *const c_void,
+    _list_package_lists: *const c_void,
+
+    export_package_lists: unsafe extern "efiapi" fn(this: *const Self,
+        handle: *const c_void, buffer_size: *mut usize, buffer: *mut u8) -> Status,
+
+    // Note: the remaining members (RegisterPackageNotify and
+    // the following ones) are not used and thus not declared
+
+}
+
+// Implementation
+impl HiiDatabase {
+
+    // Exports all the package lists in the database one after
+    // another, returns them or the status in case of an error
+    pub fn export_package_lists(&self) -> Result<Vec<u8>, Status> {
+
+        // Determine the size first
+        let mut size = 0;
+        let status = unsafe {
+            (self.export_package_lists)(self, null(), &mut size, null_mut())
+        };
+
+        // Nothing to export
+        if status == Status::SUCCESS {
+            return Ok(Vec::new());
+        }
+
+        // Anything other than the size being reported is an error
+        if status != Status::BUFFER_TOO_SMALL {
+            return Err(status);
+        }
+
+        // Allocate the buffer and export into it
+        let mut buffer = vec![0; size];
+        let status = unsafe {
+            (self.export_package_lists)(self, null(), &mut size, buffer.as_mut_ptr())
+        };
+
+        // Convert the status
+        if status.is_error() {
+            Err(status)
+        } else {
+            buffer.truncate(size);
+            Ok(buffer)
+        }
+
+    }
+
+}
+
+// Shell Protocol
+// Provided by the UEFI Shell, resolves file paths relative to
+// the current directory, or to any of the mapped file systems
+
+#[repr(C)]
+#[unsafe_protocol("6302d008-7f9b-4f30-87ac-60c9fef5da4e")]
+pub struct Shell {
+
+    // Note: the members preceding OpenFileByName (Execute to
+    // SetFileInfo) are not used, and are only declared to keep
+    // the layout of the structure
+    _before_open_file_by_name: [*const c_void; 24],
+
+    open_file_by_name: unsafe extern "efiapi" fn(file_name: *const Char16,
+        file_handle: *mut *mut c_void, open_mode: u64) -> Status,
+
+    close_file: unsafe extern "efiapi" fn(file_handle: *mut c_void) -> Status,
+
+    _create_file: *const c_void,
+
+    read_file: unsafe extern "efiapi" fn(file_handle: *mut c_void,
+        read_size: *mut usize, buffer: *mut c_void) -> Status,
+
+    write_file: unsafe extern "efiapi" fn(file_handle: *mut c_void,
+        buffer_size: *mut usize, buffer: *const c_void) -> Status,
+
+    _delete_file: *const c_void,
+
+    delete_file_by_name: unsafe extern "efiapi" fn(file_name: *const Char16) -> Status,
+
+    // Note: the members between DeleteFileByName and GetFileSize
+    // (GetFilePosition to FindFilesInDir) are not used either
+    _before_get_file_size: [*const c_void; 5],
+
+    get_file_size: unsafe extern "efiapi" fn(file_handle: *mut c_void,
+        size: *mut u64) -> Status,
+
+    // Note: the remaining members (OpenRoot and
+    // the following ones) are not used and thus not declared
+
+}
+
+// Implementation
+impl Shell {
+
+    // Reads an entire file, with the path either absolute or relative
+    // to the current directory, returns the contents or the status
+    // in case of an error, together with whether the file was opened
+    pub fn read_file_by_name(&self, path: &CStr16) -> Result<Vec<u8>, (Status, bool)> {
+
+        // Open the file for reading
+        let mut handle = null_mut();
+        let status = unsafe {
+            (self.open_file_by_name)(path.as_ptr(), &mut handle, SHELL_FILE_MODE_READ)
+        };
+        if status.is_error() {
+            return Err((status, false));
+        }
+
+        // Determine the size, and read the file into a buffer that large
+        let mut size = 0;
+        let mut status = unsafe { (self.get_file_size)(handle, &mut size) };
+        let mut buffer = vec![0u8; size as usize];
+        if !status.is_error() {
+            let mut read = buffer.len();
+            status = unsafe { (self.read_file)(handle, &mut read, buffer.as_mut_ptr().cast()) };
+            buffer.truncate(read);
+        }
+
+        // Close the file either way
+        unsafe { (self.close_file)(handle) };
+
+        // Convert the status
+        if status.is_error() {
+            Err((status, true))
+        } else {
+            Ok(buffer)
+        }
+
+    }
+
+    // Writes an entire file, replacing it if it exists, with the path
+    // either absolute or relative to the current directory, returns
+    // the status in case of an error, together with whether the file
+    // was opened
+    pub fn write_file_by_name(&self, path: &CStr16, bytes: &[u8]) -> Result<(), (Status, bool)> {
+
+        // Remove the existing file, if any, which would otherwise
+        // keep any of its previous contents past the new ones
+        // Note: the file not being found is not an error here
+        unsafe { (self.delete_file_by_name)(path.as_ptr()) };
+
+        // Create the file for writing
+        let mut handle = null_mut();
+        let status = unsafe {
+            (self.open_file_by_name)(path.as_ptr(), &mut handle, SHELL_FILE_MODE_CREATE)
+        };
+        if status.is_error() {
+            return Err((status, false));
+        }
+
+        // Write the contents
+        let mut size = bytes.len();
+        let status = unsafe { (self.write_file)(handle, &mut size, bytes.as_ptr().cast()) };
+
+        // Close the file either way
+        unsafe { (self.close_file)(handle) };
+
+        // Convert the status
+        if status.is_error() {
+            Err((status, true))
+        } else {
+            Ok(())
+        }
+
+    }
+
+}
+
+// Open modes for reading, and for creating a file to write to
+// (EFI_FILE_MODE_READ, and the latter with EFI_FILE_MODE_WRITE
+// and EFI_FILE_MODE_CREATE)
+const SHELL_FILE_MODE_READ:   u64 = 0x0000000000000001;
+const SHELL_FILE_MODE_CREATE: u64 = 0x8000000000000003;
+
+// Copies a string allocated by a protocol function
+// from the pool, and then releases the pool memory
+fn take_pool_string(boot_services: &BootServices,
+    status: Status, string: *mut Char16) -> Result<CString16, Status> {
+
+    // Fail if the function did
+    if status.is_error() {
+        return Err(status);
+    }
+
+    // No results at all
+    if string.is_null() {
+        return Ok(CString16::new());
+    }
+
+    // Copy the string
+    let copy = CString16::from(unsafe { CStr16::from_ptr(string) });
+
+    // Release the pool memory
+    boot_services.free_pool(string.cast())
+        .map_err(|e| e.status())?;
+
+    // Return the copy
+    Ok(copy)
+
+}
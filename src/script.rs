@@ -0,0 +1,1809 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: Script
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Parses the parts of the input format that do not depend on the firmware,
+// working on plain strings, so that they also build and run on the host
+
+// Declare fully-qualified symbols
+// to be used in the local scope
+use alloc::{borrow::ToOwned, boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+// Symbols from other modules
+use crate::config;
+use crate::guid::parse_guid;
+use crate::smbios::SmbiosInfo;
+
+// Error list
+// Each one reported as the application error of the same name
+#[derive(Debug, PartialEq)]
+pub enum ScriptError {
+
+    // Args
+    ArgConst(String),
+    ArgExpr(String),
+    ArgExprRange(String),
+    ArgMore(String),
+    ArgNumDec(String),
+    ArgNumHex(String),
+    ArgNumHexPrefix(String),
+    ArgPosBktL,
+    ArgPosBktR,
+    ArgSize(usize),
+    ArgSizeLimit(String),
+    ArgSizeMismatch(usize, usize),
+
+    // Input
+    InputCond(String),
+    InputCondKey(String),
+    InputCondText(String),
+    InputCondVersion(String),
+    InputConst(String),
+    InputDef(String),
+    InputDefValues(String),
+    InputElse,
+    InputEndif,
+    InputOffset(usize, Box<ScriptError>),
+    InputOpt(String),
+    InputRef(String),
+    InputRequire(String, String),
+    InputRequireValue,
+    InputStruct(String),
+    InputStructNone(String),
+
+}
+
+// Trait (interface) for the errors that
+// refer to a part of the input
+pub trait ErrorOffset {
+
+    // Moves the part of the input the error refers to by the offset given,
+    // in characters, once the part parsed is known to start there, within
+    // the larger part it was split from
+    fn offset(self, offset: usize) -> Self;
+
+}
+
+// Implementation
+// for script errors
+impl ErrorOffset for ScriptError {
+
+    fn offset(self, offset: usize) -> Self {
+        match self {
+            Self::InputOffset(inner, e) => Self::InputOffset(inner + offset, e),
+            e => Self::InputOffset(offset, Box::new(e)),
+        }
+    }
+
+}
+
+// Trait (interface) for what the lines parsed so far
+// leave for the conditions in those that follow
+pub trait ConditionScope {
+
+    // Variable compared in a condition,
+    // and the error in telling which one
+    type Target;
+    type Error: From<ScriptError> + ErrorOffset;
+
+    // Whether a definition of the name given was made
+    fn is_defined(&self, name: &str) -> bool;
+
+    // Resolves the variable compared in a condition, given either by
+    // a reference, such as "@CState", or the same way as in an operation,
+    // such as "Setup:0x10", together with the constants usable in the number
+    // it is compared with, the errors referring to the part of the text given
+    fn target(&self, target: &str) -> Result<ConditionTarget<Self::Target>, Self::Error>;
+
+}
+
+// Variable compared in a condition, together with
+// the constants usable in the number it is compared with
+pub type ConditionTarget<T> = (T, Vec<(String, usize)>);
+
+// Condition
+// Checked by the conditional blocks and the requirements in the input,
+// with the variable compared given as the target
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition<T> {
+
+    All(Vec<Condition<T>>),  // Every one holds
+    Any(Vec<Condition<T>>),  // At least one holds
+    Not(Box<Condition<T>>),  // Does not hold
+
+    // Property of the system compared with a value
+    Compare { key: ConditionKey, operator: ConditionOperator, value: ConditionValue },
+
+    // Whether a definition was made before, known as soon as parsed
+    Defined(bool),
+
+    // Variable with a given name, and optionally vendor GUID, is there
+    Exists { name: String, vendor: Option<[u8; 16]> },
+
+    // Current value of a variable compared with a number
+    Value { target: T, operator: ConditionOperator, value: usize },
+
+}
+
+// Implementation
+// for conditions
+impl<T> Condition<T> {
+
+    // Checks whether the condition holds for the system given,
+    // without looking at the variables, which are taken not to
+    pub fn holds(&self, system: &SystemInfo) -> bool {
+        self.evaluate(system, &mut |_| Ok::<_, ()>(false)).unwrap_or(false)
+    }
+
+    // Whether the condition looks at the variables,
+    // and so can only be checked when running
+    pub fn checks_variables(&self) -> bool {
+        match self {
+            Self::All(conditions) | Self::Any(conditions) =>
+                conditions.iter().any(Self::checks_variables),
+            Self::Not(condition) => condition.checks_variables(),
+            Self::Compare { .. } | Self::Defined(_) => false,
+            Self::Exists { .. } | Self::Value { .. } => true,
+        }
+    }
+
+    // Checks whether the condition holds for the system given, with the
+    // variables checked by the function given, stopping at its first error,
+    // and only as far as needed to tell the outcome
+    pub fn evaluate<E>(&self, system: &SystemInfo,
+        check: &mut impl FnMut(&Self) -> Result<bool, E>) -> Result<bool, E> {
+
+        Ok(match self {
+            Self::All(conditions) => {
+                for condition in conditions {
+                    if !condition.evaluate(system, check)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            Self::Any(conditions) => {
+                for condition in conditions {
+                    if condition.evaluate(system, check)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            Self::Not(condition) => !condition.evaluate(system, check)?,
+
+            // Compare the text as is, and the versions number
+            // by number, a malformed one never matching
+            Self::Compare { key, operator, value } => {
+                let property = system.property(*key);
+                match value {
+                    ConditionValue::Text(text) => Some(property.cmp(text.as_str())),
+                    ConditionValue::Version(version) => compare_versions(property, version),
+                }.is_some_and(|ordering| operator.matches(ordering))
+            }
+
+            // Known already
+            Self::Defined(defined) => *defined,
+
+            // Up to the variables
+            Self::Exists { .. } | Self::Value { .. } => check(self)?,
+
+        })
+
+    }
+
+}
+
+// Condition property
+// Describes the system, compared with a value in a condition
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConditionKey {
+
+    BiosDate,            // BIOS release date, such as "09/12/2023"
+    BiosVersion,         // BIOS version, such as "1402"
+    BoardProduct,        // Baseboard product name, such as "PRIME Z790-P"
+    FirmwareRevision,    // Firmware revision, such as 5.24
+    FirmwareVendor,      // Firmware vendor, such as "American Megatrends"
+    SystemManufacturer,  // System manufacturer, such as "ASUSTeK COMPUTER INC."
+    SystemProduct,       // System product name
+    UefiRevision,        // UEFI specification revision, such as 2.8
+
+}
+
+// Implementation
+// for condition properties
+impl ConditionKey {
+
+    // Whether the property is a version, which can be
+    // ordered, rather than text, which can only be equal
+    pub fn is_version(&self) -> bool {
+        matches!(self, Self::FirmwareRevision | Self::UefiRevision)
+    }
+
+}
+
+// Condition operator
+// How a property is compared with a value
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConditionOperator {
+
+    Equal,         // =
+    Greater,       // >
+    GreaterEqual,  // >=
+    Less,          // <
+    LessEqual,     // <=
+    NotEqual,      // !=
+
+}
+
+// Implementation
+// for condition operators
+impl ConditionOperator {
+
+    // Checks whether the outcome of a comparison satisfies the operator
+    pub fn matches(&self, ordering: Ordering) -> bool {
+        match self {
+            Self::Equal => ordering.is_eq(),
+            Self::Greater => ordering.is_gt(),
+            Self::GreaterEqual => ordering.is_ge(),
+            Self::Less => ordering.is_lt(),
+            Self::LessEqual => ordering.is_le(),
+            Self::NotEqual => ordering.is_ne(),
+        }
+    }
+
+}
+
+// Condition value
+// What a property is compared with, depending on its kind
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConditionValue {
+
+    Text(String),     // Compared as is, case-sensitive
+    Version(String),  // Numbers separated by dots, compared one by one
+
+}
+
+// System information
+// Identity of the firmware and the board, shown in the header, and checked by the conditions
+
+#[derive(Clone, Debug, Default)]
+pub struct SystemInfo {
+    pub firmware_revision: String,   // Firmware revision, major.minor
+    pub firmware_vendor: String,     // Firmware vendor
+    pub smbios: Option<SmbiosInfo>,  // Board identification, if there are SMBIOS tables
+    pub uefi_revision: String,       // UEFI specification revision
+}
+
+// Implementation
+// for system information
+impl SystemInfo {
+
+    // Retrieval of a property to compare in a condition,
+    // empty if the SMBIOS tables are not there
+    pub fn property(&self, key: ConditionKey) -> &str {
+
+        // Retrieves a field of the board identification
+        let smbios = |field: fn(&SmbiosInfo) -> &String|
+            self.smbios.as_ref().map_or("", |smbios| field(smbios).as_str());
+
+        match key {
+            ConditionKey::BiosDate => smbios(|smbios| &smbios.bios_date),
+            ConditionKey::BiosVersion => smbios(|smbios| &smbios.bios_version),
+            ConditionKey::BoardProduct => smbios(|smbios| &smbios.board_product),
+            ConditionKey::FirmwareRevision => &self.firmware_revision,
+            ConditionKey::FirmwareVendor => &self.firmware_vendor,
+            ConditionKey::SystemManufacturer => smbios(|smbios| &smbios.system_manufacturer),
+            ConditionKey::SystemProduct => smbios(|smbios| &smbios.system_product),
+            ConditionKey::UefiRevision => &self.uefi_revision,
+        }
+
+    }
+
+}
+
+// Implementation: formatting for display,
+// the same way as in the application header
+impl Display for SystemInfo {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+
+        // Firmware
+        write!(f, "{} {} UEFI {}", self.firmware_vendor, self.firmware_revision, self.uefi_revision)?;
+
+        // Board, if known
+        match &self.smbios {
+            Some(smbios) => write!(f, ", {smbios}"),
+            None => Ok(()),
+        }
+
+    }
+
+}
+
+// Target layout
+// Structure declared once, to be bound to definitions at their offsets
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TargetLayout {
+    pub name: String,                         // Name of the structure
+    pub fields: Vec<(String, usize, usize)>,  // Name, offset and size of each field
+}
+
+// Implementation
+impl TargetLayout {
+
+    // Size of the structure, up to the end of the last field
+    pub fn size(&self) -> usize {
+        self.fields.iter().map(|(_, offset, size)| offset + size).max().unwrap_or(0)
+    }
+
+}
+
+// Implementation: formatting for display,
+// in the same format as the input
+impl Display for TargetLayout {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+
+        write!(f, "{} {}", self.name, config::CHAR_INPUT_ENUM_L)?;
+        for (name, offset, size) in &self.fields {
+
+            // Optional size (if not a byte), same as for a target
+            let size_string = if *size == 1 {
+                "".to_owned()
+            } else {
+                format!("{}{size}{}", config::CHAR_ARG_BKT_L, config::CHAR_ARG_BKT_R)
+            };
+
+            write!(f, " {name}{}{offset:#x}{size_string}", config::CHAR_ARG_POS)?;
+
+        }
+        write!(f, " {}", config::CHAR_INPUT_ENUM_R)
+
+    }
+
+}
+
+// Target path
+// Element and field selected by a reference to a definition
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TargetPath {
+    pub index: Option<usize>,   // Element of an array
+    pub field: Option<String>,  // Field of a structure
+}
+
+// Implementation
+impl TargetPath {
+
+    // Selects the element and the field of a definition at the offset and
+    // of the size given, composing the offset from its own, the index times
+    // the size of an element, and the offset of the field, each of which must
+    // be given if the definition is an array or has a structure, and not
+    // otherwise, returns the offset and the size of the single value
+    // selected, or nothing if the path does not fit the definition
+    pub fn select(&self, offset: usize, size: usize, count: Option<usize>,
+        layout: Option<&TargetLayout>) -> Option<(usize, usize)> {
+
+        // Element, each the size of the structure, if any, or of the target
+        let element = layout.map_or(size, TargetLayout::size);
+        let offset = match (count, self.index) {
+            (Some(count), Some(index)) if index < count => index.checked_mul(element)?.checked_add(offset)?,
+            (None, None) => offset,
+            _ => return None,
+        };
+
+        // Field within the element
+        match (layout, &self.field) {
+            (Some(layout), Some(field)) => layout.fields.iter()
+                .find(|(field_name, ..)| field_name == field)
+                .and_then(|&(_, field_offset, size)| Some((offset.checked_add(field_offset)?, size))),
+            (None, None) => Some((offset, size)),
+            _ => None,
+        }
+
+    }
+
+}
+
+// Implementation: formatting for display,
+// in the same format as the input
+impl Display for TargetPath {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+
+        if let Some(index) = self.index {
+            write!(f, "{}{index}{}", config::CHAR_INPUT_INDEX_L, config::CHAR_INPUT_INDEX_R)?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, "{}{field}", config::CHAR_INPUT_FIELD)?;
+        }
+        Ok(())
+
+    }
+
+}
+
+// Input conditional block
+// Started by an if directive, tracked until the matching endif
+
+#[derive(Debug)]
+pub struct InputBlock {
+    pub deferred: bool,   // Checks the variables, so both ways are kept
+    pub holds: bool,      // Whether the condition holds
+    pub otherwise: bool,  // Past the else directive
+    pub line: usize,      // Line number of the if directive
+    pub start: usize,     // Where the directive starts in the line, past any whitespace
+    pub text: String,     // The if directive as written, for the errors
+}
+
+// Implementation
+// for input conditional blocks
+impl InputBlock {
+
+    // Whether the lines at this point in the block are used:
+    // those before the else directive if the condition holds,
+    // and those after it if it does not, or all of them if
+    // the condition can only be checked when running
+    pub fn is_active(&self) -> bool {
+        self.deferred || self.holds != self.otherwise
+    }
+
+}
+
+// Input conditional block directive
+// Kept for when running, if the block checks the variables
+
+#[derive(Debug, PartialEq)]
+pub enum BlockDirective<T> {
+    If(Condition<T>),  // Start of the block, with its condition
+    Else,              // Lines used if the condition does not hold
+    EndIf,             // End of the block
+}
+
+// Directive kept for when running, if any
+pub type KeptDirective<T> = Option<BlockDirective<T>>;
+
+// Finds the offset where a part of a text starts, in characters,
+// the part being a slice taken from the text itself
+pub fn offset_in(text: &str, part: &str) -> usize {
+    text[.. part.as_ptr() as usize - text.as_ptr() as usize].chars().count()
+}
+
+// Compares two versions made up of decimal numbers separated by dots,
+// such as "5.24", number by number, with the missing ones taken as zero,
+// so that "2.8" and "2.8.0" are equal, returns nothing if either is malformed
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+
+    // Split into the numbers, each of which must be present
+    let split = |version: &str| version.split(config::CHAR_COND_VERSION)
+        .map(|number| if !number.is_empty() && number.bytes().all(|c| c.is_ascii_digit()) {
+            number.parse::<usize>().ok()
+        } else {
+            None
+        }).collect::<Option<Vec<_>>>();
+    let (a, b) = (split(a)?, split(b)?);
+
+    // Compare the numbers up to the longer of the two
+    Some((0 .. a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|ordering| ordering.is_ne()).unwrap_or(Ordering::Equal))
+
+}
+
+// Directives
+
+// Attempts to split an input directive from what follows it after whitespace,
+// such as the path in "!include defs.txt", returns nothing if the line
+// is not the directive given, or otherwise the offset where the rest
+// of it starts, and the rest itself, possibly empty
+pub fn strip_directive<'a>(line: &'a str, name: &str) -> Option<(usize, &'a str)> {
+
+    // Remove the option prefix and the directive name
+    let rest = line.strip_prefix(config::CHAR_INPUT_OPT)?.strip_prefix(name)?;
+
+    // Require whitespace in between, unless there is nothing at all
+    if !rest.is_empty() && !rest.starts_with([config::CHAR_BLANK_SPACE, config::CHAR_BLANK_TAB]) {
+        return None;
+    }
+
+    // Return the rest, and where it starts
+    let rest = rest.trim();
+    Some((offset_in(line, rest), rest))
+
+}
+
+// Attempts to parse a conditional block directive: an if, followed
+// by a condition, or an ifdef, followed by a definition name, an else,
+// or an endif, updating the blocks open at this point, returns nothing
+// if the line is not one of them, or otherwise the directive to keep if
+// the block checks the variables, to be decided when running
+pub fn parse_block_directive<S: ConditionScope>(line: &str, number: usize, start: usize,
+    text: &str, blocks: &mut Vec<InputBlock>, scope: &S, system: &SystemInfo)
+    -> Option<Result<KeptDirective<S::Target>, S::Error>> {
+
+    // Whether to keep the directive for the last block, which is only
+    // if it checks the variables, and all the blocks around it are used
+    let keep = |blocks: &[InputBlock]| blocks.split_last().is_some_and(|(block, outer)|
+        block.deferred && outer.iter().all(InputBlock::is_active));
+
+    // Condition, given as is, or as the name of a definition
+    let condition = match strip_directive(line, config::OPT_INPUT_IF) {
+        Some((offset, condition)) => Some(parse_condition(condition, scope)
+            .map_err(|e| e.offset(offset))),
+        None => strip_directive(line, config::OPT_INPUT_IFDEF).map(|(offset, name)|
+            parse_condition_defined_name(name, scope).map_err(|e| e.offset(offset))),
+    };
+
+    // Start a block, even if the condition is malformed, with nothing
+    // in it used then, so that its else and endif still match it
+    if let Some(condition) = condition {
+        let deferred = condition.as_ref().is_ok_and(Condition::checks_variables);
+        let holds = condition.as_ref().is_ok_and(|condition| condition.holds(system));
+        blocks.push(InputBlock { deferred, holds, otherwise: false,
+            line: number, start, text: text.to_owned() });
+        let keep = keep(blocks);
+        return Some(condition.map(|condition| keep.then_some(BlockDirective::If(condition))));
+    }
+
+    // Switch to the lines used if the condition does not hold, at most once
+    if let Some((_, rest)) = strip_directive(line, config::OPT_INPUT_ELSE) {
+        let keep = keep(blocks);
+        return Some(match blocks.last_mut() {
+            _ if !rest.is_empty() => Err(ScriptError::InputOpt(line.to_owned()).into()),
+            Some(block) if !block.otherwise => {
+                block.otherwise = true;
+                Ok(keep.then_some(BlockDirective::Else))
+            },
+            _ => Err(ScriptError::InputElse.into()),
+        });
+    }
+
+    // End the block
+    let (_, rest) = strip_directive(line, config::OPT_INPUT_ENDIF)?;
+    let keep = keep(blocks);
+    Some(if !rest.is_empty() {
+        Err(ScriptError::InputOpt(line.to_owned()).into())
+    } else {
+        blocks.pop().map(|_| keep.then_some(BlockDirective::EndIf)).ok_or(ScriptError::InputEndif.into())
+    })
+
+}
+
+// Attempts to parse a requirement, followed by a condition, such as
+// "!require vendor=\"American Megatrends\"", checking that it holds
+// for the system, returns nothing if the line is not a requirement
+pub fn parse_require_directive<S: ConditionScope>(line: &str, scope: &S,
+    system: &SystemInfo) -> Option<Result<(), S::Error>> {
+
+    // Stop if the condition does not hold, showing what the system is instead,
+    // the variables not being checked, as that is only done when running
+    let (offset, condition) = strip_directive(line, config::OPT_INPUT_REQUIRE)?;
+    Some(parse_condition(condition, scope).and_then(|parsed| if parsed.checks_variables() {
+        Err(ScriptError::InputRequireValue.into())
+    } else if parsed.holds(system) {
+        Ok(())
+    } else {
+        Err(ScriptError::InputRequire(condition.to_owned(), system.to_string()).into())
+    }).map_err(|e| e.offset(offset)))
+
+}
+
+// Attempts to parse a constant, with the name and the value following
+// the option name after whitespace, such as "!set BASE=0x10", or
+// a parameter declared the same way, such as "!param CL=0x22",
+// returns nothing if the line does not set a constant, or otherwise
+// the name, the value, and whether it is a parameter
+pub fn parse_constant(line: &str, consts: &[(String, usize)])
+    -> Option<Result<(String, usize, bool), ScriptError>> {
+
+    // Tell a parameter from a fixed constant
+    let ((offset, rest), param) = match strip_directive(line, config::OPT_INPUT_SET) {
+        Some(rest) => (rest, false),
+        None => (strip_directive(line, config::OPT_INPUT_PARAM)?, true),
+    };
+
+    // Split into the name and the value
+    let Some((name, value)) = rest.split_once(config::CHAR_ARG_ASS) else {
+        return Some(Err(ScriptError::InputConst(line.to_owned())));
+    };
+
+    // Check the name, which must not look like a number
+    let name = name.trim();
+    if !is_constant_name(name) {
+        return Some(Err(ScriptError::InputConst(line.to_owned())));
+    }
+
+    // Evaluate the value, which may refer to the constants set before
+    let value = value.trim();
+    Some(parse_value(value, consts)
+        .map(|value| (name.to_owned(), value, param))
+        .map_err(|e| e.offset(offset + offset_in(rest, value))))
+
+}
+
+// Conditions
+
+// System properties in conditions
+const COND_KEYS: [(&str, ConditionKey); 8] = [
+    (config::COND_KEY_BIOS, ConditionKey::BiosVersion),
+    (config::COND_KEY_BIOS_DATE, ConditionKey::BiosDate),
+    (config::COND_KEY_BOARD, ConditionKey::BoardProduct),
+    (config::COND_KEY_MANUFACTURER, ConditionKey::SystemManufacturer),
+    (config::COND_KEY_PRODUCT, ConditionKey::SystemProduct),
+    (config::COND_KEY_REVISION, ConditionKey::FirmwareRevision),
+    (config::COND_KEY_UEFI, ConditionKey::UefiRevision),
+    (config::COND_KEY_VENDOR, ConditionKey::FirmwareVendor),
+];
+
+// Comparison operators in conditions, each one
+// ahead of any other that is its prefix
+const COND_OPERATORS: [(&str, ConditionOperator); 7] = [
+    (config::COND_GE, ConditionOperator::GreaterEqual),
+    (config::COND_LE, ConditionOperator::LessEqual),
+    (config::COND_NE, ConditionOperator::NotEqual),
+    (config::COND_EQ_ALT, ConditionOperator::Equal),
+    (config::COND_EQ, ConditionOperator::Equal),
+    (config::COND_GT, ConditionOperator::Greater),
+    (config::COND_LT, ConditionOperator::Less),
+];
+
+// Attempts to parse a condition, such as vendor="American Megatrends"
+// and revision>=5.24, comparing the system properties with values,
+// or checking the variables, such as Setup:0x10==1, with the constants
+// and the definitions in the scope given, all combined with and, or, not
+// and brackets, whitespace allowed in between
+pub fn parse_condition<S: ConditionScope>(condition: &str,
+    scope: &S) -> Result<Condition<S::Target>, S::Error> {
+
+    // Parse the whole condition
+    let mut rest = condition;
+    let result = parse_condition_any(condition, &mut rest, scope)?;
+
+    // Nothing must be left
+    if !rest.trim().is_empty() {
+        return Err(condition_error(condition, rest.trim()).into());
+    }
+
+    // Return the result
+    Ok(result)
+
+}
+
+// Attempts to parse the name of a definition alone, given to an ifdef,
+// as a condition that holds if the definition was made
+pub fn parse_condition_defined_name<S: ConditionScope>(name: &str,
+    scope: &S) -> Result<Condition<S::Target>, S::Error> {
+
+    // Parse the name
+    let mut rest = name;
+    let result = parse_condition_defined(name, &mut rest, scope)?;
+
+    // Nothing must be left
+    if !rest.trim().is_empty() {
+        return Err(condition_error(name, rest.trim()).into());
+    }
+
+    // Return the result
+    Ok(result)
+
+}
+
+// Parses the conditions joined with or, any of which has to hold,
+// each of them possibly joined with and, advancing through the text
+fn parse_condition_any<S: ConditionScope>(condition: &str, rest: &mut &str,
+    scope: &S) -> Result<Condition<S::Target>, S::Error> {
+
+    // Collect the conditions, as long as they are joined
+    let mut conditions = vec![parse_condition_all(condition, rest, scope)?];
+    while strip_condition_word(rest, config::COND_OR) {
+        conditions.push(parse_condition_all(condition, rest, scope)?);
+    }
+
+    // Return a single one as is
+    Ok(if conditions.len() == 1 { conditions.remove(0) } else { Condition::Any(conditions) })
+
+}
+
+// Parses the conditions joined with and, all of which
+// have to hold, advancing through the rest of the text
+fn parse_condition_all<S: ConditionScope>(condition: &str, rest: &mut &str,
+    scope: &S) -> Result<Condition<S::Target>, S::Error> {
+
+    // Collect the conditions, as long as they are joined
+    let mut conditions = vec![parse_condition_term(condition, rest, scope)?];
+    while strip_condition_word(rest, config::COND_AND) {
+        conditions.push(parse_condition_term(condition, rest, scope)?);
+    }
+
+    // Return a single one as is
+    Ok(if conditions.len() == 1 { conditions.remove(0) } else { Condition::All(conditions) })
+
+}
+
+// Parses a single condition: a negated one, one in brackets, whether
+// a definition was made, whether a variable is there, a comparison
+// of a variable, or of a property, advancing through the rest of the text
+fn parse_condition_term<S: ConditionScope>(condition: &str, rest: &mut &str,
+    scope: &S) -> Result<Condition<S::Target>, S::Error> {
+
+    // Negated condition
+    if strip_condition_word(rest, config::COND_NOT) {
+        return Ok(Condition::Not(Box::new(parse_condition_term(condition, rest, scope)?)));
+    }
+
+    // Condition in brackets
+    *rest = rest.trim_start();
+    if let Some(inner) = rest.strip_prefix(config::CHAR_ARG_BKT_L) {
+        *rest = inner;
+        let inner = parse_condition_any(condition, rest, scope)?;
+        let Some(remaining) = rest.trim_start().strip_prefix(config::CHAR_ARG_BKT_R) else {
+            return Err(condition_error(condition, rest.trim()).into());
+        };
+        *rest = remaining;
+        return Ok(inner);
+    }
+
+    // Definition made before, known right away
+    if strip_condition_word(rest, config::COND_DEFINED) {
+        return parse_condition_defined(condition, rest, scope);
+    }
+
+    // Variable, with an optional vendor GUID in braces to tell namesakes
+    if strip_condition_word(rest, config::COND_EXISTS) {
+        let name = strip_condition_name(condition, rest, &[config::CHAR_COND_GUID_L])?;
+        let vendor = match rest.strip_prefix(config::CHAR_COND_GUID_L) {
+            Some(inner) => {
+                let Some((guid, remaining)) = inner.split_once(config::CHAR_COND_GUID_R) else {
+                    return Err(condition_error(condition, rest.trim()).into());
+                };
+                *rest = remaining;
+                Some(parse_guid(guid.trim()).ok_or_else(|| condition_error(condition, guid))?)
+            }
+            None => None,
+        };
+        return Ok(Condition::Exists { name: name.to_owned(), vendor });
+    }
+
+    // Value of a variable
+    if let Some(value) = parse_condition_value(condition, rest, scope)? {
+        return Ok(value);
+    }
+
+    // Property, by its name
+    let length = rest.find(|c| !is_constant_char(c)).unwrap_or(rest.len());
+    let (name, remaining) = rest.split_at(length);
+    if name.is_empty() {
+        return Err(condition_error(condition, rest.trim()).into());
+    }
+    let Some(&(_, key)) = COND_KEYS.iter().find(|(key, _)| *key == name) else {
+        return Err(ScriptError::InputCondKey(name.to_owned()).offset(offset_in(condition, name)).into());
+    };
+    *rest = remaining.trim_start();
+
+    // Operator, with the text only compared for equality
+    let Some(&(symbol, operator)) = COND_OPERATORS.iter().find(|(symbol, _)| rest.starts_with(symbol)) else {
+        return Err(condition_error(condition, rest.trim()).into());
+    };
+    if !key.is_version() && !matches!(operator, ConditionOperator::Equal | ConditionOperator::NotEqual) {
+        return Err(ScriptError::InputCondText(symbol.to_owned()).offset(offset_in(condition, rest)).into());
+    }
+    *rest = rest[symbol.len() ..].trim_start();
+
+    // Value, either in quotes, or up to whitespace or a closing bracket
+    let value = if let Some(quoted) = rest.strip_prefix(config::CHAR_COND_QUOTE) {
+        let Some((value, remaining)) = quoted.split_once(config::CHAR_COND_QUOTE) else {
+            return Err(condition_error(condition, rest.trim()).into());
+        };
+        *rest = remaining;
+        value
+    } else {
+        let length = rest.find(|c: char| c.is_whitespace() || c == config::CHAR_ARG_BKT_R)
+            .unwrap_or(rest.len());
+        let (value, remaining) = rest.split_at(length);
+        if value.is_empty() {
+            return Err(condition_error(condition, rest.trim()).into());
+        }
+        *rest = remaining;
+        value
+    };
+
+    // Check a version, comparing it with itself
+    let value = if key.is_version() {
+        if compare_versions(value, value).is_none() {
+            return Err(ScriptError::InputCondVersion(value.to_owned()).offset(offset_in(condition, value)).into());
+        }
+        ConditionValue::Version(value.to_owned())
+    } else {
+        ConditionValue::Text(value.to_owned())
+    };
+
+    // Return the comparison
+    Ok(Condition::Compare { key, operator, value })
+
+}
+
+// Attempts to parse a comparison of the current value of a variable
+// with a number, the variable given either by a reference, such as
+// "@CState>=2", or the same way as in an operation, such as "Setup:0x10==1",
+// advancing through the rest of the text, returns nothing if the text
+// starts with neither of them, and so is possibly a property instead
+fn parse_condition_value<S: ConditionScope>(condition: &str, rest: &mut &str,
+    scope: &S) -> Result<Option<Condition<S::Target>>, S::Error> {
+
+    // Find the operator, skipping the shifts
+    // in an expression that makes up the offset
+    let text = *rest;
+    let mut found = None;
+    let mut chars = text.char_indices();
+    while let Some((index, _)) = chars.next() {
+        let remaining = &text[index ..];
+        if remaining.starts_with(config::EXPR_SHL) || remaining.starts_with(config::EXPR_SHR) {
+            chars.next();
+        } else if let Some(&(symbol, operator)) = COND_OPERATORS.iter()
+            .find(|(symbol, _)| remaining.starts_with(symbol)) {
+            found = Some((index, symbol, operator));
+            break;
+        }
+    }
+
+    // Tell a variable from a property, which has neither
+    // the reference prefix, nor the offset indicator
+    let target = text[.. found.map_or(text.len(), |(index, ..)| index)].trim();
+    if !target.starts_with(config::CHAR_INPUT_REF) && !target.contains(config::CHAR_ARG_POS) {
+        return Ok(None);
+    }
+
+    // Either has to be compared with something
+    let Some((index, symbol, operator)) = found else {
+        return Err(condition_error(condition, text.trim()).into());
+    };
+
+    // Resolve the variable, with the constants usable in the number
+    let (target, consts) = scope.target(target).map_err(|e| e.offset(offset_in(condition, target)))?;
+
+    // Number, up to whitespace or a closing bracket, other than within
+    // the brackets that are a part of the expression making it up
+    let value = text[index + symbol.len() ..].trim_start();
+    let mut depth = 0usize;
+    let length = value.find(|c: char| match c {
+        config::CHAR_ARG_BKT_L => { depth += 1; false },
+        config::CHAR_ARG_BKT_R if depth > 0 => { depth -= 1; false },
+        config::CHAR_ARG_BKT_R => true,
+        c => c.is_whitespace() && depth == 0,
+    }).unwrap_or(value.len());
+    let (value, remaining) = value.split_at(length);
+    if value.is_empty() {
+        return Err(condition_error(condition, text.trim()).into());
+    }
+    *rest = remaining;
+
+    // Return the comparison
+    let value = parse_value(value, &consts).map_err(|e| e.offset(offset_in(condition, value)))?;
+    Ok(Some(Condition::Value { target, operator, value }))
+
+}
+
+// Parses the name of a definition following the keyword, as a condition
+// that holds if the definition was made, advancing through the rest of the text
+fn parse_condition_defined<S: ConditionScope>(condition: &str, rest: &mut &str,
+    scope: &S) -> Result<Condition<S::Target>, S::Error> {
+
+    // Look the name up in the definitions made so far
+    let name = strip_condition_name(condition, rest, &[])?;
+    Ok(Condition::Defined(scope.is_defined(name)))
+
+}
+
+// Reports a malformed condition, at the part of it given,
+// which is a slice taken from the condition itself
+fn condition_error(condition: &str, part: &str) -> ScriptError {
+    ScriptError::InputCond(part.to_owned()).offset(offset_in(condition, part))
+}
+
+// Attempts to remove a name following a keyword, such as that of a definition,
+// from the start of the text, up to whitespace, a closing bracket, or any
+// of the other characters given, returning it, which must not be empty
+fn strip_condition_name<'a>(condition: &str, rest: &mut &'a str,
+    stop: &[char]) -> Result<&'a str, ScriptError> {
+
+    // Take the name
+    *rest = rest.trim_start();
+    let length = rest.find(|c: char| c.is_whitespace() || c == config::CHAR_ARG_BKT_R || stop.contains(&c))
+        .unwrap_or(rest.len());
+    let (name, remaining) = rest.split_at(length);
+    if name.is_empty() {
+        return Err(condition_error(condition, rest.trim()));
+    }
+
+    // Return it
+    *rest = remaining;
+    Ok(name)
+
+}
+
+// Attempts to remove a keyword such as "and" from the start of the text,
+// after any whitespace, unless it is only the start of a longer word
+fn strip_condition_word(rest: &mut &str, word: &str) -> bool {
+    match rest.trim_start().strip_prefix(word) {
+        Some(remaining) if !remaining.starts_with(is_constant_char) => {
+            *rest = remaining;
+            true
+        },
+        _ => false,
+    }
+}
+
+// Definitions
+
+// Attempts to parse the names of the values of a definition in braces,
+// each followed by the value, which may refer to the constants given,
+// and has to fit within the size of the target, in bytes
+pub fn parse_target_def_values(values: &str, consts: &[(String, usize)], size: usize)
+    -> Result<Vec<(String, usize)>, ScriptError> {
+
+    // Remove the braces
+    let error = || ScriptError::InputDefValues(values.to_owned());
+    let inner = values.strip_prefix(config::CHAR_INPUT_ENUM_L)
+        .and_then(|inner| inner.strip_suffix(config::CHAR_INPUT_ENUM_R)).ok_or_else(error)?;
+
+    // Split into the names and the values, each name
+    // being the same as that of a constant
+    inner.split(config::CHAR_INPUT_ENUM).map(|value| {
+        let (name, value) = value.split_once(config::CHAR_ARG_ASS).ok_or_else(error)?;
+        let name = name.trim();
+        if !is_constant_name(name) {
+            return Err(error());
+        }
+        let value = value.trim();
+        let offset = offset_in(values, value);
+        let number = parse_value(value, consts).map_err(|e| e.offset(offset))?;
+        if !fits_size(number, size) {
+            return Err(ScriptError::ArgSizeMismatch(number, size).offset(offset));
+        }
+        Ok((name.to_owned(), number))
+    }).collect()
+
+}
+
+// Attempts to parse the name of a structure and its fields in braces,
+// separated by whitespace, each with a name, an offset and optionally
+// a size, same as a target, which may refer to the constants given
+pub fn parse_layout(layout: &str, consts: &[(String, usize)]) -> Result<TargetLayout, ScriptError> {
+
+    // Split into the name and the fields, removing the braces
+    let error = || ScriptError::InputStruct(layout.to_owned());
+    let (name, fields) = layout.split_once(config::CHAR_INPUT_ENUM_L).ok_or_else(error)?;
+    let fields = fields.trim_end().strip_suffix(config::CHAR_INPUT_ENUM_R).ok_or_else(error)?;
+
+    // Check the name, which is the same as that of a constant
+    let name = name.trim();
+    if !is_constant_name(name) {
+        return Err(error());
+    }
+
+    // Parse each field the same way as a target, its name, which cannot be
+    // given twice, in place of that of the variable, before the offset
+    let mut parsed: Vec<(String, usize, usize)> = Vec::new();
+    for field in fields.split(|c: char| c.is_whitespace() || c == config::CHAR_INPUT_ENUM)
+        .filter(|field| !field.is_empty()) {
+        let (field_name, offset) = field.split_once(config::CHAR_ARG_POS).ok_or_else(error)?;
+        if !is_constant_name(field_name) || parsed.iter().any(|(name, ..)| name == field_name) {
+            return Err(error());
+        }
+        let (offset, size) = parse_offset(offset, consts).map_err(|e| e.offset(offset_in(layout, offset)))?;
+        parsed.push((field_name.to_owned(), offset, size));
+    }
+
+    // There must be at least one field
+    if parsed.is_empty() {
+        return Err(error());
+    }
+
+    // Return the structure
+    Ok(TargetLayout { name: name.to_owned(), fields: parsed })
+
+}
+
+// Attempts to parse the structure a definition is bound to, which has to be
+// declared among those given, and the number of elements in brackets, if it
+// is an array, either of them optional, but not both, such as "Timing[4]",
+// "Timing" or "[4]", the number possibly referring to the constants given
+pub fn parse_target_def_layout(layout: &str, layouts: &[TargetLayout], consts: &[(String, usize)])
+    -> Result<(Option<TargetLayout>, Option<usize>), ScriptError> {
+
+    // Split into the name and the number of elements, removing the brackets
+    let error = || ScriptError::InputDef(layout.trim().to_owned()).offset(offset_in(layout, layout.trim()));
+    let (name, count) = match layout.split_once(config::CHAR_INPUT_INDEX_L) {
+        Some((name, count)) => (name.trim(), Some(count.trim_end()
+            .strip_suffix(config::CHAR_INPUT_INDEX_R).ok_or_else(error)?)),
+        None => (layout.trim(), None),
+    };
+
+    // Find the structure, the last one declared under the name
+    let found = match name {
+        "" => None,
+        name => Some(layouts.iter().rev().find(|layout| layout.name == name).cloned()
+            .ok_or_else(|| ScriptError::InputStructNone(name.to_owned()).offset(offset_in(layout, name)))?),
+    };
+
+    // Evaluate the number of elements, which may refer to the constants
+    let count = match count {
+        Some(count) => Some(parse_value(count.trim(), consts)
+            .map_err(|e| e.offset(offset_in(layout, count.trim())))?),
+        None => None,
+    };
+
+    // Either has to be given, and an array cannot be empty
+    if (found.is_none() && count.is_none()) || count == Some(0) {
+        return Err(error());
+    }
+
+    // Return the structure and the number of elements
+    Ok((found, count))
+
+}
+
+// Attempts to split a reference into the name of the definition, and the element
+// in brackets and the field after a dot, such as "Ch0[2].tRAS", either of them
+// optional, with the index possibly referring to the constants given
+pub fn parse_target_path<'a>(arg: &'a str, consts: &[(String, usize)])
+    -> Result<(&'a str, TargetPath), ScriptError> {
+
+    // Split off the name
+    let error = || ScriptError::InputRef(arg.trim().to_owned()).offset(offset_in(arg, arg.trim()));
+    let whole = arg;
+    let arg = arg.trim();
+    let (name, mut rest) = arg.split_at(arg.find([config::CHAR_INPUT_INDEX_L, config::CHAR_INPUT_FIELD])
+        .unwrap_or(arg.len()));
+    let mut path = TargetPath::default();
+
+    // Evaluate the index, if any
+    if let Some(inner) = rest.strip_prefix(config::CHAR_INPUT_INDEX_L) {
+        let (index, remaining) = inner.split_once(config::CHAR_INPUT_INDEX_R).ok_or_else(error)?;
+        path.index = Some(parse_value(index.trim(), consts)
+            .map_err(|e| e.offset(offset_in(whole, index.trim())))?);
+        rest = remaining;
+    }
+
+    // Take the field, if any, named the same way as a constant
+    if let Some(field) = rest.strip_prefix(config::CHAR_INPUT_FIELD) {
+        if !is_constant_name(field) {
+            return Err(error());
+        }
+        path.field = Some(field.to_owned());
+        rest = "";
+    }
+
+    // Nothing must be left, and there has to be a name
+    if name.trim().is_empty() || !rest.is_empty() {
+        return Err(error());
+    }
+
+    // Return the name and the path
+    Ok((name.trim(), path))
+
+}
+
+// Constants to use in a value assigned to a definition, or compared with
+// it: those given, and the names of its values, which take precedence,
+// same as a constant set later
+pub fn with_values(consts: &[(String, usize)], values: &[(String, usize)]) -> Vec<(String, usize)> {
+    [consts, values].concat()
+}
+
+// Finds the name of a value of a definition, if it has one
+pub fn value_name(values: &[(String, usize)], value: usize) -> Option<&str> {
+    values.iter().find(|(_, named)| *named == value).map(|(name, _)| name.as_str())
+}
+
+// Checks whether a value fits within the size given, in bytes
+// Note: nothing to check at the size limit, where
+// the value takes all the bits available anyway
+pub fn fits_size(value: usize, size: usize) -> bool {
+    size.checked_mul(8).and_then(|bits| u32::try_from(bits).ok()).and_then(|bits| 1usize.checked_shl(bits))
+        .is_none_or(|limit| value < limit)
+}
+
+// Expressions
+
+// Binary operators in expressions, grouped by the precedence,
+// from the lowest to the highest, the same as in C, each giving
+// nothing if out of range, which for a left shift includes
+// dropping any bits, as shifting back would not restore them
+type Operator = (&'static str, fn(usize, usize) -> Option<usize>);
+const EXPR_OPERATORS: [&[Operator]; 5] = [
+    &[(config::EXPR_OR, |a, b| Some(a | b))],
+    &[(config::EXPR_AND, |a, b| Some(a & b))],
+    &[(config::EXPR_SHL, |a, b| {
+            let shift = u32::try_from(b).ok()?;
+            a.checked_shl(shift).filter(|result| result.checked_shr(shift) == Some(a))
+        }),
+        (config::EXPR_SHR, |a, b| a.checked_shr(u32::try_from(b).ok()?))],
+    &[(config::EXPR_ADD, usize::checked_add), (config::EXPR_SUB, usize::checked_sub)],
+    &[(config::EXPR_MUL, usize::checked_mul)],
+];
+
+// Attempts to parse a value, which can be a decimal or a hexadecimal
+// number, the name of a constant, or an expression combining them
+// with the operators above and brackets, whitespace allowed in between
+pub fn parse_value(value: &str, consts: &[(String, usize)]) -> Result<usize, ScriptError> {
+
+    // Evaluate the whole expression
+    let mut rest = value;
+    let result = parse_expression(value, &mut rest, 0, consts)?;
+
+    // Nothing must be left
+    if !rest.trim_start().is_empty() {
+        return Err(ScriptError::ArgExpr(value.to_owned()));
+    }
+
+    // Return the result
+    Ok(result)
+
+}
+
+// Evaluates an expression from the operators at the given
+// precedence level up, advancing through the rest of the text
+fn parse_expression(value: &str, rest: &mut &str, level: usize,
+    consts: &[(String, usize)]) -> Result<usize, ScriptError> {
+
+    // Past the operators, evaluate a single operand
+    let Some(operators) = EXPR_OPERATORS.get(level) else {
+        return parse_operand(value, rest, consts);
+    };
+
+    // Evaluate the left-hand side
+    let mut result = parse_expression(value, rest, level + 1, consts)?;
+
+    // Apply the operators at this level as long as there are any,
+    // from left to right, evaluating each right-hand side in turn
+    loop {
+        *rest = rest.trim_start();
+        let Some((symbol, apply)) = operators.iter().find(|(symbol, _)| rest.starts_with(symbol)) else {
+            return Ok(result);
+        };
+        *rest = &rest[symbol.len() ..];
+        let operand = parse_expression(value, rest, level + 1, consts)?;
+        result = apply(result, operand).ok_or_else(|| ScriptError::ArgExprRange(value.to_owned()))?;
+    }
+
+}
+
+// Evaluates a single operand: an expression in brackets,
+// a number, or a constant, advancing through the rest of the text
+fn parse_operand(value: &str, rest: &mut &str,
+    consts: &[(String, usize)]) -> Result<usize, ScriptError> {
+
+    // Expression in brackets
+    *rest = rest.trim_start();
+    if let Some(inner) = rest.strip_prefix(config::CHAR_ARG_BKT_L) {
+        *rest = inner;
+        let result = parse_expression(value, rest, 0, consts)?;
+        *rest = rest.trim_start().strip_prefix(config::CHAR_ARG_BKT_R)
+            .ok_or_else(|| ScriptError::ArgExpr(value.to_owned()))?;
+        return Ok(result);
+    }
+
+    // Take the number or the name
+    let length = rest.find(|c| !is_constant_char(c)).unwrap_or(rest.len());
+    let (token, remaining) = rest.split_at(length);
+    *rest = remaining;
+
+    // Parse a number as either a decimal or a hexadecimal one, by its prefix
+    if token.starts_with(|c: char| c.is_ascii_digit()) {
+        return if token.to_ascii_lowercase().starts_with("0x") {
+            parse_value_hex(token)
+        } else {
+            parse_value_dec(token)
+        }.map_err(|e| e.offset(offset_in(value, token)));
+    }
+
+    // Otherwise look up the constant, the last one set under the name
+    if token.is_empty() {
+        return Err(ScriptError::ArgExpr(value.to_owned()));
+    }
+    consts.iter().rev().find(|(name, _)| name == token).map(|&(_, value)| value)
+        .ok_or_else(|| ScriptError::ArgConst(token.to_owned()).offset(offset_in(value, token)))
+
+}
+
+// Checks if a character can be a part of a number or a constant name
+pub fn is_constant_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == config::CHAR_EXPR_NAME
+}
+
+// Checks if a constant name is valid, made up of letters, digits
+// and underscores, and not starting with a digit, unlike a number
+pub fn is_constant_name(name: &str) -> bool {
+    name.chars().all(is_constant_char) && name.starts_with(|c: char| !c.is_ascii_digit())
+}
+
+// Attempts to parse an offset, followed by the size in brackets, if any,
+// defaulting to a byte (1), either of them possibly an expression
+// referring to the constants given
+pub fn parse_offset(offset: &str, consts: &[(String, usize)]) -> Result<(usize, usize), ScriptError> {
+
+    // Determine the value size, which has to fit in a single operation
+    let (offset_value, size) = match split_size(offset)? {
+        Some((offset_value, size)) => {
+            let value = parse_value(size, consts).map_err(|e| e.offset(offset_in(offset, size)))?;
+            if value == 0 || value > config::VALUE_SIZE_MAX {
+                return Err(ScriptError::ArgSize(value).offset(offset_in(offset, size)));
+            }
+            (offset_value, value)
+        }
+        None => (offset, 1),
+    };
+
+    // Parse the offset value
+    Ok((parse_value(offset_value, consts)?, size))
+
+}
+
+// Splits the size in brackets from the end of an offset, if present, with
+// the offset itself possibly having brackets as a part of an expression:
+// the size is the last bracketed part, if it follows a number, a name
+// or a closing bracket, as an operator would come in between otherwise
+pub fn split_size(offset: &str) -> Result<Option<(&str, &str)>, ScriptError> {
+
+    // Check that the brackets are balanced
+    let depth = offset.chars().try_fold(0usize, |depth, c| match c {
+        config::CHAR_ARG_BKT_L => Some(depth + 1),
+        config::CHAR_ARG_BKT_R => depth.checked_sub(1),
+        _ => Some(depth),
+    }).ok_or(ScriptError::ArgPosBktL)?;
+    if depth > 0 {
+        return Err(ScriptError::ArgPosBktR);
+    }
+
+    // Nothing to split unless ending with a bracket
+    let Some(inner) = offset.strip_suffix(config::CHAR_ARG_BKT_R) else {
+        return Ok(None);
+    };
+
+    // Find the matching opening bracket, going backwards
+    let mut depth = 0;
+    let start = inner.char_indices().rev().find(|&(_, c)| {
+        match c {
+            config::CHAR_ARG_BKT_R => depth += 1,
+            config::CHAR_ARG_BKT_L if depth == 0 => return true,
+            config::CHAR_ARG_BKT_L => depth -= 1,
+            _ => {}
+        }
+        false
+    }).map(|(index, _)| index).unwrap();
+
+    // Split if preceded by an operand, which can only be the offset
+    let (before, size) = (inner[.. start].trim_end(), &inner[start + 1 ..]);
+    if !before.ends_with(|c: char| is_constant_char(c) || c == config::CHAR_ARG_BKT_R) {
+        return Ok(None);
+    }
+
+    // Only a single size can be given
+    if split_size(before)?.is_some() {
+        return Err(ScriptError::ArgPosBktL);
+    }
+
+    // Return the offset and the size
+    Ok(Some((before, size)))
+
+}
+
+// Numbers
+
+// Attempts to parse a decimal value
+pub fn parse_value_dec(value: &str) -> Result<usize, ScriptError> {
+
+    // Only ASCII digits are allowed in a decimal value
+    if value.chars().any(|c| !c.is_ascii_digit()) {
+        Err(ScriptError::ArgNumDec(format!("\"{value}\"")))?
+    }
+
+    // Iterate through the characters:
+    // Take the ASCII value of each (48-57), and deduct the value of 0 (48)
+    // Multiply the accumulator by 10 at each step and add to the total,
+    // as long as it fits
+    value.bytes()
+        .try_fold(0usize, |acc, n| acc.checked_mul(10)?.checked_add((n - b'0') as usize))
+        .ok_or_else(|| ScriptError::ArgSizeLimit(value.to_owned()))
+
+}
+
+// Attempts to parse a hexadecimal value
+pub fn parse_value_hex(value: &str) -> Result<usize, ScriptError> {
+
+    // Define an iterator over the input string
+    let mut str_iter = value.chars();
+
+    // Retrieve the initial two characters of the string
+    let mut next = || str_iter.next().ok_or_else(|| ScriptError::ArgMore(value.to_owned()));
+    let (c0, c1) = (next()?, next()?);
+
+    // Check the prefix
+    match (c0, c1) {
+
+        // The hexadecimal
+        // prefix is present
+        ('0', 'x') => {}
+        ('0', 'X') => {}
+
+        // Report an error in hexadecimal string formatting
+        _ => return Err(ScriptError::ArgNumHexPrefix(format!("\"{value}\""))),
+
+    }
+
+    // Check if within the size constraint
+    if value.chars().count() > 18 {
+        return Err(ScriptError::ArgSizeLimit(value.to_owned()));
+    }
+
+    // Convert each byte (char) to its hexadecimal value
+    let value = str_iter
+        .map(|c| c.to_digit(16).map(|n| n as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| ScriptError::ArgNumHex(format!("\"{value}\"")))?;
+
+    // Sum all the values, multiplied according their respective positions
+    let length = value.len();
+    let value = value.iter().enumerate().fold(0usize, |acc, (i, &n)| {
+        acc + ((n as usize) << (4 * (length - i - 1)))
+    });
+
+    // Return
+    Ok(value)
+
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Error at the offset given
+    fn at(offset: usize, e: ScriptError) -> ScriptError {
+        ScriptError::InputOffset(offset, Box::new(e))
+    }
+
+    // Constants the expressions below can refer to
+    fn consts() -> Vec<(String, usize)> {
+        vec![("BASE".to_owned(), 0x100), ("CL".to_owned(), 1), ("CL".to_owned(), 22)]
+    }
+
+    // Scope with a single definition, with the names of its values, the
+    // variables compared kept as written, and the constants above usable
+    // with them, along with the names of the values when referring to it
+    struct TestScope(Vec<(String, usize)>);
+    impl ConditionScope for TestScope {
+        type Target = String;
+        type Error = ScriptError;
+        fn is_defined(&self, name: &str) -> bool {
+            name == "CState"
+        }
+        fn target(&self, target: &str) -> Result<ConditionTarget<String>, ScriptError> {
+            Ok((target.to_owned(), match target.starts_with("@CState") {
+                true => with_values(&self.0, &values()),
+                false => self.0.clone(),
+            }))
+        }
+    }
+
+    // Names of the values of the definition above, one shadowing a constant
+    fn values() -> Vec<(String, usize)> {
+        vec![("Disabled".to_owned(), 0), ("Enabled".to_owned(), 1), ("CL".to_owned(), 5)]
+    }
+
+    // System the conditions are checked against
+    fn system() -> SystemInfo {
+        SystemInfo {
+            firmware_revision: "5.24".to_owned(),
+            firmware_vendor: "American Megatrends".to_owned(),
+            smbios: Some(SmbiosInfo {
+                bios_date: "09/12/2023".to_owned(),
+                bios_version: "1402".to_owned(),
+                board_product: "PRIME Z790-P".to_owned(),
+                system_manufacturer: "ASUSTeK COMPUTER INC.".to_owned(),
+                system_product: "System Product Name".to_owned(),
+            }),
+            uefi_revision: "2.8".to_owned(),
+        }
+    }
+
+    // Runs the lines through the block directives, returning the other
+    // lines used, along with the directives kept for when running,
+    // the errors by the line number, and the blocks left open
+    fn blocks<'a>(lines: &[&'a str]) -> (Vec<&'a str>, Vec<(usize, ScriptError)>, usize) {
+        let (mut used, mut errors, mut blocks) = (Vec::new(), Vec::new(), Vec::new());
+        for (number, &line) in lines.iter().enumerate() {
+            match parse_block_directive(line, number + 1, 0, line, &mut blocks, &TestScope(consts()), &system()) {
+                Some(Ok(Some(_))) => used.push(line),
+                Some(Ok(None)) => {}
+                Some(Err(e)) => errors.push((number + 1, e)),
+                None if blocks.iter().all(InputBlock::is_active) => used.push(line),
+                None => {}
+            }
+        }
+        (used, errors, blocks.len())
+    }
+
+    // Numbers, constants and operators, with the precedence
+    // as in C, and the results that would not fit reported
+    #[test]
+    fn expressions() {
+        for (value, expected) in [
+            ("16", Ok(16)),
+            ("0x10", Ok(0x10)),
+            ("0X1f", Ok(0x1F)),
+            ("1+2*3", Ok(7)),
+            ("(1+2)*3", Ok(9)),
+            (" 1 + ( 2 ) ", Ok(3)),
+            ("1<<4|1", Ok(17)),
+            ("0xF0&0x3C", Ok(0x30)),
+            ("0x100>>4+1", Ok(0x8)),
+            ("BASE+CL*2", Ok(0x100 + 44)),
+            ("1<<63", Ok(1 << 63)),
+            ("18446744073709551615", Ok(usize::MAX)),
+            ("2-3", Err(ScriptError::ArgExprRange("2-3".to_owned()))),
+            ("3<<63", Err(ScriptError::ArgExprRange("3<<63".to_owned()))),
+            ("0x8000000000000000<<1", Err(ScriptError::ArgExprRange("0x8000000000000000<<1".to_owned()))),
+            ("1<<64", Err(ScriptError::ArgExprRange("1<<64".to_owned()))),
+            ("0xFFFFFFFFFFFFFFFF*2", Err(ScriptError::ArgExprRange("0xFFFFFFFFFFFFFFFF*2".to_owned()))),
+            ("18446744073709551616", Err(at(0, ScriptError::ArgSizeLimit("18446744073709551616".to_owned())))),
+            ("0x12345678901234567", Err(at(0, ScriptError::ArgSizeLimit("0x12345678901234567".to_owned())))),
+            ("(1", Err(ScriptError::ArgExpr("(1".to_owned()))),
+            ("1 2", Err(ScriptError::ArgExpr("1 2".to_owned()))),
+            ("1+", Err(ScriptError::ArgExpr("1+".to_owned()))),
+            ("1 + TCL", Err(at(4, ScriptError::ArgConst("TCL".to_owned())))),
+            ("2*0x1G", Err(at(2, ScriptError::ArgNumHex("\"0x1G\"".to_owned())))),
+            ("12a", Err(at(0, ScriptError::ArgNumDec("\"12a\"".to_owned())))),
+        ] {
+            assert_eq!(parse_value(value, &consts()), expected, "{value}");
+        }
+    }
+
+    // Size in brackets at the end of an offset, told apart from the brackets
+    // that are a part of an expression, and limited to a single operation
+    #[test]
+    fn sizes() {
+        for (offset, expected) in [
+            ("0x10", Ok(None)),
+            ("0x10(2)", Ok(Some(("0x10", "2")))),
+            ("BASE*(1+2)", Ok(None)),
+            ("BASE*(1+2)(2)", Ok(Some(("BASE*(1+2)", "2")))),
+            ("(1+2)", Ok(None)),
+            ("0x10(2)(2)", Err(ScriptError::ArgPosBktL)),
+            ("0x10)", Err(ScriptError::ArgPosBktL)),
+            ("0x10(2", Err(ScriptError::ArgPosBktR)),
+        ] {
+            assert_eq!(split_size(offset), expected, "{offset}");
+        }
+        for (offset, expected) in [
+            ("BASE+1", Ok((0x101, 1))),
+            ("0x10(CL-14)", Ok((0x10, 8))),
+            ("0x10(9)", Err(at(5, ScriptError::ArgSize(9)))),
+            ("0x10(0)", Err(at(5, ScriptError::ArgSize(0)))),
+        ] {
+            assert_eq!(parse_offset(offset, &consts()), expected, "{offset}");
+        }
+    }
+
+    // Constants and parameters, with the values evaluated using
+    // those set before, and the lines that set neither skipped
+    #[test]
+    fn constants() {
+        let constant = |name: &str, value, param| Some(Ok((name.to_owned(), value, param)));
+        for (line, expected) in [
+            ("!set A=1", constant("A", 1, false)),
+            ("!param TCL = 0x22", constant("TCL", 0x22, true)),
+            ("!set\tNEXT=BASE+CL", constant("NEXT", 0x100 + 22, false)),
+            ("!set A_1=(1)", constant("A_1", 1, false)),
+            ("!set 1A=1", Some(Err(ScriptError::InputConst("!set 1A=1".to_owned())))),
+            ("!set A", Some(Err(ScriptError::InputConst("!set A".to_owned())))),
+            ("!param", Some(Err(ScriptError::InputConst("!param".to_owned())))),
+            ("!set A=1+TRP", Some(Err(at(9, ScriptError::ArgConst("TRP".to_owned()))))),
+            ("!set  A = 2-3", Some(Err(at(10, ScriptError::ArgExprRange("2-3".to_owned()))))),
+            ("!setA=1", None),
+            ("!settings", None),
+            ("Setup:0x10", None),
+        ] {
+            assert_eq!(parse_constant(line, &consts()), expected, "{line}");
+        }
+    }
+
+    // Properties of the system compared with text and versions,
+    // combined with and, or, not and brackets, and whether
+    // a definition was made, with any malformed part reported
+    #[test]
+    fn conditions() {
+        for (condition, expected) in [
+            ("vendor=\"American Megatrends\"", Ok(true)),
+            ("vendor==American", Ok(false)),
+            ("manufacturer=\"ASUSTeK COMPUTER INC.\" and bios!=1401", Ok(true)),
+            ("revision>=5.24 and uefi<2.10", Ok(true)),
+            ("revision>5.24 or not uefi!=2.8.0", Ok(true)),
+            ("(board=\"PRIME Z790-P\" or board=X) and bios_date=\"09/12/2023\"", Ok(true)),
+            ("not (product=\"System Product Name\")", Ok(false)),
+            ("defined CState and not defined Other", Ok(true)),
+            ("definedCState=1", Err(at(0, ScriptError::InputCondKey("definedCState".to_owned())))),
+            ("color=red", Err(at(0, ScriptError::InputCondKey("color".to_owned())))),
+            ("vendor>AMI", Err(at(6, ScriptError::InputCondText(">".to_owned())))),
+            ("revision>=5.x", Err(at(10, ScriptError::InputCondVersion("5.x".to_owned())))),
+            ("vendor=\"AMI", Err(at(7, ScriptError::InputCond("\"AMI".to_owned())))),
+            ("uefi=2.8 extra", Err(at(9, ScriptError::InputCond("extra".to_owned())))),
+            ("(uefi=2.8", Err(at(9, ScriptError::InputCond(String::new())))),
+            ("uefi=2.8 and", Err(at(12, ScriptError::InputCond(String::new())))),
+        ] {
+            assert_eq!(parse_condition(condition, &TestScope(consts())).map(|parsed| parsed.holds(&system())),
+                expected, "{condition}");
+        }
+    }
+
+    // Properties taken from the SMBIOS tables, which never match
+    // if there are none, the same as any malformed version
+    #[test]
+    fn conditions_without_tables() {
+        let system = SystemInfo { smbios: None, firmware_revision: "ALASKA".to_owned(), ..system() };
+        for (condition, expected) in [
+            ("board=\"PRIME Z790-P\"", false),
+            ("bios!=1402", true),
+            ("revision>=0", false),
+            ("revision<99", false),
+        ] {
+            let parsed = parse_condition(condition, &TestScope(consts())).unwrap();
+            assert_eq!(parsed.holds(&system), expected, "{condition}");
+        }
+    }
+
+    // Blocks used or skipped by their conditions, nested in one another,
+    // with an else at most once per block, and matched with an endif
+    #[test]
+    fn nesting() {
+        for (lines, used, errors, open) in [
+            (&["!if vendor=\"American Megatrends\"", "a", "!else", "b", "!endif", "c"][..],
+                &["a", "c"][..], vec![], 0),
+            (&["!if revision<5", "!if uefi>=2.8", "a", "!else", "b", "!endif", "!else", "c", "!endif"],
+                &["c"], vec![], 0),
+            (&["!if uefi>=2.8", "!if revision<5", "a", "!else", "b", "!endif", "!endif"], &["b"], vec![], 0),
+            (&["!ifdef CState", "a", "!endif", "!ifdef Other", "b", "!endif"], &["a"], vec![], 0),
+            (&["!if uefi=2.8.0", "a", "!else", "b", "!else", "c", "!endif"],
+                &["a"], vec![(5, ScriptError::InputElse)], 0),
+            (&["!endif", "a"], &["a"], vec![(1, ScriptError::InputEndif)], 0),
+            (&["!else"], &[], vec![(1, ScriptError::InputElse)], 0),
+            (&["!if uefi=2.8", "!else x", "!endif y"], &[], vec![
+                (2, ScriptError::InputOpt("!else x".to_owned())),
+                (3, ScriptError::InputOpt("!endif y".to_owned()))], 1),
+            (&["!if vendor~x", "a", "!endif"],
+                &[], vec![(1, at(10, ScriptError::InputCond("~x".to_owned())))], 0),
+            (&["!ifdef", "a", "!endif"], &[], vec![(1, at(6, ScriptError::InputCond(String::new())))], 0),
+            (&["!if uefi=2.8", "!iffy", "!endif", "!if uefi=2.9"], &["!iffy"], vec![], 1),
+        ] {
+            assert_eq!(blocks(lines), (used.to_vec(), errors, open), "{lines:?}");
+        }
+    }
+
+    // Requirements, which hold or stop the script, showing what
+    // the system is instead, and cannot check the variables
+    #[test]
+    fn requirements() {
+        for (line, expected) in [
+            ("!require uefi>=2.8", Some(Ok(()))),
+            ("!require vendor=Phoenix", Some(Err(at(9,
+                ScriptError::InputRequire("vendor=Phoenix".to_owned(), system().to_string()))))),
+            ("!require Setup:0x10==1", Some(Err(at(9, ScriptError::InputRequireValue)))),
+            ("!require", Some(Err(at(8, ScriptError::InputCond(String::new()))))),
+            ("!requirements", None),
+        ] {
+            assert_eq!(parse_require_directive(line, &TestScope(consts()), &system()), expected, "{line}");
+        }
+    }
+
+    // Variables checked when running, compared with a number, or given
+    // by name and optionally vendor GUID, combined with the properties
+    #[test]
+    fn deferred_conditions() {
+        let target = |target: &str, operator, value|
+            Condition::Value { target: target.to_owned(), operator, value };
+        let vendor = parse_guid("EC87D643-EBA4-4BB5-A1E5-3F3E36B20DA9");
+        for (condition, expected) in [
+            ("Setup:0x10==1", Ok(target("Setup:0x10", ConditionOperator::Equal, 1))),
+            ("@CState >= BASE+1", Ok(target("@CState", ConditionOperator::GreaterEqual, 0x101))),
+            ("Setup:(1<<4)(2)!=(0x10)", Ok(target("Setup:(1<<4)(2)", ConditionOperator::NotEqual, 0x10))),
+            ("exists Setup", Ok(Condition::Exists { name: "Setup".to_owned(), vendor: None })),
+            ("exists Setup{EC87D643-EBA4-4BB5-A1E5-3F3E36B20DA9}",
+                Ok(Condition::Exists { name: "Setup".to_owned(), vendor })),
+            ("(@CState<2) and uefi>=2.8", Ok(Condition::All(vec![
+                target("@CState", ConditionOperator::Less, 2),
+                Condition::Compare { key: ConditionKey::UefiRevision, operator: ConditionOperator::GreaterEqual,
+                    value: ConditionValue::Version("2.8".to_owned()) }]))),
+            ("Setup:0x10", Err(at(0, ScriptError::InputCond("Setup:0x10".to_owned())))),
+            ("Setup:0x10==", Err(at(0, ScriptError::InputCond("Setup:0x10==".to_owned())))),
+            ("@CState==X", Err(at(9, ScriptError::ArgConst("X".to_owned())))),
+            ("exists", Err(at(6, ScriptError::InputCond(String::new())))),
+            ("exists Setup{zz}", Err(at(13, ScriptError::InputCond("zz".to_owned())))),
+            ("exists Setup{", Err(at(12, ScriptError::InputCond("{".to_owned())))),
+        ] {
+            assert_eq!(parse_condition(condition, &TestScope(consts())), expected, "{condition}");
+        }
+    }
+
+    // Variables checked only as far as needed to tell the outcome, with
+    // the first error stopping, and taken not to hold if not checked
+    #[test]
+    fn evaluation() {
+        for (condition, checks, holds, evaluated) in [
+            ("uefi>=2.8 or Setup:0x10==1", true, true, (Ok(true), 0)),
+            ("uefi<2.8 or Setup:0x10==1", true, false, (Ok(true), 1)),
+            ("uefi<2.8 and exists Setup", true, false, (Ok(false), 0)),
+            ("not exists Setup", true, true, (Ok(false), 1)),
+            ("exists Setup and @Fail==1", true, false, (Err(()), 2)),
+            ("defined CState", false, true, (Ok(true), 0)),
+        ] {
+            let parsed = parse_condition(condition, &TestScope(consts())).unwrap();
+            let mut checked = 0;
+            let result = parsed.evaluate(&system(), &mut |term| {
+                checked += 1;
+                match term {
+                    Condition::Value { target, .. } if target == "@Fail" => Err(()),
+                    _ => Ok(true),
+                }
+            });
+            assert_eq!((parsed.checks_variables(), parsed.holds(&system()), (result, checked)),
+                (checks, holds, evaluated), "{condition}");
+        }
+    }
+
+    // Blocks checking the variables kept for when running, with all
+    // the lines in them used, and those around and within them
+    // known right away followed the same as anywhere else
+    #[test]
+    fn deferred_nesting() {
+        for (lines, used, errors) in [
+            (&["!if Setup:0x10==1", "a", "!else", "b", "!endif"][..],
+                &["!if Setup:0x10==1", "a", "!else", "b", "!endif"][..], vec![]),
+            (&["!if Setup:0x10==1", "!if uefi>=2.8", "a", "!else", "b", "!endif", "!endif"],
+                &["!if Setup:0x10==1", "a", "!endif"], vec![]),
+            (&["!if Setup:0x10==1", "!else", "!if revision<5", "a", "!else", "b", "!endif", "!endif"],
+                &["!if Setup:0x10==1", "!else", "b", "!endif"], vec![]),
+            (&["!if uefi<2.8", "!if Setup:0x10==1", "a", "!endif", "!endif"], &[], vec![]),
+            (&["!if uefi<2.8", "!else", "!if Setup:0x10==1", "a", "!else", "b", "!endif", "!endif"],
+                &["!if Setup:0x10==1", "a", "!else", "b", "!endif"], vec![]),
+            (&["!if exists Setup", "!if @CState==2", "a", "!endif", "!endif"],
+                &["!if exists Setup", "!if @CState==2", "a", "!endif", "!endif"], vec![]),
+            (&["!if Setup:0x10==1", "a", "!else", "b", "!else", "c", "!endif"],
+                &["!if Setup:0x10==1", "a", "!else", "b", "c", "!endif"], vec![(5, ScriptError::InputElse)]),
+        ] {
+            assert_eq!(blocks(lines), (used.to_vec(), errors, 0), "{lines:?}");
+        }
+    }
+
+    // Names of the values of a definition, each fitting within its size
+    #[test]
+    fn named_values() {
+        let named = |values: &[(&str, usize)]|
+            Ok(values.iter().map(|&(name, value)| (name.to_owned(), value)).collect());
+        for (values, size, expected) in [
+            ("{Disabled=0,Enabled=1}", 1, named(&[("Disabled", 0), ("Enabled", 1)])),
+            ("{ Off = 0 , Auto = BASE }", 2, named(&[("Off", 0), ("Auto", 0x100)])),
+            ("{Max=0xFFFFFFFFFFFFFFFF}", 8, named(&[("Max", usize::MAX)])),
+            ("{On=0x100}", 1, Err(at(4, ScriptError::ArgSizeMismatch(0x100, 1)))),
+            ("{A=1,B=X}", 1, Err(at(7, ScriptError::ArgConst("X".to_owned())))),
+            ("{A=0 B=1}", 1, Err(at(3, ScriptError::ArgExpr("0 B=1".to_owned())))),
+            ("{1st=1}", 1, Err(ScriptError::InputDefValues("{1st=1}".to_owned()))),
+            ("{A=1", 1, Err(ScriptError::InputDefValues("{A=1".to_owned()))),
+            ("{}", 1, Err(ScriptError::InputDefValues("{}".to_owned()))),
+        ] {
+            assert_eq!(parse_target_def_values(values, &consts(), size), expected, "{values}");
+        }
+        assert!(fits_size(0xFF, 1) && !fits_size(0x100, 1) && fits_size(usize::MAX, usize::MAX));
+    }
+
+    // Names of the values used in place of numbers, assigned or compared,
+    // taking precedence over the constants, and shown in the results
+    #[test]
+    fn named_value_use() {
+        let named = with_values(&consts(), &values());
+        for (value, expected) in [("Enabled", 1), ("CL+1", 6), ("BASE|Enabled", 0x101)] {
+            assert_eq!(parse_value(value, &named), Ok(expected), "{value}");
+        }
+        for (condition, expected) in [("@CState==Enabled", 1), ("Setup:0x10==CL", 22), ("@CState==CL", 5)] {
+            let Ok(Condition::Value { value, .. }) = parse_condition(condition, &TestScope(consts())) else {
+                panic!("{condition}");
+            };
+            assert_eq!(value, expected, "{condition}");
+        }
+        assert_eq!(value_name(&values(), 1), Some("Enabled"));
+        assert_eq!(value_name(&values(), 2), None);
+    }
+
+    // Structures with their fields, each with an offset and optionally
+    // a size, shown the same way as declared, with any malformed part,
+    // field given twice, or structure without fields reported
+    #[test]
+    fn layouts() {
+        let layout = parse_layout("Timing { tCL:0 tRCD:1, tRAS:CL(2) }", &consts()).unwrap();
+        let fields = [("tCL", 0, 1), ("tRCD", 1, 1), ("tRAS", 22, 2)];
+        assert_eq!(layout.name, "Timing");
+        assert_eq!(layout.fields, fields.map(|(name, offset, size)| (name.to_string(), offset, size)));
+        assert_eq!(layout.size(), 24);
+        assert_eq!(layout.to_string(), "Timing { tCL:0x0 tRCD:0x1 tRAS:0x16(2) }");
+        assert_eq!(parse_layout(&layout.to_string(), &[]), Ok(layout));
+        for layout in ["Timing tCL:0", "Timing { tCL:0", "{ tCL:0 }", "9T { tCL:0 }", "T { tCL }",
+            "T { tCL:0 tCL:1 }", "T { 9:0 }", "T { }"] {
+            assert_eq!(parse_layout(layout, &consts()), Err(ScriptError::InputStruct(layout.to_owned())));
+        }
+        assert_eq!(parse_layout("T { a:0 b:NOPE }", &consts()), Err(at(10, ScriptError::ArgConst("NOPE".into()))));
+        assert_eq!(parse_layout("T { a:0(NOPE) }", &consts()), Err(at(8, ScriptError::ArgConst("NOPE".into()))));
+        assert_eq!(parse_layout("T { a:0(16) }", &consts()), Err(at(8, ScriptError::ArgSize(16))));
+    }
+
+    // Structures bound to definitions, found by name, the last one declared
+    // winning, with the number of elements if an array, at least one
+    #[test]
+    fn def_layouts() {
+        let first = parse_layout("Timing { tCL:0 }", &[]).unwrap();
+        let last = parse_layout("Timing { tCL:0 tRAS:1 }", &[]).unwrap();
+        let layouts = [first, last.clone()];
+        let parse = |layout| parse_target_def_layout(layout, &layouts, &consts());
+        assert_eq!(parse("Timing[4]"), Ok((Some(last.clone()), Some(4))));
+        assert_eq!(parse(" Timing "), Ok((Some(last), None)));
+        assert_eq!(parse("[CL]"), Ok((None, Some(22))));
+        assert_eq!(parse("Nope[2]"), Err(at(0, ScriptError::InputStructNone("Nope".into()))));
+        assert_eq!(parse(" [NOPE]"), Err(at(2, ScriptError::ArgConst("NOPE".into()))));
+        for (layout, error) in [("[0]", "[0]"), ("", ""), (" Timing[2", "Timing[2")] {
+            assert_eq!(parse(layout), Err(at(offset_in(layout, layout.trim()),
+                ScriptError::InputDef(error.into()))), "{layout}");
+        }
+    }
+
+    // References split into the name of the definition, the element
+    // and the field, each optional, the index an expression
+    #[test]
+    fn paths() {
+        let path = |index: Option<usize>, field: Option<&str>| TargetPath { index, field: field.map(str::to_owned) };
+        for (arg, name, expected) in [("Ch", "Ch", path(None, None)),
+            (" Ch[2].tRAS", "Ch", path(Some(2), Some("tRAS"))), ("Ch[CL-20]", "Ch", path(Some(2), None)),
+            ("Ch.tCL", "Ch", path(None, Some("tCL")))] {
+            assert_eq!(parse_target_path(arg, &consts()), Ok((name, expected.clone())), "{arg}");
+            assert_eq!(format!("{name}{expected}"), arg.trim().replace("CL-20", "2"));
+        }
+        for arg in [" [2]", "Ch[2", "Ch.", "Ch.9", "Ch[2]x", "Ch.tCL[2]"] {
+            assert_eq!(parse_target_path(arg, &consts()),
+                Err(at(offset_in(arg, arg.trim()), ScriptError::InputRef(arg.trim().into()))), "{arg}");
+        }
+        assert_eq!(parse_target_path("Ch[ NOPE ]", &consts()), Err(at(4, ScriptError::ArgConst("NOPE".into()))));
+    }
+
+    // Elements and fields selected from arrays of structures or values,
+    // with the path having to match the definition, and the offsets
+    // that would not fit reported the same as any mismatch
+    #[test]
+    fn selection() {
+        let layout = parse_layout("Timing { tCL:0 tRAS:2(2) }", &[]).unwrap();
+        let path = |index: Option<usize>, field: Option<&str>| TargetPath { index, field: field.map(str::to_owned) };
+        let array = |path: TargetPath| path.select(0x100, 1, Some(4), Some(&layout));
+        assert_eq!(array(path(Some(0), Some("tCL"))), Some((0x100, 1)));
+        assert_eq!(array(path(Some(3), Some("tRAS"))), Some((0x10e, 2)));
+        for path in [path(Some(4), Some("tCL")), path(None, Some("tCL")), path(Some(1), None),
+            path(Some(1), Some("tRP"))] {
+            assert_eq!(array(path.clone()), None, "{path}");
+        }
+        assert_eq!(path(Some(2), None).select(0x100, 2, Some(4), None), Some((0x104, 2)));
+        assert_eq!(path(None, Some("tRAS")).select(0x100, 1, None, Some(&layout)), Some((0x102, 2)));
+        assert_eq!(path(None, None).select(0x100, 4, None, None), Some((0x100, 4)));
+        assert_eq!(path(Some(0), None).select(0x100, 4, None, None), None);
+        assert_eq!(path(Some(usize::MAX - 1), None).select(0, 2, Some(usize::MAX), None), None);
+        assert_eq!(path(Some(1), None).select(usize::MAX - 1, 2, Some(2), None), None);
+        assert_eq!(path(None, Some("tRAS")).select(usize::MAX, 1, None, Some(&layout)), None);
+    }
+
+}
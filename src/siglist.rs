@@ -0,0 +1,650 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: Signature List
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Decodes the Secure Boot signature databases (EFI_SIGNATURE_LIST),
+// including the subjects and issuers of the X.509 certificates within,
+// as well as the authenticated updates to them (EFI_VARIABLE_AUTHENTICATION_2)
+
+// Note: this module operates on plain bytes only and has no dependencies
+// on the UEFI environment, so that it can also be used on a host
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+// Symbols from other modules
+#[cfg(feature = "host")]
+use crate::config;
+use crate::config::locale as msg;
+use crate::guid::{format_guid, guid};
+
+// Signature list header: type GUID, list size, header size and signature size
+const LIST_HEADER_SIZE: usize = 28;
+
+// Each signature begins with the GUID of its owner
+const OWNER_SIZE: usize = 16;
+
+// Authentication header: timestamp, then the certificate length, revision and type
+const AUTH_HEADER_SIZE: usize = 24;
+
+// Authentication certificate revision and type (WIN_CERT_TYPE_EFI_GUID)
+const AUTH_CERT_REVISION: u16 = 0x0200;
+const AUTH_CERT_TYPE_GUID: u16 = 0x0EF1;
+
+// Authentication certificate GUID type: PKCS #7 signed data (EFI_CERT_TYPE_PKCS7_GUID)
+const AUTH_CERT_PKCS7: [u8; 16] =
+    guid(0x4AAFD29D, 0x68DF, 0x49EE, [0x8A, 0xA9, 0x34, 0x7D, 0x37, 0x56, 0x65, 0xA7]);
+
+// Size of the timestamp (EFI_TIME)
+const TIMESTAMP_SIZE: usize = 16;
+
+// Attributes of the signature databases, which are covered by the signature:
+// non-volatile, boot service and runtime access, time-based authenticated write
+#[cfg(feature = "host")]
+pub const AUTH_ATTRIBUTES: u32 = 0x00000027;
+#[cfg(feature = "host")]
+pub const AUTH_ATTRIBUTE_APPEND: u32 = 0x00000040;  // Append to the contents
+
+// Vendor GUIDs of the signature databases
+#[cfg(feature = "host")]
+const VENDOR_GLOBAL: [u8; 16] =  // PK, KEK (EFI_GLOBAL_VARIABLE)
+    guid(0x8BE4DF61, 0x93CA, 0x11D2, [0xAA, 0x0D, 0x00, 0xE0, 0x98, 0x03, 0x2B, 0x8C]);
+#[cfg(feature = "host")]
+const VENDOR_IMAGE_SECURITY: [u8; 16] =  // db, dbx, dbt (EFI_IMAGE_SECURITY_DATABASE)
+    guid(0xD719B2CB, 0x3D3A, 0x4596, [0xA3, 0xBC, 0xDA, 0xD0, 0x0E, 0x67, 0x65, 0x6F]);
+
+// Signature types that can be built
+pub const TYPE_SHA256: [u8; 16] =
+    guid(0xC1C41626, 0x504C, 0x4092, [0xAC, 0xA9, 0x41, 0xF9, 0x36, 0x93, 0x43, 0x28]);
+pub const TYPE_X509: [u8; 16] =
+    guid(0xA5C059A1, 0x94E4, 0x4AA7, [0x87, 0xB5, 0xAB, 0x15, 0x5C, 0x2B, 0xF0, 0x72]);
+
+// Signature kind
+// How the data of each signature is to be interpreted
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SignatureKind {
+
+    Certificate,  // DER-encoded X.509 certificate
+    Hash(usize),  // Hash of a given size, possibly followed by more data
+    Key,          // Public key
+
+}
+
+// Known signature types: GUID, name and kind
+const SIGNATURE_TYPES: [([u8; 16], &str, SignatureKind); 10] = [
+    (guid(0x826CA512, 0xCF10, 0x4AC9, [0xB1, 0x87, 0xBE, 0x01, 0x49, 0x66, 0x31, 0xBD]),
+        "SHA-1", SignatureKind::Hash(20)),
+    (guid(0x0B6E5233, 0xA65C, 0x44C9, [0x94, 0x07, 0xD9, 0xAB, 0x83, 0xBF, 0xC8, 0xBD]),
+        "SHA-224", SignatureKind::Hash(28)),
+    (TYPE_SHA256, "SHA-256", SignatureKind::Hash(32)),
+    (guid(0xFF3E5307, 0x9FD0, 0x48C9, [0x85, 0xF1, 0x8A, 0xD5, 0x6C, 0x70, 0x1E, 0x01]),
+        "SHA-384", SignatureKind::Hash(48)),
+    (guid(0x093E0FAE, 0xA6C4, 0x4F50, [0x9F, 0x1B, 0xD4, 0x1E, 0x2B, 0x89, 0xC1, 0x9A]),
+        "SHA-512", SignatureKind::Hash(64)),
+    (guid(0x3C5766E8, 0x269C, 0x4E34, [0xAA, 0x14, 0xED, 0x77, 0x6E, 0x85, 0xB3, 0xB6]),
+        "RSA-2048", SignatureKind::Key),
+    (TYPE_X509, "X.509", SignatureKind::Certificate),
+    (guid(0x3BD2A492, 0x96C0, 0x4079, [0xB4, 0x20, 0xFC, 0xF9, 0x8E, 0xF1, 0x03, 0xED]),
+        "X.509 SHA-256", SignatureKind::Hash(32)),
+    (guid(0x7076876E, 0x80C2, 0x4EE6, [0xAA, 0xD2, 0x28, 0xB3, 0x49, 0xA6, 0x86, 0x5B]),
+        "X.509 SHA-384", SignatureKind::Hash(48)),
+    (guid(0x446DBF63, 0x2502, 0x4CDA, [0xBC, 0xFA, 0x24, 0x65, 0xD2, 0xB0, 0xFE, 0x9D]),
+        "X.509 SHA-512", SignatureKind::Hash(64)),
+];
+
+// DER tags used in certificates
+const DER_TAG_INTEGER:    u8 = 0x02;
+const DER_TAG_SEQUENCE:   u8 = 0x30;
+const DER_TAG_VERSION:    u8 = 0xA0;  // Explicit context-specific tag [0]
+const DER_TAG_BMP_STRING: u8 = 0x1E;  // UCS-2, big-endian
+
+// Names of the common attributes in distinguished names, by encoded OID
+const NAME_ATTRIBUTES: [(&[u8], &str); 8] = [
+    (&[0x55, 0x04, 0x03], "CN"),  // Common name
+    (&[0x55, 0x04, 0x05], "SN"),  // Serial number
+    (&[0x55, 0x04, 0x06], "C"),   // Country
+    (&[0x55, 0x04, 0x07], "L"),   // Locality
+    (&[0x55, 0x04, 0x08], "ST"),  // State or province
+    (&[0x55, 0x04, 0x0A], "O"),   // Organization
+    (&[0x55, 0x04, 0x0B], "OU"),  // Organizational unit
+    (&[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x01], "E"),  // E-mail address
+];
+
+// Signature
+// A single entry within a signature list (EFI_SIGNATURE_DATA)
+
+#[derive(Clone, Debug, Default)]
+pub struct Signature {
+    pub owner: [u8; 16],  // GUID of the agent that added it
+    pub data: Vec<u8>,    // Certificate, hash or key
+}
+
+// Signature list
+// A set of signatures of the same type and size (EFI_SIGNATURE_LIST)
+
+#[derive(Clone, Debug, Default)]
+pub struct SignatureList {
+    pub kind: [u8; 16],              // Signature type GUID
+    pub signatures: Vec<Signature>,  // Entries
+}
+
+// Implementation
+impl SignatureList {
+
+    // Decodes all the signature lists stored back to back in a variable,
+    // returns nothing if any of them is malformed or runs out of bounds
+    pub fn parse_all(mut bytes: &[u8]) -> Option<Vec<Self>> {
+
+        let mut lists = Vec::new();
+        while !bytes.is_empty() {
+
+            // Fixed-size header
+            let kind = bytes.get(0 .. 16)?.try_into().ok()?;
+            let list_size = read_u32(bytes, 16)? as usize;
+            let header_size = read_u32(bytes, 20)? as usize;
+            let signature_size = read_u32(bytes, 24)? as usize;
+
+            // Type-specific header, usually empty and not used,
+            // then the signatures filling the rest of the list
+            let list = bytes.get(0 .. list_size)?;
+            let body = list.get(LIST_HEADER_SIZE.checked_add(header_size)? ..)?;
+            if signature_size < OWNER_SIZE || body.len() % signature_size != 0 {
+                return None;
+            }
+
+            // Each signature begins with its owner
+            let signatures = body.chunks_exact(signature_size).map(|s| Signature {
+                owner: s[0 .. OWNER_SIZE].try_into().unwrap(),
+                data: s[OWNER_SIZE ..].to_vec() }).collect();
+
+            // Move on to the next list
+            lists.push(Self { kind, signatures });
+            bytes = &bytes[list_size ..];
+
+        }
+
+        // Return the result
+        Some(lists)
+
+    }
+
+    // Encodes the signature list, all the signatures must be of the same size
+    #[cfg(feature = "host")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+
+        // Sizes
+        let signature_size = OWNER_SIZE + self.signatures.first().map_or(0, |s| s.data.len());
+        let list_size = LIST_HEADER_SIZE + signature_size * self.signatures.len();
+
+        // Header, without the type-specific part
+        let mut bytes = self.kind.to_vec();
+        bytes.extend_from_slice(&(list_size as u32).to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&(signature_size as u32).to_le_bytes());
+
+        // Signatures, each with its owner first
+        for signature in &self.signatures {
+            bytes.extend_from_slice(&signature.owner);
+            bytes.extend_from_slice(&signature.data);
+        }
+
+        // Return the result
+        bytes
+
+    }
+
+    // Name and kind of the signature type, if known
+    pub fn signature_type(&self) -> Option<(&'static str, SignatureKind)> {
+        SIGNATURE_TYPES.iter()
+            .find(|(guid, _, _)| *guid == self.kind)
+            .map(|&(_, name, kind)| (name, kind))
+    }
+
+}
+
+// Certificate
+// Identification of an X.509 certificate
+
+#[derive(Clone, Debug, Default)]
+pub struct Certificate {
+    pub issuer: String,   // Distinguished name of the issuer
+    pub serial: Vec<u8>,  // Serial number, big-endian
+    pub subject: String,  // Distinguished name of the subject
+}
+
+// Implementation
+impl Certificate {
+
+    // Decodes the identification from a DER-encoded certificate,
+    // returns nothing if the structure is not as expected
+    pub fn from_der(bytes: &[u8]) -> Option<Self> {
+
+        // Certificate, then the part to be signed within
+        let (DER_TAG_SEQUENCE, certificate, _) = read_der(bytes)? else { return None };
+        let (DER_TAG_SEQUENCE, mut tbs, _) = read_der(certificate)? else { return None };
+
+        // Optional version
+        if let (DER_TAG_VERSION, _, rest) = read_der(tbs)? {
+            tbs = rest;
+        }
+
+        // Serial number and signature algorithm
+        let (DER_TAG_INTEGER, serial, tbs) = read_der(tbs)? else { return None };
+        let (_, _, tbs) = read_der(tbs)?;
+
+        // Issuer, validity and subject
+        let (DER_TAG_SEQUENCE, issuer, tbs) = read_der(tbs)? else { return None };
+        let (_, _, tbs) = read_der(tbs)?;
+        let (DER_TAG_SEQUENCE, subject, _) = read_der(tbs)? else { return None };
+
+        // Return the result
+        Some(Self {
+            issuer: format_name(issuer)?,
+            serial: serial.to_vec(),
+            subject: format_name(subject)? })
+
+    }
+
+}
+
+// Timestamp
+// Date and time of an authenticated update (EFI_TIME), the time zone
+// and daylight saving fields being always zero, as they must be
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Timestamp {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+// Implementation
+impl Timestamp {
+
+    // Decodes the timestamp from its stored form
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(0 .. TIMESTAMP_SIZE)?;
+        Some(Self { year: u16::from_le_bytes([bytes[0], bytes[1]]),
+            month: bytes[2], day: bytes[3], hour: bytes[4], minute: bytes[5], second: bytes[6] })
+    }
+
+    // Parses user input such as "2024-03-05" or "2024-03-05T10:20:30",
+    // with the time being midnight if omitted
+    #[cfg(feature = "host")]
+    pub fn parse(string: &str) -> Option<Self> {
+
+        // Split into the date and the time
+        let (date, time) = string.split_once(['T', ' ']).unwrap_or((string, "00:00:00"));
+
+        // Each consists of three numbers
+        let numbers = |s: &str, separator| s.split(separator)
+            .map(|n| n.parse::<u16>().ok()).collect::<Option<Vec<_>>>()
+            .filter(|n| n.len() == 3);
+        let date = numbers(date, '-')?;
+        let time = numbers(time, ':')?;
+
+        // Check the ranges
+        if !(1 ..= 12).contains(&date[1]) || !(1 ..= 31).contains(&date[2])
+            || time[0] > 23 || time[1] > 59 || time[2] > 59 {
+            return None;
+        }
+
+        // Return the result
+        Some(Self { year: date[0], month: date[1] as u8, day: date[2] as u8,
+            hour: time[0] as u8, minute: time[1] as u8, second: time[2] as u8 })
+
+    }
+
+    // Encodes the timestamp in its stored form
+    #[cfg(feature = "host")]
+    pub fn to_bytes(self) -> [u8; TIMESTAMP_SIZE] {
+        let mut bytes = [0; TIMESTAMP_SIZE];
+        bytes[0 .. 2].copy_from_slice(&self.year.to_le_bytes());
+        bytes[2 .. 7].copy_from_slice(&[self.month, self.day, self.hour, self.minute, self.second]);
+        bytes
+    }
+
+}
+
+// Implementation: formatting for display
+impl Display for Timestamp {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second)
+    }
+
+}
+
+// Authenticated update
+// Signed payload to be written to a signature database as it is,
+// consisting of the authentication header followed by the new contents
+
+#[derive(Clone, Debug, Default)]
+pub struct AuthenticatedUpdate<'a> {
+    pub timestamp: Timestamp,   // Must be later than that of the previous update
+    pub signature: &'a [u8],    // PKCS #7 signed data
+    pub content: &'a [u8],      // Signature lists, possibly none
+}
+
+// Implementation
+impl<'a> AuthenticatedUpdate<'a> {
+
+    // Decodes the authentication header (EFI_VARIABLE_AUTHENTICATION_2),
+    // such as of the .auth files produced by sign-efi-sig-list, returns
+    // nothing if it is malformed, or the signature is not PKCS #7 signed data
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+
+        // Timestamp
+        let timestamp = Timestamp::from_bytes(bytes)?;
+
+        // Certificate header, the length covers the certificate as a whole
+        let length = read_u32(bytes, TIMESTAMP_SIZE)? as usize;
+        let revision = u16::from_le_bytes(bytes.get(20 .. 22)?.try_into().ok()?);
+        let kind = u16::from_le_bytes(bytes.get(22 .. 24)?.try_into().ok()?);
+        if revision != AUTH_CERT_REVISION || kind != AUTH_CERT_TYPE_GUID {
+            return None;
+        }
+
+        // Certificate type, then the signature filling the rest of the certificate
+        let end = TIMESTAMP_SIZE.checked_add(length)?;
+        if bytes.get(AUTH_HEADER_SIZE .. AUTH_HEADER_SIZE + 16)? != AUTH_CERT_PKCS7 {
+            return None;
+        }
+        let signature = bytes.get(AUTH_HEADER_SIZE + 16 .. end)?;
+
+        // The contents follow
+        Some(Self { timestamp, signature, content: &bytes[end ..] })
+
+    }
+
+    // Encodes the authentication header followed by the contents
+    #[cfg(feature = "host")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+
+        // Timestamp, then the certificate header
+        let mut bytes = self.timestamp.to_bytes().to_vec();
+        let length = AUTH_HEADER_SIZE - TIMESTAMP_SIZE + AUTH_CERT_PKCS7.len() + self.signature.len();
+        bytes.extend_from_slice(&(length as u32).to_le_bytes());
+        bytes.extend_from_slice(&AUTH_CERT_REVISION.to_le_bytes());
+        bytes.extend_from_slice(&AUTH_CERT_TYPE_GUID.to_le_bytes());
+
+        // Certificate type and the signature, then the contents
+        bytes.extend_from_slice(&AUTH_CERT_PKCS7);
+        bytes.extend_from_slice(self.signature);
+        bytes.extend_from_slice(self.content);
+
+        // Return the result
+        bytes
+
+    }
+
+}
+
+// Public Functions
+
+// Describes the contents of a signature database as lines of text,
+// a summary with the number of signatures first, followed by an indented
+// entry for each signature with its type, owner and the relevant details
+pub fn describe_signature_database(name: &str, bytes: &[u8]) -> Vec<String> {
+
+    // Decode the lists, which might fail
+    let Some(lists) = SignatureList::parse_all(bytes) else {
+        return Vec::from([format!("{name}: {}", msg::SIGLIST_MALFORMED)]);
+    };
+
+    // Summary
+    let count = lists.iter().map(|l| l.signatures.len()).sum::<usize>();
+    let mut lines = Vec::from([format!("{name}: {count} {}", msg::SIGLIST_SIGNATURES)]);
+
+    // Each signature of each list
+    for list in &lists {
+        for signature in &list.signatures {
+
+            // Type and owner, then the details depending on the type
+            let owner = format_guid(&signature.owner);
+            match list.signature_type() {
+
+                // Certificate: subject and issuer
+                Some((type_name, SignatureKind::Certificate)) => {
+                    lines.push(format!("  {type_name} {{{owner}}}"));
+                    match Certificate::from_der(&signature.data) {
+                        Some(certificate) => {
+                            lines.push(format!("    {}: {}", msg::SIGLIST_SUBJECT, certificate.subject));
+                            lines.push(format!("    {}: {}", msg::SIGLIST_ISSUER, certificate.issuer));
+                            lines.push(format!("    {}: {}", msg::SIGLIST_SERIAL, format_hex(&certificate.serial)));
+                        }
+                        None => lines.push(format!("    {}", msg::SIGLIST_CERT_MALFORMED)),
+                    }
+                }
+
+                // Hash: the value, leaving out any data that follows,
+                // such as the time of revocation for certificate hashes
+                Some((type_name, SignatureKind::Hash(size))) => {
+                    let hash = &signature.data[0 .. size.min(signature.data.len())];
+                    lines.push(format!("  {type_name} {{{owner}}} {}", format_hex(hash)));
+                }
+
+                // Key: size only
+                Some((type_name, SignatureKind::Key)) => {
+                    lines.push(format!("  {type_name} {{{owner}}} {} {}",
+                        signature.data.len(), msg::SIGLIST_BYTES));
+                }
+
+                // Unknown type: its GUID and the size
+                None => {
+                    lines.push(format!("  {{{}}} {{{owner}}} {} {}",
+                        format_guid(&list.kind), signature.data.len(), msg::SIGLIST_BYTES));
+                }
+
+            }
+
+        }
+    }
+
+    // Return the result
+    lines
+
+}
+
+// Determines the vendor GUID of a signature database by its name
+#[cfg(feature = "host")]
+pub fn database_vendor(name: &str) -> [u8; 16] {
+    match name {
+        config::VAR_SIGLIST_DB | config::VAR_SIGLIST_DBX | config::VAR_SIGLIST_DBT =>
+            VENDOR_IMAGE_SECURITY,
+        _ => VENDOR_GLOBAL,
+    }
+}
+
+// Reads a DER element at the start of the bytes, returning its tag,
+// the contents and the bytes remaining after it
+pub fn read_der(bytes: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+
+    // Tag, then the length in either the short or the long form
+    let tag = *bytes.first()?;
+    let first = *bytes.get(1)? as usize;
+    let (length, start) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let count = first & 0x7F;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        (bytes.get(2 .. 2 + count)?.iter().fold(0, |l, &b| l << 8 | b as usize), 2 + count)
+    };
+
+    // Contents and the rest
+    let end = start.checked_add(length)?;
+    Some((tag, bytes.get(start .. end)?, &bytes[end ..]))
+
+}
+
+// Assembles the data covered by the signature of an authenticated update:
+// the variable name (without the terminating null), the vendor GUID,
+// the attributes, the timestamp and the contents
+#[cfg(feature = "host")]
+pub fn signed_data(name: &str, attributes: u32,
+    timestamp: &Timestamp, content: &[u8]) -> Vec<u8> {
+    let mut bytes = name.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>();
+    bytes.extend_from_slice(&database_vendor(name));
+    bytes.extend_from_slice(&attributes.to_le_bytes());
+    bytes.extend_from_slice(&timestamp.to_bytes());
+    bytes.extend_from_slice(content);
+    bytes
+}
+
+// Private Functions
+
+// Formats bytes as contiguous uppercase hexadecimal digits
+fn format_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+// Formats a distinguished name, such as "C=US, O=Example, CN=Example CA",
+// using the short names of the common attributes, and the dotted OIDs otherwise
+fn format_name(mut name: &[u8]) -> Option<String> {
+
+    // A sequence of sets, each with one or more attributes
+    let mut parts = Vec::new();
+    while !name.is_empty() {
+        let (_, mut set, rest) = read_der(name)?;
+        name = rest;
+        while !set.is_empty() {
+
+            // Each attribute is a sequence of the OID and the value
+            let (_, attribute, rest) = read_der(set)?;
+            set = rest;
+            let (_, oid, attribute) = read_der(attribute)?;
+            let (tag, value, _) = read_der(attribute)?;
+
+            // Key and value
+            let key = NAME_ATTRIBUTES.iter()
+                .find(|(attribute_oid, _)| *attribute_oid == oid)
+                .map(|(_, key)| (*key).to_owned())
+                .unwrap_or_else(|| format_oid(oid));
+            parts.push(format!("{key}={}", decode_der_string(tag, value)));
+
+        }
+    }
+
+    // Return the result
+    Some(parts.join(", "))
+
+}
+
+// Formats an encoded OID in the dotted notation
+fn format_oid(oid: &[u8]) -> String {
+
+    // The first byte holds the first two components
+    let mut parts = match oid.first() {
+        Some(&first) => Vec::from([format!("{}.{}", first / 40, first % 40)]),
+        None => return String::new(),
+    };
+
+    // The others are in base 128, with the top bit set on all but the last byte
+    let mut value = 0u64;
+    for &byte in &oid[1 ..] {
+        value = value << 7 | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            parts.push(format!("{value}"));
+            value = 0;
+        }
+    }
+
+    // Return the result
+    parts.join(".")
+
+}
+
+// Decodes a DER string value, all types other than the
+// UCS-2 one are treated as UTF-8, which is a superset of ASCII
+fn decode_der_string(tag: u8, value: &[u8]) -> String {
+    if tag == DER_TAG_BMP_STRING {
+        char::decode_utf16(value.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])))
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+    } else {
+        String::from_utf8_lossy(value).into_owned()
+    }
+}
+
+// Reads a little-endian 32-bit value at a given offset
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(offset .. offset + 4)?.try_into().ok()?))
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Constructed databases, see fixtures/README.md
+    const DB: &[u8] = include_bytes!("../fixtures/siglist/db.bin");
+    const DBX: &[u8] = include_bytes!("../fixtures/siglist/dbx.bin");
+
+    // Decodes the lists of both kinds, and encodes them back the same
+    #[test]
+    fn lists() {
+
+        let lists = SignatureList::parse_all(DB).unwrap();
+        let kinds = lists.iter().map(|l| (l.kind, l.signatures.len())).collect::<Vec<_>>();
+        assert_eq!(kinds, [(TYPE_X509, 1), (TYPE_SHA256, 2)]);
+        assert_eq!(lists[0].signature_type(), Some(("X.509", SignatureKind::Certificate)));
+        assert_eq!(lists[1].signature_type(), Some(("SHA-256", SignatureKind::Hash(32))));
+
+        // Encoded back to the same bytes
+        assert_eq!(lists.iter().flat_map(SignatureList::to_bytes).collect::<Vec<_>>(), DB);
+
+        // Certificate identification
+        let certificate = Certificate::from_der(&lists[0].signatures[0].data).unwrap();
+        assert_eq!(certificate.subject, "CN=UVT Test db, O=UVT Fixtures");
+        assert_eq!(certificate.issuer, certificate.subject);
+        assert_eq!(certificate.serial, [0x12, 0x34]);
+
+    }
+
+    // Describes each signature with its owner and details
+    #[test]
+    fn describe() {
+
+        assert_eq!(describe_signature_database("db", DB), [
+            "db: 3 signature(s)",
+            "  X.509 {77FA9ABD-0359-4D32-BD60-28F4E78F784B}",
+            "    Subject: CN=UVT Test db, O=UVT Fixtures",
+            "    Issuer: CN=UVT Test db, O=UVT Fixtures",
+            "    Serial: 1234",
+            "  SHA-256 {77FA9ABD-0359-4D32-BD60-28F4E78F784B} \
+                3DB6A77098754AE4BF2212893E7613C5DBBDDBBA2FB7C5E0F3E3A1C306F8C7A9",
+            "  SHA-256 {77FA9ABD-0359-4D32-BD60-28F4E78F784B} \
+                DF6CAE1AB93CFBB49E885B7CC914EE4559F2997EDDE1CEA92A5A1CB810FB5D0E"]);
+
+        assert_eq!(describe_signature_database("dbx", DBX), [
+            "dbx: 1 signature(s)",
+            "  SHA-256 {77FA9ABD-0359-4D32-BD60-28F4E78F784B} \
+                6F050FC31794AE2D8255CD03BBC7FF8A4F6609E11FE44DE09C6D1BEBAE153195"]);
+
+    }
+
+    // Rejects a list running out of bounds, and tolerates a malformed certificate
+    #[test]
+    fn malformed() {
+
+        assert!(SignatureList::parse_all(&DB[.. DB.len() - 1]).is_none());
+        assert_eq!(describe_signature_database("db", &DB[.. 20]), ["db: (malformed signature list)"]);
+
+        // Certificate cut short, with the sizes adjusted to match
+        let mut list = SignatureList::parse_all(DB).unwrap().swap_remove(0);
+        list.signatures[0].data.truncate(40);
+        assert_eq!(describe_signature_database("db", &list.to_bytes())[2], "    (malformed certificate)");
+
+    }
+
+}
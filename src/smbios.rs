@@ -0,0 +1,321 @@
+//           -|-
+//  |   ||   /|   UEFI Variable Tool (UVT) * Module: SMBIOS
+//  |   ||  / |   https://github.com/GeographicCone/UefiVarTool
+//  `---'`-'  `-  Copyright © 2022 Datasone, © 2023 Piotr Szczepański
+
+// Reads the board identification from the SMBIOS tables: the manufacturer
+// and the product name of the system, the product name of the baseboard,
+// and the version and the release date of the BIOS
+
+// Note: this module operates on plain bytes only and has no dependencies
+// on the UEFI environment, so that it can also be used on a host
+
+// Declare fully-qualified symbols to be used in the local scope
+use alloc::{string::{String, ToString}, vec::Vec};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+// Symbols from other modules
+use crate::config::locale as msg;
+
+// Entry point anchors: 32-bit (SMBIOS 2.x) and 64-bit (SMBIOS 3.x)
+const ANCHOR_32: &[u8] = b"_SM_";
+const ANCHOR_64: &[u8] = b"_SM3_";
+
+// Offsets within the 32-bit entry point: its length,
+// then the structure table length and address
+const ENTRY_32_LENGTH:        usize = 0x05;
+const ENTRY_32_TABLE_LENGTH:  usize = 0x16;
+const ENTRY_32_TABLE_ADDRESS: usize = 0x18;
+
+// Offsets within the 64-bit entry point: its length,
+// then the structure table maximum size and address
+const ENTRY_64_LENGTH:        usize = 0x06;
+const ENTRY_64_TABLE_SIZE:    usize = 0x0C;
+const ENTRY_64_TABLE_ADDRESS: usize = 0x10;
+
+// Bytes needed to tell the kind and the length of an entry point
+pub const ENTRY_HEADER_SIZE: usize = ENTRY_64_LENGTH + 1;
+
+// Structure header: type, length and handle
+const STRUCTURE_HEADER_SIZE: usize = 4;
+
+// Structure types
+const TYPE_BIOS:      u8 = 0;    // BIOS information
+const TYPE_SYSTEM:    u8 = 1;    // System information
+const TYPE_BASEBOARD: u8 = 2;    // Baseboard (module) information
+const TYPE_END:       u8 = 127;  // End of the table
+
+// Offsets of the string numbers within the structures
+const BIOS_VERSION:        usize = 0x05;
+const BIOS_DATE:           usize = 0x08;
+const SYSTEM_MANUFACTURER: usize = 0x04;
+const SYSTEM_PRODUCT:      usize = 0x05;
+const BASEBOARD_PRODUCT:   usize = 0x05;
+
+// Entry point
+// Where the structure table is, and how long it is (at most)
+
+#[derive(Clone, Copy, Debug)]
+pub struct EntryPoint {
+    pub address: u64,   // Physical address of the structure table
+    pub length: usize,  // Length, or the maximum size for SMBIOS 3.x
+}
+
+// Implementation
+impl EntryPoint {
+
+    // Determines the length of the entry point from its first bytes,
+    // returns nothing if neither of the anchors is there
+    pub fn length(header: &[u8]) -> Option<usize> {
+        if header.starts_with(ANCHOR_64) {
+            header.get(ENTRY_64_LENGTH).map(|&length| length as usize)
+        } else if header.starts_with(ANCHOR_32) {
+            header.get(ENTRY_32_LENGTH).map(|&length| length as usize)
+        } else {
+            None
+        }
+    }
+
+    // Parses an entry point, either 32-bit or 64-bit, checking that
+    // its bytes add up to zero, returns nothing if it is malformed
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+
+        // Take the whole entry point, and verify the checksum
+        let bytes = bytes.get(.. Self::length(bytes)?)?;
+        if bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0 {
+            return None;
+        }
+
+        // Retrieve the structure table location
+        if bytes.starts_with(ANCHOR_64) {
+            Some(Self {
+                address: u64::from_le_bytes(bytes.get(ENTRY_64_TABLE_ADDRESS .. ENTRY_64_TABLE_ADDRESS + 8)?
+                    .try_into().ok()?),
+                length: u32::from_le_bytes(bytes.get(ENTRY_64_TABLE_SIZE .. ENTRY_64_TABLE_SIZE + 4)?
+                    .try_into().ok()?) as usize })
+        } else {
+            Some(Self {
+                address: u32::from_le_bytes(bytes.get(ENTRY_32_TABLE_ADDRESS .. ENTRY_32_TABLE_ADDRESS + 4)?
+                    .try_into().ok()?) as u64,
+                length: u16::from_le_bytes(bytes.get(ENTRY_32_TABLE_LENGTH .. ENTRY_32_TABLE_LENGTH + 2)?
+                    .try_into().ok()?) as usize })
+        }
+
+    }
+
+}
+
+// SMBIOS information
+// Identification of the board, empty where not given
+
+#[derive(Clone, Debug, Default)]
+pub struct SmbiosInfo {
+    pub bios_date: String,            // BIOS release date, such as 09/12/2023
+    pub bios_version: String,         // BIOS version, such as 1402
+    pub board_product: String,        // Baseboard product name, such as PRIME Z790-P
+    pub system_manufacturer: String,  // System manufacturer, such as ASUSTeK COMPUTER INC.
+    pub system_product: String,       // System product name
+}
+
+// Implementation
+impl SmbiosInfo {
+
+    // Parses the structure table, taking the first structure of each
+    // type, and stopping at the end of the table, or at a malformed one
+    pub fn parse(table: &[u8]) -> Self {
+
+        // Set up the information
+        let mut info = Self::default();
+
+        // Go through the structures
+        let mut offset = 0;
+        while let Some(header) = table.get(offset .. offset + STRUCTURE_HEADER_SIZE) {
+
+            // Each begins with the type and the length of its formatted part
+            let (kind, length) = (header[0], header[1] as usize);
+            if kind == TYPE_END || length < STRUCTURE_HEADER_SIZE {
+                break;
+            }
+            let Some(formatted) = table.get(offset .. offset + length) else {
+                break;
+            };
+
+            // The strings follow, each ending with a zero, with another one
+            // at the end of them, or two zeros if there are no strings at all
+            let Some(strings) = table.get(offset + length ..) else {
+                break;
+            };
+            let Some(end) = strings.windows(2).position(|pair| pair == [0, 0]) else {
+                break;
+            };
+            let strings = strings[.. end].split(|&byte| byte == 0).collect::<Vec<_>>();
+
+            // Retrieves a string by its number, from one, at an offset in the
+            // formatted part, zero meaning none, with the padding trimmed
+            let string = |at: usize| formatted.get(at)
+                .and_then(|&number| strings.get((number as usize).checked_sub(1)?))
+                .map(|string| String::from_utf8_lossy(string).trim().to_string())
+                .unwrap_or_default();
+
+            // Keep the strings from the first structure of each type
+            match kind {
+                TYPE_BIOS if info.bios_version.is_empty() && info.bios_date.is_empty() => {
+                    info.bios_version = string(BIOS_VERSION);
+                    info.bios_date = string(BIOS_DATE);
+                }
+                TYPE_SYSTEM if info.system_manufacturer.is_empty() && info.system_product.is_empty() => {
+                    info.system_manufacturer = string(SYSTEM_MANUFACTURER);
+                    info.system_product = string(SYSTEM_PRODUCT);
+                }
+                TYPE_BASEBOARD if info.board_product.is_empty() => {
+                    info.board_product = string(BASEBOARD_PRODUCT);
+                }
+                _ => (),
+            }
+
+            // Move on to the next structure
+            offset += length + end + 2;
+
+        }
+
+        // Return the information
+        info
+
+    }
+
+    // Checks if none of the identification was found
+    pub fn is_empty(&self) -> bool {
+        [&self.bios_date, &self.bios_version, &self.board_product,
+            &self.system_manufacturer, &self.system_product].iter().all(|field| field.is_empty())
+    }
+
+    // Parses a captured table, either a structure table alone, as read
+    // from /sys/firmware/dmi/tables/DMI, or preceded by the entry point,
+    // as written by dmidecode --dump-bin, with the address of the table
+    // within the file then
+    #[cfg(feature = "host")]
+    pub fn parse_captured(bytes: &[u8]) -> Self {
+        match EntryPoint::parse(bytes) {
+            Some(entry) => Self::parse(usize::try_from(entry.address).ok()
+                .and_then(|address| bytes.get(address ..))
+                .map(|table| &table[.. table.len().min(entry.length)]).unwrap_or_default()),
+            None => Self::parse(bytes),
+        }
+    }
+
+}
+
+// Implementation: formatting for display, as in the application header:
+// the manufacturer, the baseboard, or else the system product, and the BIOS
+impl Display for SmbiosInfo {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+
+        // Prefer the baseboard, which tells the boards apart,
+        // while the system product is often a placeholder
+        let product = if self.board_product.is_empty() { &self.system_product } else { &self.board_product };
+
+        // Leave out whatever is missing
+        let bios = if self.bios_version.is_empty() && self.bios_date.is_empty() { "" } else { msg::SMBIOS_BIOS };
+        let parts = [self.system_manufacturer.as_str(), product, bios, &self.bios_version, &self.bios_date];
+        write!(f, "{}", parts.iter().filter(|part| !part.is_empty()).copied().collect::<Vec<_>>().join(" "))
+
+    }
+
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Constructed tables preceded by the entry point, see fixtures/README.md
+    const SMBIOS_2: &[u8] = include_bytes!("../fixtures/smbios/smbios2.bin");
+    const SMBIOS_3: &[u8] = include_bytes!("../fixtures/smbios/smbios3.bin");
+
+    // Offset of the table after the entry point, in both files
+    const TABLE: usize = 0x20;
+
+    // Finds the table after either kind of entry point, checking the sum
+    #[test]
+    fn entry_points() {
+
+        assert_eq!(EntryPoint::length(SMBIOS_2), Some(0x1F));
+        assert_eq!(EntryPoint::length(SMBIOS_3), Some(0x18));
+        assert_eq!(EntryPoint::length(&SMBIOS_2[TABLE ..]), None);
+
+        // Address, and the length, or the maximum size for SMBIOS 3.x
+        let entry = EntryPoint::parse(SMBIOS_2).unwrap();
+        assert_eq!((entry.address, entry.length), (TABLE as u64, SMBIOS_2.len() - TABLE));
+        let entry = EntryPoint::parse(SMBIOS_3).unwrap();
+        assert_eq!((entry.address, entry.length), (TABLE as u64, 0x1000));
+
+        // Bytes not adding up to zero, or cut short
+        let mut bytes = SMBIOS_3.to_vec();
+        bytes[ENTRY_64_TABLE_ADDRESS] ^= 1;
+        assert!(EntryPoint::parse(&bytes).is_none());
+        assert!(EntryPoint::parse(&SMBIOS_2[.. 0x1E]).is_none());
+
+    }
+
+    // Takes the manufacturer, the board and the BIOS from an SMBIOS 2.x table,
+    // skipping a structure without strings, and trimming the padding
+    #[test]
+    fn smbios_2() {
+
+        let info = SmbiosInfo::parse_captured(SMBIOS_2);
+        assert_eq!(info.system_manufacturer, "ASUSTeK COMPUTER INC.");
+        assert_eq!(info.system_product, "System Product Name");
+        assert_eq!(info.board_product, "PRIME Z790-P");
+        assert_eq!(info.bios_version, "1402");
+        assert_eq!(info.bios_date, "09/12/2023");
+        assert_eq!(info.to_string(), "ASUSTeK COMPUTER INC. PRIME Z790-P BIOS 1402 09/12/2023");
+
+        // The same from the structure table alone
+        assert_eq!(SmbiosInfo::parse(&SMBIOS_2[TABLE ..]).to_string(), info.to_string());
+
+    }
+
+    // Takes the same from an SMBIOS 3.x table, after a structure of another type,
+    // with the product name given as string number zero, meaning none, and
+    // the second baseboard ignored, stopping at the end before the maximum size
+    #[test]
+    fn smbios_3() {
+
+        let info = SmbiosInfo::parse_captured(SMBIOS_3);
+        assert_eq!(info.system_manufacturer, "Micro-Star International Co., Ltd.");
+        assert_eq!(info.system_product, "");
+        assert_eq!(info.board_product, "MAG B650 TOMAHAWK WIFI (MS-7D75)");
+        assert_eq!(info.bios_version, "1.A0");
+        assert_eq!(info.bios_date, "05/23/2024");
+        assert_eq!(info.to_string(),
+            "Micro-Star International Co., Ltd. MAG B650 TOMAHAWK WIFI (MS-7D75) BIOS 1.A0 05/23/2024");
+
+    }
+
+    // Stops at a structure whose strings are not terminated, or that is cut short,
+    // keeping what came before it, and finds nothing in a table that is empty
+    #[test]
+    fn malformed() {
+
+        // Baseboard strings missing the terminator
+        let board = TABLE + 0xB6;
+        let info = SmbiosInfo::parse(&SMBIOS_3[TABLE .. board + 0x28]);
+        assert_eq!(info.system_manufacturer, "Micro-Star International Co., Ltd.");
+        assert_eq!(info.bios_version, "1.A0");
+        assert_eq!(info.board_product, "");
+        assert_eq!(info.to_string(), "Micro-Star International Co., Ltd. BIOS 1.A0 05/23/2024");
+
+        // Baseboard formatted part cut short
+        assert_eq!(SmbiosInfo::parse(&SMBIOS_3[TABLE .. board + 0x08]).board_product, "");
+
+        // Nothing at all
+        assert!(SmbiosInfo::parse(&[]).is_empty());
+        assert!(SmbiosInfo::parse(&[TYPE_END, 4, 0, 0, 0, 0]).is_empty());
+        assert_eq!(SmbiosInfo::default().to_string(), "");
+
+    }
+
+}
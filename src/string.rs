@@ -8,13 +8,13 @@
 
 // Declare fully-qualified symbols
 // to be used in the local scope
-use alloc::{string::ToString, vec::Vec};
+use alloc::{format, string::{String, ToString}, vec::Vec};
 use uefi::{Char16, CStr16, CString16, data_types::chars::NUL_16};
 
 // Symbols from other modules
 use crate::config;
+use crate::data::TextEncoding;
 use crate::config::locale as msg;
-use crate::error::AppError;
 
 // Converts a Char16 vector to CString16
 pub fn char16_vec_to_cstring16(string: Vec<Char16>) -> CString16 {
@@ -28,13 +28,176 @@ pub fn char16_vec_to_cstring16(string: Vec<Char16>) -> CString16 {
 
 }
 
-// Tries to retrieve the next char of a string
-pub fn try_next_char(
-    iter: &mut impl Iterator<Item = char>,
-    string: &CStr16) -> Result<char, AppError> {
+// Decodes the text of a script file stored as UTF-8 (including plain ASCII)
+// or UTF-16 (LE or BE), ignoring the Byte Order Mark (BOM) and carriage
+// returns, the same way as when reading a stream, returns nothing if the
+// text is malformed, or has characters the firmware cannot represent (UCS-2)
+pub fn decode_script(bytes: &[u8]) -> Option<CString16> {
 
-    // Error if no next char
-    iter.next().ok_or_else(|| AppError::ArgMore(string.to_string()))
+    // Determine the encoding
+    let (encoding, bytes) = detect_encoding(bytes);
+
+    // Decode the characters
+    let chars = match encoding {
+
+        // UTF-8 and ASCII
+        TextEncoding::Utf8 => core::str::from_utf8(bytes).ok()?.chars().collect::<Vec<_>>(),
+
+        // UTF-16, in which each unit takes two bytes
+        TextEncoding::Utf16Be | TextEncoding::Utf16Le => {
+            let units = bytes.chunks_exact(2);
+            if !units.remainder().is_empty() {
+                return None;
+            }
+            char::decode_utf16(units.map(|pair| match encoding {
+                TextEncoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+                _ => u16::from_le_bytes([pair[0], pair[1]]) }))
+                .collect::<Result<Vec<_>, _>>().ok()?
+        }
+
+    };
+
+    // Set up the string for the decoded text
+    let mut text = CString16::new();
+
+    // Add the characters one by one, skipping the ignored ones
+    for c in chars {
+        if [config::CHAR_CTL_BOM, config::CHAR_CTL_CR, '\0'].contains(&c) {
+            continue;
+        }
+        text.push(Char16::try_from(c).ok()?);
+    }
+
+    // Return
+    Some(text)
+
+}
+
+// Encodes a text to be written to a file, with the Byte Order Mark (BOM)
+// preceding it in UTF-16, where it is expected, but not in UTF-8
+pub fn encode_text(text: &str, encoding: TextEncoding) -> Vec<u8> {
+    match encoding {
+
+        // UTF-8 is the same as in memory
+        TextEncoding::Utf8 => text.as_bytes().to_vec(),
+
+        // UTF-16, with each unit taking two bytes
+        TextEncoding::Utf16Be => core::iter::once(config::CHAR_CTL_BOM as u16)
+            .chain(text.encode_utf16()).flat_map(u16::to_be_bytes).collect(),
+        TextEncoding::Utf16Le => core::iter::once(config::CHAR_CTL_BOM as u16)
+            .chain(text.encode_utf16()).flat_map(u16::to_le_bytes).collect(),
+
+    }
+}
+
+// Resolves a file path relative to the directory of the file given, if any,
+// unless it is already absolute, starting either with the path separator,
+// or with a file system name, such as "fs0:"
+pub fn resolve_relative_path(base: Option<&CStr16>, path: &CStr16) -> CString16 {
+
+    // Nothing to resolve against
+    let Some(base) = base else {
+        return CString16::from(path);
+    };
+
+    // Already absolute
+    let path = path.to_string();
+    if path.starts_with(config::CHAR_FILE_PATH) || path.contains(config::CHAR_FILE_FS) {
+        return CString16::try_from(path.as_str()).unwrap();
+    }
+
+    // Keep the base up to and including the last separator, if any
+    let base = base.to_string();
+    let directory = match base.rfind([config::CHAR_FILE_PATH, config::CHAR_FILE_FS]) {
+        Some(index) => &base[..= index],
+        None => "",
+    };
+
+    // Join the two
+    CString16::try_from(format!("{directory}{path}").as_str()).unwrap()
+
+}
+
+// Quotes a text as a JSON string, escaping the quotation mark,
+// the backslash and the control characters, the only ones required
+pub fn quote_json(text: &str) -> String {
+
+    // Start with the opening quotation mark
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+
+    // Escape the characters that need it
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    // Close the string
+    quoted.push('"');
+    quoted
+
+}
+
+// Determines the encoding of a text by its Byte Order Mark (BOM), returning
+// the text without it, or if absent, by where the zero bytes fall, as every
+// ASCII character takes one in UTF-16, either after (LE) or before it (BE)
+fn detect_encoding(bytes: &[u8]) -> (TextEncoding, &[u8]) {
+
+    // Look for the BOM first
+    for (bom, encoding) in [
+        (&[0xEF, 0xBB, 0xBF][..], TextEncoding::Utf8),
+        (&[0xFE, 0xFF][..], TextEncoding::Utf16Be),
+        (&[0xFF, 0xFE][..], TextEncoding::Utf16Le)] {
+        if let Some(bytes) = bytes.strip_prefix(bom) {
+            return (encoding, bytes);
+        }
+    }
+
+    // Count the zero bytes at the even and at the odd positions
+    let zeros = |parity| bytes.iter().skip(parity).step_by(2).filter(|&&b| b == 0).count();
+    let (even, odd) = (zeros(0), zeros(1));
+
+    // Zero bytes mostly after the characters mean LE, mostly
+    // before them BE, and none at all (or no clear pattern) UTF-8
+    let encoding = if odd > even {
+        TextEncoding::Utf16Le
+    } else if even > odd {
+        TextEncoding::Utf16Be
+    } else {
+        TextEncoding::Utf8
+    };
+
+    // Return the encoding, and the text as it is
+    (encoding, bytes)
+
+}
+
+// Formats bytes as a hexadecimal dump, with the offset at the start
+// of each line, followed by the bytes and their printable characters
+pub fn format_hex_dump(bytes: &[u8]) -> Vec<String> {
+
+    // Split into lines of a fixed length
+    bytes.chunks(config::DUMP_LINE_SIZE).enumerate().map(|(i, chunk)| {
+
+        // Bytes in hexadecimal, separated with spaces
+        let hex = chunk.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ");
+
+        // Printable ASCII characters, others shown as dots
+        let text = chunk.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' {
+            b as char } else { '.' }).collect::<String>();
+
+        // Pad the last line so that the characters are aligned
+        format!("{:#06x}  {hex:<width$}  {text}", i * config::DUMP_LINE_SIZE,
+            width = config::DUMP_LINE_SIZE * 3 - 1)
+
+    }).collect()
 
 }
 
@@ -43,6 +206,15 @@ pub fn try_next_char(
 #[macro_export]
 macro_rules! parse_multiple {
 
+    // Case with the offset where the input starts, moving the error
+    (@offset $offset:expr; $input:expr, $($parsers:expr),+ $(,)? ) => {{
+
+        // Call the parsers, and add the offset to the error, if any
+        parse_multiple!($input, $($parsers),+)
+            .map_err(|e: $crate::error::AppError| $crate::script::ErrorOffset::offset(e, $offset))
+
+    }};
+
     // Base case with just a single parser
     ($input:expr, $parser:expr) => {{
 
@@ -91,6 +263,10 @@ pub trait CStr16Ext {
     // Splits the string into parts separated by the given char
     fn split(&self, search: char) -> Vec<CString16>;
 
+    // Splits the string into parts separated by the given char,
+    // each with the offset where it starts, in characters
+    fn split_offsets(&self, search: char) -> Vec<(usize, CString16)>;
+
     // Splits the string into two parts separated by the given char
     fn split_once(&self, search: char) -> Option<(CString16, CString16)>;
 
@@ -106,6 +282,10 @@ pub trait CStr16Ext {
     // Removes whitespace on both ends
     fn trim(&self) -> CString16;
 
+    // Removes whitespace on both ends, with the offset
+    // where what is left starts, in characters
+    fn trim_offset(&self) -> (usize, CString16);
+
 }
 
 // Implementation on top of CStr16
@@ -162,15 +342,25 @@ impl CStr16Ext for CStr16 {
     // Splits the string into parts separated by the given char
     fn split(&self, search: char) -> Vec<CString16> {
 
+        // Drop the offsets
+        self.split_offsets(search).into_iter().map(|(_, part)| part).collect()
+
+    }
+
+    // Splits the string into parts separated by the given char,
+    // each with the offset where it starts, in characters
+    fn split_offsets(&self, search: char) -> Vec<(usize, CString16)> {
+
         // Convert the search character to match data type
         let search = Char16::try_from(search).unwrap();
 
         // Set up output and helper variables
         let mut split_strings = Vec::new();
         let mut current_string = Vec::new();
+        let mut current_start = 0;
 
         // Iterate through input string
-        for c in self.iter() {
+        for (i, c) in self.iter().enumerate() {
 
             // If not a split char
             if *c != search {
@@ -185,11 +375,12 @@ impl CStr16Ext for CStr16 {
                 current_string.push(0u16);
 
                 // Append the current string to the array
-                split_strings.push(current_string.try_into()
-                    .expect(msg::ERR_INT_SPLIT));
+                split_strings.push((current_start, current_string.try_into()
+                    .expect(msg::ERR_INT_SPLIT)));
 
-                // Reset the current string
-                current_string = Vec::new()
+                // Reset the current string, which starts past the split char
+                current_string = Vec::new();
+                current_start = i + 1;
 
             }
 
@@ -202,8 +393,8 @@ impl CStr16Ext for CStr16 {
             current_string.push(0u16);
 
             // Append the leftover string to the array
-            split_strings.push(current_string.try_into()
-                .expect(msg::ERR_INT_SPLIT));
+            split_strings.push((current_start, current_string.try_into()
+                .expect(msg::ERR_INT_SPLIT)));
 
         }
 
@@ -314,9 +505,18 @@ impl CStr16Ext for CStr16 {
     // Removes whitespace on both ends
     fn trim(&self) -> CString16 {
 
+        // Drop the offset
+        self.trim_offset().1
+
+    }
+
+    // Removes whitespace on both ends, with the offset
+    // where what is left starts, in characters
+    fn trim_offset(&self) -> (usize, CString16) {
+
         // Do not trim empty strings
         if self.is_empty() {
-            return CString16::new();
+            return (0, CString16::new());
         }
 
         // Define a way for determining whitespace chars
@@ -349,10 +549,10 @@ impl CStr16Ext for CStr16 {
         // Result
         if start > end {
             // Empty string
-            CString16::new()
+            (0, CString16::new())
         } else {
             // Actual substring
-            self.substring(start, end)
+            (start, self.substring(start, end))
         }
 
     }